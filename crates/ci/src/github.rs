@@ -0,0 +1,185 @@
+use anyhow::Result;
+use pre_commit_core::{ExecutionResult, HookResult, Severity};
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+/// `--format github`: annotate failed hooks with workflow commands and, when
+/// `$GITHUB_STEP_SUMMARY` is set, append a results table to the job summary
+pub fn report(result: &ExecutionResult) -> Result<()> {
+    for annotation in annotations(result) {
+        println!("{}", annotation);
+    }
+
+    if let Some(path) = env::var_os("GITHUB_STEP_SUMMARY") {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(summary(result).as_bytes())?;
+    }
+    Ok(())
+}
+
+/// One `::error` (or `::warning`, for warning-severity hooks) workflow command
+/// per failed hook, carrying its output
+pub fn annotations(result: &ExecutionResult) -> Vec<String> {
+    result
+        .hooks
+        .iter()
+        .filter(|hook| hook.is_failure())
+        .map(|hook| {
+            let command = match hook.severity {
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            };
+            format!(
+                "::{} title={}::{}",
+                command,
+                escape_property(&hook.hook_id),
+                escape_data(&failure_message(hook))
+            )
+        })
+        .collect()
+}
+
+/// The hook's stderr, else its stdout, else just its exit code
+fn failure_message(hook: &HookResult) -> String {
+    match (hook.visible_stderr(), hook.visible_stdout(), hook.exit_code) {
+        (Some(output), _, _) | (None, Some(output), _) => output.to_string(),
+        (None, None, Some(code)) => format!("exited with code {}", code),
+        (None, None, None) => "failed".to_string(),
+    }
+}
+
+/// Markdown table of every hook for the job summary
+pub fn summary(result: &ExecutionResult) -> String {
+    let mut markdown = String::from("## pre-commit results\n\n");
+    markdown.push_str("| | Hook | Duration | Details |\n|---|---|---|---|\n");
+
+    for hook in &result.hooks {
+        let (status, details) = match (&hook.skip_reason, hook.success, hook.severity) {
+            (Some(reason), _, _) => ("⏭️", reason.clone()),
+            (None, true, _) => ("✅", String::new()),
+            (None, false, severity) => {
+                let status = if severity == Severity::Warning {
+                    "⚠️"
+                } else {
+                    "❌"
+                };
+                let message = failure_message(hook);
+                (
+                    status,
+                    message.lines().next().unwrap_or_default().to_string(),
+                )
+            }
+        };
+        markdown.push_str(&format!(
+            "| {} | `{}` | {}ms | {} |\n",
+            status,
+            escape_cell(&hook.hook_id),
+            hook.duration_ms,
+            escape_cell(&details)
+        ));
+    }
+
+    let outcome = if result.all_passed {
+        "All hooks passed"
+    } else {
+        "Some hooks failed"
+    };
+    markdown.push_str(&format!(
+        "\n{} in {}ms.\n",
+        outcome, result.total_duration_ms
+    ));
+    markdown
+}
+
+/// Escape a workflow command message so newlines survive
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property such as `title`
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+fn escape_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook_result(id: &str, success: bool, stderr: &str) -> HookResult {
+        let mut result = HookResult::skipped(id, "unused");
+        result.skip_reason = None;
+        result.success = success;
+        result.exit_code = Some(if success { 0 } else { 1 });
+        result.stderr = stderr.to_string();
+        result
+    }
+
+    fn execution(hooks: Vec<HookResult>) -> ExecutionResult {
+        ExecutionResult {
+            all_passed: hooks.iter().all(|h| h.success),
+            levels: Vec::new(),
+            hooks,
+            total_duration_ms: 42,
+        }
+    }
+
+    #[test]
+    fn test_annotations_for_failures_only() {
+        let mut warning = hook_result("spelling", false, "");
+        warning.severity = Severity::Warning;
+        let result = execution(vec![
+            hook_result("fmt", true, "reformatted"),
+            hook_result(
+                "lint",
+                false,
+                "src/a.rs:1: unused\nsrc/b.rs:2: 100% wrong\n",
+            ),
+            HookResult::skipped("docs", "no files to check"),
+            warning,
+        ]);
+
+        assert_eq!(
+            annotations(&result),
+            vec![
+                "::error title=lint::src/a.rs:1: unused%0Asrc/b.rs:2: 100%25 wrong",
+                "::warning title=spelling::exited with code 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape_property() {
+        assert_eq!(escape_property("proj/a:b,c"), "proj/a%3Ab%2Cc");
+    }
+
+    #[test]
+    fn test_summary_lists_every_hook() {
+        let result = execution(vec![
+            hook_result("fmt", true, ""),
+            hook_result("lint", false, "bad | worse\nmore"),
+            HookResult::skipped("docs", "no files to check"),
+        ]);
+
+        let summary = summary(&result);
+        assert!(summary.contains("| ✅ | `fmt` | 0ms |  |"), "{}", summary);
+        assert!(
+            summary.contains("| ❌ | `lint` | 0ms | bad \\| worse |"),
+            "{}",
+            summary
+        );
+        assert!(
+            summary.contains("| ⏭️ | `docs` | 0ms | no files to check |"),
+            "{}",
+            summary
+        );
+        assert!(summary.ends_with("Some hooks failed in 42ms.\n"));
+    }
+}
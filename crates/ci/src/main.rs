@@ -1,17 +1,40 @@
+mod github;
+mod sarif;
+mod timing;
+
 use anyhow::Result;
 use clap::{Parser, ValueEnum};
-use pre_commit_core::{Executor, PlanBuilder};
+use owo_colors::{OwoColorize, Stream::Stdout};
+use pre_commit_core::command;
+use pre_commit_core::diagnostics::{parse_diagnostics, Diagnostic};
+use pre_commit_core::result_cache::ResultCache;
+use pre_commit_core::{
+    resolve_jobs, shell, ColorChoice, Config, ExecutionOptions, ExecutionPlan, ExecutionResult,
+    Executor, Hook, HookResult, PlanBuilder, Severity,
+};
 use pre_commit_dag::DagBuilder;
 use pre_commit_executor_parallel::ParallelExecutor;
 use pre_commit_executor_sync::SyncExecutor;
-use pre_commit_parser::{extract_hooks, parse_config_file, validate_config};
-use std::path::PathBuf;
+use pre_commit_files::{
+    expand_dirs, find_configs, glob_files, resolve_git_binary, resolve_repo_root, tracked_files,
+};
+use pre_commit_parser::{
+    dedup_dependencies, parse_config_file, parse_config_file_lenient, resolve_hooks,
+    select_profile, validate_config,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process;
 
 #[derive(Debug, Clone, ValueEnum)]
 enum OutputFormat {
     Json,
     Human,
+    /// Human output plus GitHub Actions annotations and a job summary
+    Github,
+    /// A SARIF 2.1.0 log with one result per failed hook, for code scanning
+    Sarif,
 }
 
 #[derive(Parser)]
@@ -30,71 +53,204 @@ struct Cli {
     #[arg(short = 'f', long, value_enum, default_value = "human")]
     format: OutputFormat,
 
-    /// Files to check (if not provided, checks all files in repo)
-    files: Vec<PathBuf>,
-}
+    /// Discover files matching this glob by walking the tree instead of asking git
+    /// (repeatable)
+    #[arg(long = "glob", value_name = "PATTERN")]
+    globs: Vec<String>,
 
-fn get_all_files() -> Result<Vec<PathBuf>> {
-    let output = process::Command::new("git").args(["ls-files"]).output()?;
+    /// Repository root used for file discovery and as the hooks' working directory
+    /// (defaults to `git rev-parse --show-toplevel`)
+    #[arg(long)]
+    repo_root: Option<PathBuf>,
 
-    if !output.status.success() {
-        anyhow::bail!("Failed to get files from git");
-    }
+    /// Git executable used for file discovery (defaults to `git` on PATH)
+    #[arg(long, env = "PRE_COMMIT_GIT", value_name = "PATH")]
+    git_binary: Option<PathBuf>,
 
-    let files = String::from_utf8(output.stdout)?
-        .lines()
-        .map(PathBuf::from)
-        .collect();
+    /// Only run the hooks in this config profile (plus their dependencies)
+    #[arg(long)]
+    profile: Option<String>,
 
-    Ok(files)
-}
+    /// In human output, group diagnostics from failed hooks by file
+    #[arg(long)]
+    group_by_file: bool,
 
-fn output_json(result: &pre_commit_core::ExecutionResult) -> Result<()> {
-    let json = serde_json::to_string_pretty(result)?;
-    println!("{}", json);
-    Ok(())
+    /// In human output, show one aligned row per hook and only the output of
+    /// failed hooks (colored unless `NO_COLOR` is set)
+    #[arg(long, conflicts_with = "group_by_file")]
+    compact: bool,
+
+    /// Write JSON or SARIF output to this file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    output_file: Option<PathBuf>,
+
+    /// Print JSON output on a single line
+    #[arg(long)]
+    json_compact: bool,
+
+    /// In JSON output, append each hook's stderr to its stdout and leave stderr empty
+    #[arg(long)]
+    merge_streams: bool,
+
+    /// Fail when no hook actually runs (e.g. every hook was skipped by its filters)
+    #[arg(long)]
+    error_on_no_hooks: bool,
+
+    /// Shell used for `shell: true` hooks, e.g. `bash` (defaults to `sh`, `cmd` on Windows)
+    #[arg(long = "exec", value_name = "SHELL")]
+    shell: Option<String>,
+
+    /// Lowest hook severity whose failures fail the run (`error` or `warning`)
+    #[arg(long, default_value = "error", value_name = "SEVERITY")]
+    fail_on: Severity,
+
+    /// Only exit non-zero when more than N hooks fail (counting failures at
+    /// the `--fail-on` severity); every failure is still reported. 0 fails on
+    /// any failure.
+    #[arg(long, default_value_t = 0, value_name = "N")]
+    fail_threshold: usize,
+
+    /// Reuse successful hook results from this directory when the hook's
+    /// command and matched files are unchanged, and store new ones there;
+    /// hooks with `always_run` or no matched files always run
+    #[arg(long, env = "PRE_COMMIT_CI_CACHE_DIR", value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// Ignore `--cache-dir` (and `PRE_COMMIT_CI_CACHE_DIR`) and run every hook
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Only parse, validate and plan the config; never discover files or run hooks
+    #[arg(long)]
+    check_only: bool,
+
+    /// Print the exact command line each hook would run, level by level, with
+    /// the files it matched, then exit without running anything
+    #[arg(long, conflicts_with_all = ["check_only", "config_dir"])]
+    dry_run: bool,
+
+    /// Warn about and ignore config keys no field accepts, instead of failing
+    #[arg(long)]
+    allow_unknown_fields: bool,
+
+    /// Run every config named like `--config` found under this directory, each
+    /// against its own project's files (a nested project's files are checked
+    /// only by its own config)
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["repo_root", "check_only", "files"])]
+    config_dir: Option<PathBuf>,
+
+    /// With `--parallel`, run at most this many hooks at once (0 or unset: one
+    /// per logical CPU)
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<usize>,
+
+    /// Stop after the first failing hook (and, with `--config-dir`, after the
+    /// first failing project); hooks already running finish
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// When to color output and force color on hooks: `auto` (when stdout is a
+    /// terminal and `NO_COLOR` is unset), `always` or `never`
+    #[arg(long, default_value = "auto", value_name = "WHEN")]
+    color: ColorChoice,
+
+    /// After the results, rank the N slowest hooks with their share of the
+    /// run's time
+    #[arg(long, value_name = "N")]
+    slowest: Option<usize>,
+
+    /// Write a JSON timing summary to this file: wall time, per-hook and
+    /// per-level durations, and the parallel speedup
+    #[arg(long, value_name = "PATH")]
+    timing_json: Option<PathBuf>,
+
+    /// Files to check, a directory standing for the files git tracks under it
+    /// (if not provided, checks all files in repo)
+    files: Vec<PathBuf>,
 }
 
-fn output_human(result: &pre_commit_core::ExecutionResult) {
-    println!("Pre-commit Hook Results");
-    println!("=======================\n");
+/// Exit code for `--error-on-no-hooks`, distinct from hook failures (1)
+const NO_HOOKS_RAN_EXIT_CODE: i32 = 2;
 
-    for hook_result in &result.hooks {
-        let status = if hook_result.success { "PASS" } else { "FAIL" };
-        println!("[{}] {}", status, hook_result.hook_id);
-        println!("  Duration: {}ms", hook_result.duration_ms);
+fn output_json(result: &ExecutionResult, compact: bool) -> Result<String> {
+    let json = if compact {
+        serde_json::to_string(result)?
+    } else {
+        serde_json::to_string_pretty(result)?
+    };
+    Ok(json)
+}
 
-        if let Some(code) = hook_result.exit_code {
-            println!("  Exit code: {}", code);
+/// Print machine-readable output, or write it to `--output-file`
+fn write_output(output_file: Option<&Path>, text: &str) -> Result<()> {
+    match output_file {
+        Some(path) => fs::write(path, format!("{}\n", text))
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e)),
+        None => {
+            println!("{}", text);
+            Ok(())
         }
+    }
+}
 
-        if !hook_result.stdout.is_empty() {
-            println!("  Output:");
-            for line in hook_result.stdout.lines() {
-                println!("    {}", line);
-            }
-        }
+fn print_hook_human(hook_result: &HookResult) {
+    if let Some(reason) = &hook_result.skip_reason {
+        println!("[SKIP] {}", hook_result.hook_id);
+        println!("  Reason: {}\n", reason);
+        return;
+    }
 
-        if !hook_result.stderr.is_empty() {
-            println!("  Errors:");
-            for line in hook_result.stderr.lines() {
-                println!("    {}", line);
-            }
+    let status = match (hook_result.success, hook_result.severity) {
+        (true, _) => "PASS",
+        (false, Severity::Warning) => "WARN",
+        (false, Severity::Error) => "FAIL",
+    };
+    println!("[{}] {}", status, hook_result.hook_id);
+    println!("  Duration: {}ms", hook_result.duration_ms);
+
+    if let Some(code) = hook_result.exit_code {
+        println!("  Exit code: {}", code);
+    }
+
+    print_hook_output(hook_result);
+    println!();
+}
+
+fn print_hook_output(hook_result: &HookResult) {
+    if let Some(stdout) = hook_result.visible_stdout() {
+        println!("  Output:");
+        for line in stdout.lines() {
+            println!("    {}", line);
         }
+    }
 
-        println!();
+    if let Some(stderr) = hook_result.visible_stderr() {
+        println!("  Errors:");
+        for line in stderr.lines() {
+            println!("    {}", line);
+        }
     }
+}
 
+fn print_summary(result: &ExecutionResult) {
     println!("Summary");
     println!("-------");
     println!("Total hooks: {}", result.hooks.len());
     println!(
         "Passed: {}",
-        result.hooks.iter().filter(|h| h.success).count()
+        result
+            .hooks
+            .iter()
+            .filter(|h| h.success && !h.is_skipped())
+            .count()
+    );
+    println!(
+        "Skipped: {}",
+        result.hooks.iter().filter(|h| h.is_skipped()).count()
     );
     println!(
         "Failed: {}",
-        result.hooks.iter().filter(|h| !h.success).count()
+        result.hooks.iter().filter(|h| h.is_failure()).count()
     );
     println!("Total time: {}ms", result.total_duration_ms);
     println!(
@@ -107,46 +263,613 @@ fn output_human(result: &pre_commit_core::ExecutionResult) {
     );
 }
 
+fn output_human(result: &ExecutionResult) {
+    println!("Pre-commit Hook Results");
+    println!("=======================\n");
+
+    for hook_result in &result.hooks {
+        print_hook_human(hook_result);
+    }
+
+    print_summary(result);
+}
+
+/// `--compact`: a table of status, hook id, duration and exit code, then the
+/// output of the hooks that failed
+fn output_compact(result: &ExecutionResult) {
+    println!("Pre-commit Hook Results");
+    println!("=======================\n");
+
+    for row in compact_rows(result) {
+        println!("{}", row);
+    }
+
+    for hook_result in result.hooks.iter().filter(|h| !h.success) {
+        println!("\n{}", hook_result.hook_id);
+        print_hook_output(hook_result);
+    }
+
+    println!();
+    print_summary(result);
+}
+
+/// Rows of the `--compact` table, columns padded to the widest value; colors
+/// are applied after padding so they don't throw off the alignment
+fn compact_rows(result: &ExecutionResult) -> Vec<String> {
+    let rows: Vec<_> = result
+        .hooks
+        .iter()
+        .map(|hook_result| {
+            let status = match (&hook_result.skip_reason, hook_result.success) {
+                (Some(_), _) => "SKIP",
+                (None, true) => "PASS",
+                (None, false) if hook_result.severity == Severity::Warning => "WARN",
+                (None, false) => "FAIL",
+            };
+            let (duration, exit_code) = match hook_result.skip_reason {
+                Some(_) => ("-".to_string(), "-".to_string()),
+                None => (
+                    format!("{}ms", hook_result.duration_ms),
+                    hook_result
+                        .exit_code
+                        .map_or("-".to_string(), |code| code.to_string()),
+                ),
+            };
+            (status, hook_result.hook_id.as_str(), duration, exit_code)
+        })
+        .collect();
+
+    let id_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0).max(4);
+    let time_width = rows.iter().map(|r| r.2.len()).max().unwrap_or(0).max(4);
+
+    let header = format!(
+        "STATUS  {:<id_width$}  {:>time_width$}  EXIT",
+        "HOOK", "TIME"
+    );
+    std::iter::once(header)
+        .chain(rows.into_iter().map(|(status, id, duration, exit_code)| {
+            let padded = format!("{:<6}", status);
+            let status = match status {
+                "PASS" => padded.if_supports_color(Stdout, |t| t.green()).to_string(),
+                "FAIL" => padded.if_supports_color(Stdout, |t| t.red()).to_string(),
+                "WARN" => padded.if_supports_color(Stdout, |t| t.yellow()).to_string(),
+                _ => padded.if_supports_color(Stdout, |t| t.dimmed()).to_string(),
+            };
+            format!(
+                "{}  {:<id_width$}  {:>time_width$}  {}",
+                status, id, duration, exit_code
+            )
+        }))
+        .collect()
+}
+
+/// Diagnostics from failed hooks keyed by file, each tagged with its hook id
+fn diagnostics_by_file(result: &ExecutionResult) -> BTreeMap<PathBuf, Vec<(&str, Diagnostic)>> {
+    let mut by_file: BTreeMap<PathBuf, Vec<(&str, Diagnostic)>> = BTreeMap::new();
+
+    for hook_result in result.hooks.iter().filter(|h| !h.success) {
+        let output = format!("{}\n{}", hook_result.stdout, hook_result.stderr);
+        for diagnostic in parse_diagnostics(&output) {
+            by_file
+                .entry(diagnostic.file.clone())
+                .or_default()
+                .push((hook_result.hook_id.as_str(), diagnostic));
+        }
+    }
+
+    for diagnostics in by_file.values_mut() {
+        diagnostics.sort_by_key(|(_, d)| (d.line, d.column));
+    }
+
+    by_file
+}
+
+fn output_grouped_by_file(result: &ExecutionResult) {
+    println!("Pre-commit Results by File");
+    println!("==========================\n");
+
+    let by_file = diagnostics_by_file(result);
+
+    for (file, diagnostics) in &by_file {
+        println!("{}", file.display());
+        for (hook_id, diagnostic) in diagnostics {
+            let location = match diagnostic.column {
+                Some(column) => format!("{}:{}", diagnostic.line, column),
+                None => diagnostic.line.to_string(),
+            };
+            println!("  {} [{}] {}", location, hook_id, diagnostic.message);
+        }
+        println!();
+    }
+
+    // Failed hooks whose output had nothing file-shaped keep the per-hook view
+    let grouped: HashSet<&str> = by_file
+        .values()
+        .flatten()
+        .map(|(hook_id, _)| *hook_id)
+        .collect();
+    for hook_result in &result.hooks {
+        if !hook_result.success && !grouped.contains(hook_result.hook_id.as_str()) {
+            print_hook_human(hook_result);
+        }
+    }
+
+    print_summary(result);
+}
+
+/// Parse and validate a config, then resolve the hooks it runs; also returns
+/// the config for its run-wide settings (`exclude`, `fail_fast`). With
+/// `allow_unknown_fields`, unknown keys are reported as warnings and dropped.
+fn load_hooks(
+    config_path: &Path,
+    profile: Option<&str>,
+    allow_unknown_fields: bool,
+) -> Result<(Vec<Hook>, Config)> {
+    let config = if allow_unknown_fields {
+        let (config, dropped) = parse_config_file_lenient(config_path)?;
+        for field in dropped {
+            eprintln!("Warning: ignoring {}", field);
+        }
+        config
+    } else {
+        parse_config_file(config_path)?
+    };
+    validate_config(&config)?;
+
+    let mut hooks = resolve_hooks(&config)?;
+    for warning in dedup_dependencies(&mut hooks) {
+        eprintln!("Warning: {}", warning);
+    }
+    if let Some(profile) = profile {
+        hooks = select_profile(&config, &hooks, profile)?;
+    }
+    Ok((hooks, config))
+}
+
+fn execute_hooks(
+    cli: &Cli,
+    hooks: &[Hook],
+    files: &[PathBuf],
+    options: ExecutionOptions,
+) -> Result<ExecutionResult> {
+    let cache_dir = cli.cache_dir.as_ref().filter(|_| !cli.no_cache);
+    let Some(cache_dir) = cache_dir else {
+        return run_executor(hooks, files, options, cli.parallel);
+    };
+
+    let mut cache = ResultCache::in_dir(cache_dir.clone());
+    let (cached, remaining, keys) = cache.partition(hooks, files, &options);
+    if !cached.is_empty() {
+        eprintln!("Reused {} cached hook result(s)", cached.len());
+    }
+
+    let fresh = if remaining.is_empty() {
+        ExecutionResult {
+            hooks: Vec::new(),
+            total_duration_ms: 0,
+            all_passed: true,
+            levels: Vec::new(),
+        }
+    } else {
+        run_executor(&remaining, files, options, cli.parallel)?
+    };
+    // Failing to store is not fatal; the hooks just run again next time
+    cache.record(&keys, &fresh);
+    cache.save().ok();
+
+    // Keep results in config order, cached or not
+    let mut by_id: HashMap<String, HookResult> = cached
+        .into_iter()
+        .chain(fresh.hooks)
+        .map(|result| (result.hook_id.clone(), result))
+        .collect();
+    Ok(ExecutionResult {
+        hooks: hooks
+            .iter()
+            .filter_map(|hook| by_id.remove(&hook.id))
+            .collect(),
+        total_duration_ms: fresh.total_duration_ms,
+        all_passed: fresh.all_passed,
+        levels: fresh.levels,
+    })
+}
+
+fn run_executor(
+    hooks: &[Hook],
+    files: &[PathBuf],
+    options: ExecutionOptions,
+    parallel: bool,
+) -> Result<ExecutionResult> {
+    // Built even when running sequentially, so dependency cycles are reported
+    let plan = DagBuilder::new().build_plan(hooks)?;
+    let result = if parallel {
+        ParallelExecutor::new(plan)
+            .with_options(options)
+            .execute(hooks, files)?
+    } else {
+        SyncExecutor::with_options(options).execute(hooks, files)?
+    };
+    Ok(result)
+}
+
+/// Outcome of one project in a `--config-dir` run
+struct ProjectSummary {
+    name: String,
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+    duration_ms: u64,
+    /// Passed at the `--fail-on` threshold
+    success: bool,
+    error: Option<String>,
+}
+
+impl ProjectSummary {
+    fn new(name: String, result: &ExecutionResult, fail_on: Severity) -> Self {
+        let skipped = result.hooks.iter().filter(|h| h.is_skipped()).count();
+        let failed = result.hooks.iter().filter(|h| h.is_failure()).count();
+        Self {
+            name,
+            passed: result.hooks.len() - skipped - failed,
+            failed,
+            skipped,
+            duration_ms: result.total_duration_ms,
+            success: result.passes(fail_on),
+            error: None,
+        }
+    }
+
+    fn failed(name: String, error: anyhow::Error) -> Self {
+        Self {
+            name,
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+            duration_ms: 0,
+            success: false,
+            error: Some(format!("{:#}", error)),
+        }
+    }
+
+    fn is_success(&self) -> bool {
+        self.success
+    }
+}
+
+/// Run one project of a `--config-dir` run against the files under its
+/// directory, leaving out the `nested` project directories (relative to it),
+/// which their own configs check
+fn run_project(
+    cli: &Cli,
+    config_path: &Path,
+    nested: &[PathBuf],
+    git: &Path,
+) -> Result<ExecutionResult> {
+    let project_dir = config_path.parent().unwrap_or(Path::new("."));
+    let (hooks, config) = load_hooks(
+        config_path,
+        cli.profile.as_deref(),
+        cli.allow_unknown_fields,
+    )?;
+    if hooks.is_empty() {
+        return Ok(ExecutionResult {
+            hooks: Vec::new(),
+            total_duration_ms: 0,
+            all_passed: true,
+            levels: Vec::new(),
+        });
+    }
+
+    let mut files = if cli.globs.is_empty() {
+        tracked_files(git, Some(project_dir))?
+    } else {
+        glob_files(project_dir, &cli.globs)?
+    };
+    files.retain(|file| !nested.iter().any(|dir| file.starts_with(dir)));
+    let options = ExecutionOptions {
+        repo_root: Some(project_dir.to_path_buf()),
+        exclude: config.exclude,
+        jobs: Some(resolve_jobs(cli.jobs)),
+        shell: cli.shell.clone(),
+        fail_fast: cli.fail_fast || config.fail_fast,
+        no_color: !cli.color.enabled(),
+        fail_on: cli.fail_on,
+        trap_ctrl_c: true,
+        ..Default::default()
+    };
+
+    execute_hooks(cli, &hooks, &files, options)
+}
+
+/// Run every config under `dir` and merge the results, prefixing hook ids
+/// with the project's path relative to `dir`
+fn run_config_dir(
+    cli: &Cli,
+    dir: &Path,
+    git: &Path,
+) -> Result<(ExecutionResult, Vec<ProjectSummary>)> {
+    let config_name = cli
+        .config
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Config path has no file name: {}", cli.config.display()))?
+        .to_string_lossy();
+    let configs = find_configs(dir, &config_name)?;
+    if configs.is_empty() {
+        anyhow::bail!("No {} found under {}", config_name, dir.display());
+    }
+
+    let mut merged = ExecutionResult {
+        hooks: Vec::new(),
+        total_duration_ms: 0,
+        all_passed: true,
+        levels: Vec::new(),
+    };
+    let mut projects = Vec::new();
+
+    for config_path in &configs {
+        let project_dir = config_path.parent().unwrap_or(dir);
+        let name = match project_dir.strip_prefix(dir) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative.display().to_string(),
+            _ => ".".to_string(),
+        };
+
+        let nested: Vec<PathBuf> = configs
+            .iter()
+            .filter_map(|other| other.parent()?.strip_prefix(project_dir).ok())
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .collect();
+
+        // A broken project is reported but doesn't stop the others
+        let summary = match run_project(cli, config_path, &nested, git) {
+            Ok(result) => {
+                let summary = ProjectSummary::new(name.clone(), &result, cli.fail_on);
+                merged.total_duration_ms += result.total_duration_ms;
+                merged
+                    .hooks
+                    .extend(result.hooks.into_iter().map(|mut hook| {
+                        hook.hook_id = format!("{}/{}", name, hook.hook_id);
+                        hook
+                    }));
+                summary
+            }
+            Err(e) => ProjectSummary::failed(name, e),
+        };
+
+        let success = summary.is_success();
+        merged.all_passed &= success;
+        projects.push(summary);
+        if !success && cli.fail_fast {
+            break;
+        }
+    }
+
+    Ok((merged, projects))
+}
+
+/// `--slowest`: the `n` slowest hooks with their share of the run's time, or
+/// of the summed hook time when hooks ran in parallel and overlapped
+fn print_slowest(result: &ExecutionResult, n: usize, parallel: bool) {
+    let (of_total_ms, share) = if parallel {
+        (
+            result.summed_duration_ms(),
+            "share of summed hook time, not wall time, as hooks ran in parallel",
+        )
+    } else {
+        (result.total_duration_ms, "share of total time")
+    };
+    let slowest = result.slowest_hooks(n, of_total_ms);
+    if slowest.is_empty() {
+        return;
+    }
+
+    println!("\nSlowest hooks ({})", share);
+    println!("-------------");
+    let id_width = slowest
+        .iter()
+        .map(|(hook, _)| hook.hook_id.len())
+        .max()
+        .unwrap_or(0);
+    for (rank, (hook, percent)) in slowest.iter().enumerate() {
+        println!(
+            "{}. {:<id_width$}  {:>6}ms  {:>5.1}%",
+            rank + 1,
+            hook.hook_id,
+            hook.duration_ms,
+            percent
+        );
+    }
+}
+
+fn print_project_summaries(projects: &[ProjectSummary]) {
+    println!("\nProjects");
+    println!("--------");
+    for project in projects {
+        match &project.error {
+            Some(error) => println!("[ERROR] {}: {}", project.name, error),
+            None => println!(
+                "[{}] {}: {} passed, {} failed, {} skipped ({}ms)",
+                if project.is_success() { "PASS" } else { "FAIL" },
+                project.name,
+                project.passed,
+                project.failed,
+                project.skipped,
+                project.duration_ms
+            ),
+        }
+    }
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    owo_colors::set_override(cli.color.enabled());
 
-    // Parse and validate config
-    let config = parse_config_file(&cli.config)?;
-    validate_config(&config)?;
+    if let Some(dir) = cli.config_dir.clone() {
+        return run_config_dir_main(&cli, &dir);
+    }
 
-    // Extract hooks
-    let hooks = extract_hooks(&config);
+    let (hooks, config) = load_hooks(
+        &cli.config,
+        cli.profile.as_deref(),
+        cli.allow_unknown_fields,
+    )?;
+
+    if cli.check_only {
+        let plan = DagBuilder::new().build_plan(&hooks)?;
+        match cli.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&plan.stats())?),
+            OutputFormat::Human | OutputFormat::Github | OutputFormat::Sarif => {
+                println!("Config OK: {}", plan.stats())
+            }
+        }
+        return Ok(());
+    }
 
     if hooks.is_empty() {
         eprintln!("No hooks to run");
+        if cli.error_on_no_hooks {
+            process::exit(NO_HOOKS_RAN_EXIT_CODE);
+        }
         return Ok(());
     }
 
+    if let Some(shell) = &cli.shell {
+        command::check_shell(shell)
+            .map_err(|e| anyhow::anyhow!("Shell '{}' is not usable: {}", shell, e))?;
+    }
+    let git = resolve_git_binary(cli.git_binary.clone())?;
+    let repo_root = resolve_repo_root(cli.repo_root.clone(), &git)?;
+
     // Get files to check
-    let files_to_check = if cli.files.is_empty() {
-        get_all_files()?
+    let files_to_check = if !cli.files.is_empty() {
+        expand_dirs(&git, repo_root.as_deref(), &cli.files)?
+    } else if !cli.globs.is_empty() {
+        let root = repo_root.clone().unwrap_or_else(|| PathBuf::from("."));
+        glob_files(&root, &cli.globs)?
     } else {
-        cli.files
+        tracked_files(&git, repo_root.as_deref())?
     };
 
-    // Execute hooks
-    let result = if cli.parallel {
-        let builder = DagBuilder::new();
-        let plan = builder.build_plan(&hooks)?;
-        let executor = ParallelExecutor::new(plan);
-        executor.execute(&hooks, &files_to_check)?
-    } else {
-        let executor = SyncExecutor::new();
-        executor.execute(&hooks, &files_to_check)?
+    let options = ExecutionOptions {
+        repo_root,
+        exclude: config.exclude,
+        jobs: Some(resolve_jobs(cli.jobs)),
+        shell: cli.shell.clone(),
+        fail_fast: cli.fail_fast || config.fail_fast,
+        no_color: !cli.color.enabled(),
+        fail_on: cli.fail_on,
+        trap_ctrl_c: true,
+        ..Default::default()
     };
 
+    if cli.dry_run {
+        let plan = DagBuilder::new().build_plan(&hooks)?;
+        print_dry_run(&plan, &files_to_check, &options);
+        return Ok(());
+    }
+
+    let result = execute_hooks(&cli, &hooks, &files_to_check, options)?;
+    report(&cli, result, &[])
+}
+
+/// `--dry-run`: each level's hooks with the argv of every invocation they
+/// would make, or why they would be skipped
+fn print_dry_run(plan: &ExecutionPlan, files: &[PathBuf], options: &ExecutionOptions) {
+    println!(
+        "Dry run: {} hooks on {} files, nothing is run",
+        plan.levels.iter().map(Vec::len).sum::<usize>(),
+        files.len()
+    );
+    for (index, level) in plan.levels.iter().enumerate() {
+        println!("\nLevel {}:", index + 1);
+        for hook in level {
+            match command::dry_run(hook, files, options) {
+                command::DryRun::Skip(reason) => println!("  {} (skipped: {})", hook.id, reason),
+                command::DryRun::Run(argvs) => {
+                    for argv in argvs {
+                        match argv {
+                            Ok(argv) => println!("  {} {}", hook.id, shell::join(&argv)),
+                            Err(e) => println!("  {} error: {}", hook.id, e),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn run_config_dir_main(cli: &Cli, dir: &Path) -> Result<()> {
+    if let Some(shell) = &cli.shell {
+        command::check_shell(shell)
+            .map_err(|e| anyhow::anyhow!("Shell '{}' is not usable: {}", shell, e))?;
+    }
+    let git = resolve_git_binary(cli.git_binary.clone())?;
+
+    let (result, projects) = run_config_dir(cli, dir, &git)?;
+    for project in &projects {
+        if let Some(error) = &project.error {
+            eprintln!("Project {} failed to run: {}", project.name, error);
+        }
+    }
+    report(cli, result, &projects)
+}
+
+/// Print the results and exit with the matching code
+fn report(cli: &Cli, mut result: ExecutionResult, projects: &[ProjectSummary]) -> Result<()> {
+    // Hook failures from every project count towards one threshold, but a
+    // cancelled run or a project that failed to run always fails the run
+    result.all_passed = result.failure_count(cli.fail_on) <= cli.fail_threshold
+        && !result.was_cancelled()
+        && projects.iter().all(|project| project.error.is_none());
+    if cli.merge_streams && matches!(cli.format, OutputFormat::Json) {
+        result
+            .hooks
+            .iter_mut()
+            .for_each(|hook| hook.merge_streams());
+    }
+
+    if let Some(path) = &cli.timing_json {
+        let timing = serde_json::to_string_pretty(&timing::document(&result))?;
+        write_output(Some(path), &timing)?;
+    }
+
     // Output results
     match cli.format {
-        OutputFormat::Json => output_json(&result)?,
+        OutputFormat::Json => write_output(
+            cli.output_file.as_deref(),
+            &output_json(&result, cli.json_compact)?,
+        )?,
+        OutputFormat::Sarif => write_output(
+            cli.output_file.as_deref(),
+            &serde_json::to_string_pretty(&sarif::document(&result))?,
+        )?,
+        OutputFormat::Human if cli.group_by_file => output_grouped_by_file(&result),
+        OutputFormat::Human if cli.compact => output_compact(&result),
         OutputFormat::Human => output_human(&result),
+        OutputFormat::Github => {
+            if cli.compact {
+                output_compact(&result);
+            } else {
+                output_human(&result);
+            }
+            github::report(&result)?;
+        }
+    }
+    if !matches!(cli.format, OutputFormat::Json | OutputFormat::Sarif) {
+        if let Some(n) = cli.slowest {
+            print_slowest(&result, n, cli.parallel);
+        }
+        if !projects.is_empty() {
+            print_project_summaries(projects);
+        }
     }
 
     // Exit with appropriate code
+    if cli.error_on_no_hooks && result.executed_count() == 0 {
+        eprint!("{}", result.no_hooks_ran_report());
+        process::exit(NO_HOOKS_RAN_EXIT_CODE);
+    }
+
     if !result.all_passed {
         process::exit(1);
     }
@@ -0,0 +1,106 @@
+use pre_commit_core::{ExecutionResult, HookResult, Severity};
+use serde_json::{json, Value};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// `--format sarif`: a SARIF 2.1.0 log with a single run, holding one result
+/// per failed hook. Passed and skipped hooks produce no results.
+pub fn document(result: &ExecutionResult) -> Value {
+    let results: Vec<Value> = result
+        .hooks
+        .iter()
+        .filter(|hook| hook.is_failure())
+        .map(|hook| {
+            json!({
+                "ruleId": hook.hook_id,
+                "level": level(hook.severity),
+                "message": { "text": message(hook) },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "pre-commit-rs",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+/// The hook's trimmed stderr; SARIF messages can't be empty, so a silent
+/// failure falls back to its exit code
+fn message(hook: &HookResult) -> String {
+    match (hook.stderr.trim(), hook.exit_code) {
+        ("", Some(code)) => format!("exited with code {}", code),
+        ("", None) => "failed".to_string(),
+        (stderr, _) => stderr.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed(id: &str, stderr: &str, severity: Severity) -> HookResult {
+        let mut result = HookResult::skipped(id, "unused");
+        result.skip_reason = None;
+        result.success = false;
+        result.exit_code = Some(1);
+        result.stderr = stderr.to_string();
+        result.severity = severity;
+        result
+    }
+
+    #[test]
+    fn test_results_for_failures_only() {
+        let mut passed = failed("fmt", "", Severity::Error);
+        passed.success = true;
+        let result = ExecutionResult {
+            hooks: vec![
+                passed,
+                failed("lint", "\n  a.rs:1: unused\n", Severity::Error),
+                HookResult::skipped("docs", "no files to check"),
+                failed("spelling", "", Severity::Warning),
+            ],
+            total_duration_ms: 0,
+            all_passed: false,
+            levels: Vec::new(),
+        };
+
+        let document = document(&result);
+        assert_eq!(document["version"], "2.1.0");
+        assert_eq!(
+            document["runs"][0]["tool"]["driver"]["name"],
+            "pre-commit-rs"
+        );
+        assert_eq!(
+            document["runs"][0]["results"],
+            json!([
+                {
+                    "ruleId": "lint",
+                    "level": "error",
+                    "message": { "text": "a.rs:1: unused" },
+                },
+                {
+                    "ruleId": "spelling",
+                    "level": "warning",
+                    "message": { "text": "exited with code 1" },
+                },
+            ])
+        );
+    }
+}
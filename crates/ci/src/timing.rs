@@ -0,0 +1,82 @@
+use pre_commit_core::ExecutionResult;
+use serde_json::{json, Value};
+
+/// `--timing-json`: wall time, how long each hook and plan level took, and
+/// the speedup from running hooks in parallel (summed hook time over wall
+/// time; `null` when the run took no measurable time). Levels are only
+/// recorded with `--parallel`.
+pub fn document(result: &ExecutionResult) -> Value {
+    let hooks: Vec<Value> = result
+        .hooks
+        .iter()
+        .filter(|hook| !hook.is_skipped())
+        .map(|hook| json!({ "hook_id": hook.hook_id, "duration_ms": hook.duration_ms }))
+        .collect();
+    let summed_ms = result.summed_duration_ms();
+    let speedup =
+        (result.total_duration_ms > 0).then(|| summed_ms as f64 / result.total_duration_ms as f64);
+
+    json!({
+        "total_duration_ms": result.total_duration_ms,
+        "summed_duration_ms": summed_ms,
+        "speedup": speedup,
+        "hooks": hooks,
+        "levels": result.levels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pre_commit_core::{HookResult, LevelTiming};
+
+    fn ran(id: &str, duration_ms: u64) -> HookResult {
+        let mut result = HookResult::skipped(id, "unused");
+        result.skip_reason = None;
+        result.duration_ms = duration_ms;
+        result
+    }
+
+    #[test]
+    fn test_document() {
+        let result = ExecutionResult {
+            hooks: vec![
+                ran("fmt", 100),
+                ran("lint", 60),
+                HookResult::skipped("test", "no files to check"),
+            ],
+            total_duration_ms: 80,
+            all_passed: true,
+            levels: vec![LevelTiming {
+                hooks: vec!["fmt".to_string(), "lint".to_string()],
+                start_ms: 0,
+                duration_ms: 80,
+            }],
+        };
+
+        let doc = document(&result);
+        assert_eq!(doc["total_duration_ms"], 80);
+        assert_eq!(doc["summed_duration_ms"], 160);
+        assert_eq!(doc["speedup"], 2.0);
+        assert_eq!(
+            doc["hooks"],
+            json!([
+                { "hook_id": "fmt", "duration_ms": 100 },
+                { "hook_id": "lint", "duration_ms": 60 },
+            ])
+        );
+        assert_eq!(doc["levels"][0]["hooks"], json!(["fmt", "lint"]));
+        assert_eq!(doc["levels"][0]["duration_ms"], 80);
+    }
+
+    #[test]
+    fn test_speedup_of_an_instant_run_is_null() {
+        let result = ExecutionResult {
+            hooks: Vec::new(),
+            total_duration_ms: 0,
+            all_passed: true,
+            levels: Vec::new(),
+        };
+        assert!(document(&result)["speedup"].is_null());
+    }
+}
@@ -0,0 +1,71 @@
+//! Fixtures shared by the integration tests
+
+// Each test binary uses only some of these
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// `pre-commit-ci` with the `config.yaml` in `root`, which is also the repo
+/// root, uncolored and without a result cache
+pub fn run_command(root: &Path) -> Command {
+    run_command_with_color(root, "never")
+}
+
+/// [`run_command`] with `--color <color>`
+pub fn run_command_with_color(root: &Path, color: &str) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_pre-commit-ci"));
+    command
+        .arg("--config")
+        .arg(root.join("config.yaml"))
+        .arg("--repo-root")
+        .arg(root)
+        .args(["--no-cache", "--color", color]);
+    command
+}
+
+/// Run [`run_command`] with `args` to completion
+pub fn run(root: &Path, args: &[&str]) -> Output {
+    run_command(root).args(args).output().unwrap()
+}
+
+/// A new directory holding `config` as `config.yaml`
+pub fn project(config: &str) -> tempfile::TempDir {
+    let root = tempfile::tempdir().unwrap();
+    fs::write(root.path().join("config.yaml"), config).unwrap();
+    root
+}
+
+/// Run git in `root` with a fixed identity, returning its trimmed stdout
+pub fn git(root: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(root)
+        .env_remove("PRE_COMMIT_RS_SKIP")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {:?}",
+        args,
+        output
+    );
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+/// A new repository on `main` with one commit holding `files`, given as
+/// `(path, content)` pairs
+pub fn git_repo(files: &[(&str, &str)]) -> tempfile::TempDir {
+    let root = tempfile::tempdir().unwrap();
+    git(root.path(), &["init", "-q", "-b", "main"]);
+    for (path, content) in files {
+        let path = root.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    git(root.path(), &["add", "."]);
+    git(root.path(), &["commit", "-q", "-m", "initial"]);
+    root
+}
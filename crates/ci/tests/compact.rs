@@ -0,0 +1,87 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::Output;
+
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: fmt
+        name: Format
+        entry: echo formatted everything
+        language: system
+      - id: clippy-lints
+        name: Lints
+        entry: sh -c 'echo lint output; echo bad >&2; exit 3'
+        language: system
+      - id: docs
+        name: Docs
+        entry: "true"
+        language: system
+        files: '\.md$'
+        pass_filenames: true
+"#;
+
+/// `--compact` on `a.rs` with `--color <color>`, and `NO_COLOR` set to
+/// `no_color` or unset
+fn run_compact(root: &Path, color: &str, no_color: Option<&str>) -> Output {
+    let mut command = common::run_command_with_color(root, color);
+    command.args(["--compact", "a.rs"]);
+    match no_color {
+        Some(value) => command.env("NO_COLOR", value),
+        None => command.env_remove("NO_COLOR"),
+    };
+    command.output().unwrap()
+}
+
+#[test]
+fn test_compact_rows_are_aligned_and_only_failures_show_output() {
+    let root = common::project(CONFIG);
+    fs::write(root.path().join("a.rs"), "").unwrap();
+    let output = run_compact(root.path(), "auto", Some("1"));
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains('\x1b'), "{}", stdout);
+    let table: Vec<&str> = stdout
+        .lines()
+        .skip_while(|line| !line.starts_with("STATUS"))
+        .take(4)
+        .collect();
+    assert_eq!(table.len(), 4, "{}", stdout);
+    assert!(table[0].starts_with("STATUS  HOOK          "), "{}", stdout);
+    assert!(table[1].starts_with("PASS    fmt           "), "{}", stdout);
+    assert!(table[2].starts_with("FAIL    clippy-lints  "), "{}", stdout);
+    assert!(table[2].ends_with("  3"), "{}", stdout);
+    assert!(table[3].starts_with("SKIP    docs          "), "{}", stdout);
+    assert!(table[3].ends_with("-  -"), "{}", stdout);
+
+    // Every row has its columns at the same offsets
+    let exit_column = table[0].find("EXIT").unwrap();
+    for row in &table[1..] {
+        assert_eq!(row.len(), exit_column + 1, "{:?}", row);
+    }
+
+    assert!(stdout.contains("lint output"), "{}", stdout);
+    assert!(stdout.contains("bad"), "{}", stdout);
+    assert!(!stdout.contains("formatted everything"), "{}", stdout);
+    assert!(stdout.contains("Result: FAILURE"), "{}", stdout);
+}
+
+#[test]
+fn test_compact_status_color_follows_color_option() {
+    let root = common::project(CONFIG);
+    fs::write(root.path().join("a.rs"), "").unwrap();
+    let stdout = |color: &str, no_color: Option<&str>| {
+        String::from_utf8_lossy(&run_compact(root.path(), color, no_color).stdout).into_owned()
+    };
+
+    let colored = stdout("always", Some("1"));
+    assert!(colored.contains("\x1b[32mPASS  \x1b[39m"), "{}", colored);
+
+    // Piped output isn't a terminal, so `auto` leaves it plain
+    assert!(!stdout("auto", None).contains('\x1b'));
+    assert!(!stdout("never", None).contains('\x1b'));
+}
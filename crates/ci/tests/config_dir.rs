@@ -0,0 +1,86 @@
+mod common;
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Lists the files it is given, so a test can see which project checked what
+const ROOT_CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: list
+        name: List
+        entry: sh -c 'printf "%s\n" "$@"' --
+        language: system
+        pass_filenames: true
+"#;
+
+const FAILING_CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: lint
+        name: Lint
+        entry: "false"
+        language: system
+      - id: list
+        name: List
+        entry: sh -c 'printf "%s\n" "$@"' --
+        language: system
+        pass_filenames: true
+"#;
+
+/// `--config-dir root`, which can't be combined with `--repo-root`
+fn run_config_dir(root: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pre-commit-ci"))
+        .args(["--config", "config.yaml", "--no-cache", "--color", "never"])
+        .arg("--config-dir")
+        .arg(root)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_each_project_checks_only_its_own_files() {
+    // `nested` sorts before the root config's files, so it runs first and
+    // fails without stopping the root project
+    let root = common::git_repo(&[
+        ("config.yaml", ROOT_CONFIG),
+        ("a.rs", ""),
+        ("nested/config.yaml", FAILING_CONFIG),
+        ("nested/b.rs", ""),
+    ]);
+
+    let output = run_config_dir(root.path(), &["--format", "json"]);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["all_passed"], false);
+    let hooks = result["hooks"].as_array().unwrap();
+    let ids: Vec<&str> = hooks
+        .iter()
+        .map(|hook| hook["hook_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["./list", "nested/lint", "nested/list"]);
+
+    let listed = |id: &str| {
+        let hook = hooks.iter().find(|hook| hook["hook_id"] == id).unwrap();
+        hook["stdout"].as_str().unwrap().lines().collect::<Vec<_>>()
+    };
+    assert_eq!(listed("./list"), vec!["a.rs", "config.yaml"]);
+    assert_eq!(listed("nested/list"), vec!["b.rs", "config.yaml"]);
+
+    let output = run_config_dir(root.path(), &[]);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("[PASS] .: 1 passed, 0 failed, 0 skipped"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("[FAIL] nested: 1 passed, 1 failed, 0 skipped"),
+        "{}",
+        stdout
+    );
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use std::fs;
+
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: mark
+        name: Mark
+        entry: touch spawned
+        language: system
+        files: '\.rs$'
+        pass_filenames: true
+      - id: python
+        name: Python
+        entry: touch spawned-python
+        language: system
+        files: '\.py$'
+        pass_filenames: true
+        depends_on: [mark]
+"#;
+
+#[test]
+fn test_dry_run_prints_argv_without_running() {
+    let root = common::project(CONFIG);
+    fs::write(root.path().join("a.rs"), "").unwrap();
+    fs::write(root.path().join("notes.txt"), "").unwrap();
+
+    let output = common::run(root.path(), &["--dry-run", "a.rs", "notes.txt"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout,
+        "Dry run: 2 hooks on 2 files, nothing is run\n\
+         \n\
+         Level 1:\n  mark touch spawned a.rs\n\
+         \n\
+         Level 2:\n  python (skipped: no files to check)\n"
+    );
+
+    assert!(!root.path().join("spawned").exists());
+    assert!(!root.path().join("spawned-python").exists());
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use std::process::Output;
+
+/// Two failing hooks and one that passes
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: fmt
+        name: Format
+        entry: "true"
+        language: system
+        pass_filenames: false
+      - id: lint
+        name: Lint
+        entry: "false"
+        language: system
+        pass_filenames: false
+      - id: spelling
+        name: Spelling
+        entry: "false"
+        language: system
+        pass_filenames: false
+"#;
+
+fn assert_failures_reported(output: &Output) {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[FAIL] lint"), "{}", stdout);
+    assert!(stdout.contains("[FAIL] spelling"), "{}", stdout);
+}
+
+#[test]
+fn test_default_threshold_fails_on_any_failure() {
+    let root = common::project(CONFIG);
+    let output = common::run(root.path(), &["config.yaml"]);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert_failures_reported(&output);
+}
+
+#[test]
+fn test_failures_above_threshold() {
+    let root = common::project(CONFIG);
+    let output = common::run(root.path(), &["--fail-threshold", "1", "config.yaml"]);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert_failures_reported(&output);
+}
+
+#[test]
+fn test_failures_at_threshold() {
+    let root = common::project(CONFIG);
+    let output = common::run(root.path(), &["--fail-threshold", "2", "config.yaml"]);
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    assert_failures_reported(&output);
+}
+
+#[test]
+fn test_dependents_of_a_failure_are_not_counted() {
+    let config = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: lint
+        name: Lint
+        entry: "false"
+        language: system
+        pass_filenames: false
+      - id: test
+        name: Test
+        entry: "true"
+        language: system
+        pass_filenames: false
+        depends_on: [lint]
+"#;
+    let root = common::project(config);
+    let run = |threshold: &str| {
+        common::run(
+            root.path(),
+            &[
+                "--parallel",
+                "--compact",
+                "--fail-threshold",
+                threshold,
+                "config.yaml",
+            ],
+        )
+    };
+
+    // `test` never ran, so only `lint` counts towards the threshold
+    let output = run("1");
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("dependency 'lint' failed"), "{}", stdout);
+
+    let output = run("0");
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+}
+
+#[test]
+fn test_failures_below_threshold() {
+    let root = common::project(CONFIG);
+    let output = common::run(
+        root.path(),
+        &["--fail-threshold", "3", "--format", "json", "config.yaml"],
+    );
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["all_passed"], true);
+    let failed: Vec<_> = result["hooks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|hook| hook["success"] == false)
+        .map(|hook| hook["hook_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(failed, vec!["lint", "spelling"]);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use std::fs;
+
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: fmt
+        name: Format
+        entry: "true"
+        language: system
+        pass_filenames: false
+      - id: lint
+        name: Lint
+        entry: >-
+          sh -c 'printf "a.rs:1: unused\na.rs:2: 50%% done\n" >&2; exit 1'
+        language: system
+        pass_filenames: false
+"#;
+
+#[test]
+fn test_github_format_annotates_failures() {
+    let root = common::project(CONFIG);
+    fs::write(root.path().join("a.rs"), "").unwrap();
+    let summary = root.path().join("summary.md");
+
+    let output = common::run_command(root.path())
+        .args(["--format", "github", "a.rs"])
+        .env("GITHUB_STEP_SUMMARY", &summary)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let annotations: Vec<_> = stdout.lines().filter(|l| l.starts_with("::")).collect();
+    assert_eq!(
+        annotations,
+        vec!["::error title=lint::a.rs:1: unused%0Aa.rs:2: 50%25 done"],
+        "{}",
+        stdout
+    );
+
+    let summary = fs::read_to_string(summary).unwrap();
+    assert!(summary.contains("| ✅ | `fmt` |"), "{}", summary);
+    assert!(summary.contains("| ❌ | `lint` |"), "{}", summary);
+}
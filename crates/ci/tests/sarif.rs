@@ -0,0 +1,65 @@
+mod common;
+
+use std::fs;
+
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: fmt
+        name: Format
+        entry: "true"
+        language: system
+        pass_filenames: false
+      - id: lint
+        name: Lint
+        entry: >-
+          sh -c 'printf "\na.rs:1: unused\n\n" >&2; exit 1'
+        language: system
+        pass_filenames: false
+"#;
+
+fn assert_sarif(text: &str) {
+    let sarif: serde_json::Value = serde_json::from_str(text).unwrap();
+    assert_eq!(sarif["version"], "2.1.0");
+    assert!(sarif["$schema"].as_str().unwrap().contains("sarif-2.1.0"));
+
+    let runs = sarif["runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0]["tool"]["driver"]["name"], "pre-commit-rs");
+
+    let results = runs[0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1, "{}", text);
+    assert_eq!(results[0]["ruleId"], "lint");
+    assert_eq!(results[0]["level"], "error");
+    assert_eq!(results[0]["message"]["text"], "a.rs:1: unused");
+}
+
+#[test]
+fn test_sarif_on_stdout() {
+    let root = common::project(CONFIG);
+    let output = common::run(root.path(), &["--format", "sarif", "config.yaml"]);
+
+    assert!(!output.status.success());
+    assert_sarif(&String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn test_sarif_to_output_file() {
+    let root = common::project(CONFIG);
+    let path = root.path().join("results.sarif");
+    let output = common::run(
+        root.path(),
+        &[
+            "--format",
+            "sarif",
+            "--output-file",
+            path.to_str().unwrap(),
+            "config.yaml",
+        ],
+    );
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty(), "{:?}", output);
+    assert_sarif(&fs::read_to_string(path).unwrap());
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::Output;
+
+/// Two independent hooks that each take a while, so run in parallel they
+/// overlap
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: one
+        name: One
+        entry: sleep 0.3
+        language: system
+        always_run: true
+      - id: two
+        name: Two
+        entry: sleep 0.3
+        language: system
+        always_run: true
+"#;
+
+/// Run with `--timing-json` writing `timing.json` in `root`
+fn run_timed(root: &Path, args: &[&str]) -> Output {
+    common::run_command(root)
+        .arg("--timing-json")
+        .arg(root.join("timing.json"))
+        .args(args)
+        .arg("config.yaml")
+        .output()
+        .unwrap()
+}
+
+fn timing(root: &Path) -> serde_json::Value {
+    let text = fs::read_to_string(root.join("timing.json")).unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+#[test]
+fn test_timing_json_shows_parallel_speedup() {
+    let root = common::project(CONFIG);
+    // Independent of the machine's CPU count, which sets the default
+    let output = run_timed(root.path(), &["--parallel", "--jobs", "2"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let timing = timing(root.path());
+    let total = timing["total_duration_ms"].as_u64().unwrap();
+    assert!(total >= 300, "{}", timing);
+    assert!(timing["summed_duration_ms"].as_u64().unwrap() >= 600);
+    assert!(timing["speedup"].as_f64().unwrap() > 1.0, "{}", timing);
+
+    let hooks = timing["hooks"].as_array().unwrap();
+    let ids: Vec<_> = hooks
+        .iter()
+        .map(|h| h["hook_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["one", "two"]);
+    assert!(hooks
+        .iter()
+        .all(|h| h["duration_ms"].as_u64().unwrap() >= 300));
+
+    let levels = timing["levels"].as_array().unwrap();
+    assert_eq!(levels.len(), 1, "{}", timing);
+    assert_eq!(levels[0]["hooks"], serde_json::json!(["one", "two"]));
+    assert!(levels[0]["duration_ms"].as_u64().unwrap() <= total);
+}
+
+#[test]
+fn test_timing_json_sequential_has_no_levels() {
+    let root = common::project(CONFIG);
+    let output = run_timed(root.path(), &["--format", "json"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let timing = timing(root.path());
+    assert!(timing["speedup"].as_f64().unwrap() <= 1.0, "{}", timing);
+    assert_eq!(timing["levels"], serde_json::json!([]));
+
+    // The regular JSON output is unchanged and still goes to stdout
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(result.get("levels").is_none());
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use pre_commit_core::{ExecutionResult, Hook};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LAST_RUN_FILE: &str = "last-run.json";
+
+/// The outcome of the previous `run`, kept so failures can be re-run
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LastRun {
+    pub files: Vec<PathBuf>,
+    pub result: ExecutionResult,
+}
+
+/// Persist the run into the cache directory, creating it if needed
+pub fn save(cache_dir: &Path, last_run: &LastRun) -> Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    let json = serde_json::to_string(last_run)?;
+    fs::write(cache_dir.join(LAST_RUN_FILE), json)?;
+    Ok(())
+}
+
+/// Load the previous run, or `None` if nothing has been recorded yet
+pub fn load(cache_dir: &Path) -> Result<Option<LastRun>> {
+    let path = cache_dir.join(LAST_RUN_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = fs::read_to_string(path)?;
+    Ok(Some(serde_json::from_str(&json)?))
+}
+
+/// Select the hooks that failed in `result`.
+///
+/// Dependencies on hooks outside the selection are dropped: those hooks
+/// already passed, so there is nothing left to wait for.
+pub fn failed_hooks(hooks: &[Hook], result: &ExecutionResult) -> Vec<Hook> {
+    let failed: HashSet<&str> = result
+        .hooks
+        .iter()
+        .filter(|r| !r.success)
+        .map(|r| r.hook_id.as_str())
+        .collect();
+
+    hooks
+        .iter()
+        .filter(|hook| failed.contains(hook.id.as_str()))
+        .map(|hook| {
+            let mut hook = hook.clone();
+            hook.depends_on.retain(|dep| failed.contains(dep.as_str()));
+            hook
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pre_commit_core::HookResult;
+
+    fn make_hook(id: &str, depends_on: &[&str]) -> Hook {
+        Hook {
+            id: id.to_string(),
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn make_result(results: &[(&str, bool)]) -> ExecutionResult {
+        let hooks: Vec<HookResult> = results
+            .iter()
+            .map(|(id, success)| HookResult {
+                hook_id: id.to_string(),
+                success: *success,
+                exit_code: Some(if *success { 0 } else { 1 }),
+                stdout: String::new(),
+                stderr: String::new(),
+                duration_ms: 1,
+                skip_reason: None,
+                severity: Default::default(),
+                cached: false,
+            })
+            .collect();
+        let all_passed = hooks.iter().all(|h| h.success);
+        ExecutionResult {
+            hooks,
+            total_duration_ms: 1,
+            all_passed,
+            levels: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_failed_hooks_selection() {
+        let hooks = vec![
+            make_hook("a", &[]),
+            make_hook("b", &["a"]),
+            make_hook("c", &["b"]),
+        ];
+        let result = make_result(&[("a", true), ("b", false), ("c", false)]);
+
+        let selected = failed_hooks(&hooks, &result);
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected[0].id, "b");
+        assert!(selected[0].depends_on.is_empty());
+        assert_eq!(selected[1].depends_on, vec!["b"]);
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join("pre-commit-rs");
+        assert!(load(&cache_dir).unwrap().is_none());
+
+        let last_run = LastRun {
+            files: vec![PathBuf::from("src/main.rs")],
+            result: make_result(&[("a", false)]),
+        };
+        save(&cache_dir, &last_run).unwrap();
+
+        let loaded = load(&cache_dir).unwrap().unwrap();
+        assert_eq!(loaded.files, last_run.files);
+        assert_eq!(loaded.result.hooks[0].hook_id, "a");
+        assert!(!loaded.result.all_passed);
+    }
+}
@@ -1,15 +1,36 @@
+mod last_run;
+mod timings;
+mod tui;
+
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::builder::PossibleValuesParser;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use crossterm::{cursor, execute, terminal};
-use owo_colors::OwoColorize;
-use pre_commit_core::{Executor, Hook, PlanBuilder};
-use pre_commit_dag::DagBuilder;
+use owo_colors::{OwoColorize, Stream::Stdout, Style};
+use pre_commit_core::command;
+use pre_commit_core::language::LANGUAGES;
+use pre_commit_core::result_cache::ResultCache;
+use pre_commit_core::shell;
+use pre_commit_core::{
+    resolve_jobs, ColorChoice, Config, DependencyFailures, ExecutionOptions, ExecutionPlan,
+    Executor, Hook, OutputCallback, PlanBuilder, PreCommitError, Severity, DEFAULT_STAGE, STAGES,
+};
+use pre_commit_dag::{CriticalStep, DagBuilder};
+use pre_commit_executor_parallel::{cancel_on_ctrl_c, JobBudget, ParallelExecutor};
 use pre_commit_executor_sync::SyncExecutor;
-use pre_commit_parser::{extract_hooks, parse_config_file, validate_config};
-use std::collections::HashMap;
+use pre_commit_files::{
+    changed_files, expand_dirs, git_command, glob_files, parse_push_refs, pushed_files,
+    resolve_git_binary, resolve_repo_root, staged_files, tracked_files,
+};
+use pre_commit_parser::{
+    config_schema, dedup_dependencies, extract_hooks, find_config, parse_config_file,
+    parse_config_file_lenient, resolve_hooks, select_hooks, select_profile, select_stage,
+    skip_hooks, validate_config,
+};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
 use std::process;
 
 #[derive(Parser)]
@@ -18,77 +39,545 @@ use std::process;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Warn about and ignore config keys no field accepts, instead of failing
+    #[arg(long, global = true)]
+    allow_unknown_fields: bool,
+
+    /// When to color output and force color on hooks: `auto` (when stdout is a
+    /// terminal and `NO_COLOR` is unset), `always` or `never`
+    #[arg(long, global = true, default_value = "auto", value_name = "WHEN")]
+    color: ColorChoice,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Run pre-commit hooks
-    Run {
-        /// Path to config file
-        #[arg(short, long, default_value = ".pre-commit-config.yaml")]
-        config: PathBuf,
-
-        /// Run hooks sequentially (by default, runs in parallel)
-        #[arg(short, long)]
-        sequential: bool,
-
-        /// Run hooks on all files in the repository
-        #[arg(long)]
-        all_files: bool,
-
-        /// Files to check (if not provided, checks all staged files)
-        files: Vec<PathBuf>,
-    },
+    Run(Box<RunArgs>),
     /// Install pre-commit hook
     Install {
         /// Path to git repository
         #[arg(short, long, default_value = ".")]
         repo: PathBuf,
+
+        /// Git hook to install; it runs the hooks configured for that stage
+        #[arg(long, default_value = DEFAULT_STAGE, value_parser = stage_parser())]
+        hook_type: String,
     },
     /// Uninstall pre-commit hook
     Uninstall {
         /// Path to git repository
         #[arg(short, long, default_value = ".")]
         repo: PathBuf,
+
+        /// Git hook to remove
+        #[arg(long, default_value = DEFAULT_STAGE, value_parser = stage_parser())]
+        hook_type: String,
+    },
+    /// Check a config file (syntax, ids, dependencies, cycles) without running anything
+    Validate {
+        /// Path to config file (defaults to the nearest `.pre-commit-config.yaml`
+        /// or `.yml`, searching upward to the git root)
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+    },
+    /// Print the execution plan: each level's hooks, which run in parallel
+    List {
+        /// Path to config file (defaults to the nearest `.pre-commit-config.yaml`
+        /// or `.yml`, searching upward to the git root)
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "plain")]
+        format: ListFormat,
+    },
+    /// Print the hook dependency graph, e.g. for `dot -Tsvg`
+    Graph {
+        /// Path to config file (defaults to the nearest `.pre-commit-config.yaml`
+        /// or `.yml`, searching upward to the git root)
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "dot")]
+        format: GraphFormat,
+    },
+    /// Check that the installed hook is current, the config is valid and git works
+    Doctor {
+        /// Path to git repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+
+        /// Path to config file, relative to the repository
+        #[arg(short, long, default_value = ".pre-commit-config.yaml")]
+        config: PathBuf,
+
+        /// Git executable to check (defaults to `git` on PATH)
+        #[arg(long, env = "PRE_COMMIT_GIT", value_name = "PATH")]
+        git_binary: Option<PathBuf>,
+    },
+    /// List the supported hook languages and whether each can run here
+    Languages {
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "plain")]
+        format: ListFormat,
+    },
+    /// Print the JSON Schema for the config file
+    Schema {
+        /// Write the schema to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print a commented starter config to build on
+    SampleConfig {
+        /// Config syntax
+        #[arg(short, long, value_enum, default_value = "yaml")]
+        format: SampleFormat,
     },
+    /// Print a shell completion script, e.g. `pre-commit-rs completions bash >
+    /// /etc/bash_completion.d/pre-commit-rs`
+    Completions {
+        /// Shell to complete for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SampleFormat {
+    Yaml,
+    Toml,
+}
+
+/// `sample-config` output, e.g. `pre-commit-rs sample-config > .pre-commit-config.yaml`
+const SAMPLE_CONFIG_YAML: &str = r#"# pre-commit-rs configuration
+#
+# Hooks run in parallel unless one `depends_on` another, in which case it
+# waits for that hook to pass. Check your edits with `pre-commit-rs validate`.
+repos:
+  - repo: local
+    hooks:
+      # With `pass_filenames`, the staged files matching `files` (a regex) are
+      # appended to `entry`
+      - id: fmt
+        name: Format
+        entry: rustfmt --check --edition 2021
+        language: system
+        files: \.rs$
+        pass_filenames: true
+
+      # Without it the command runs once and finds its own files; it is
+      # skipped when no staged file matches `files`
+      - id: clippy
+        name: Clippy
+        entry: cargo clippy --all-targets -- -D warnings
+        language: system
+        files: \.rs$
+        pass_filenames: false
+        depends_on: [fmt]
+
+      - id: test
+        name: Test
+        entry: cargo test
+        language: system
+        files: \.rs$
+        pass_filenames: false
+        depends_on: [clippy]
+"#;
+
+/// The same starter config for `sample-config --format toml`, e.g. saved as
+/// `pre-commit.toml` and used with `--config pre-commit.toml`
+const SAMPLE_CONFIG_TOML: &str = r#"# pre-commit-rs configuration
+#
+# Hooks run in parallel unless one `depends_on` another, in which case it
+# waits for that hook to pass. Check your edits with `pre-commit-rs validate`.
+[[repos]]
+repo = "local"
+
+# With `pass_filenames`, the staged files matching `files` (a regex) are
+# appended to `entry`
+[[repos.hooks]]
+id = "fmt"
+name = "Format"
+entry = "rustfmt --check --edition 2021"
+language = "system"
+files = '\.rs$'
+pass_filenames = true
+
+# Without it the command runs once and finds its own files; it is skipped
+# when no staged file matches `files`
+[[repos.hooks]]
+id = "clippy"
+name = "Clippy"
+entry = "cargo clippy --all-targets -- -D warnings"
+language = "system"
+files = '\.rs$'
+pass_filenames = false
+depends_on = ["fmt"]
+
+[[repos.hooks]]
+id = "test"
+name = "Test"
+entry = "cargo test"
+language = "system"
+files = '\.rs$'
+pass_filenames = false
+depends_on = ["clippy"]
+"#;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ListFormat {
+    Plain,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Human,
+    /// The run's `ExecutionResult` as JSON, alone on stdout
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum GraphFormat {
+    /// GraphViz DOT
+    Dot,
 }
 
-fn get_staged_files() -> Result<Vec<PathBuf>> {
-    let output = process::Command::new("git")
-        .args(["diff", "--cached", "--name-only", "--diff-filter=ACM"])
+#[derive(Args)]
+struct RunArgs {
+    /// Path to config file (defaults to the nearest `.pre-commit-config.yaml` or
+    /// `.yml`, searching upward to the git root)
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Run hooks sequentially (by default, runs in parallel)
+    #[arg(short, long)]
+    sequential: bool,
+
+    /// Run hooks on every file git tracks instead of the staged ones
+    #[arg(long, conflicts_with = "files")]
+    all_files: bool,
+
+    /// Run hooks on the files changed between this commit and `--to-ref`
+    #[arg(
+        long,
+        value_name = "REF",
+        requires = "to_ref",
+        conflicts_with_all = ["files", "all_files", "globs"]
+    )]
+    from_ref: Option<String>,
+
+    /// Run hooks on the files changed between `--from-ref` and this commit
+    #[arg(long, value_name = "REF", requires = "from_ref")]
+    to_ref: Option<String>,
+
+    /// Discover files matching this glob by walking the tree instead of asking git
+    /// (repeatable)
+    #[arg(long = "glob", value_name = "PATTERN")]
+    globs: Vec<String>,
+
+    /// Repository root used for file discovery and as the hooks' working directory
+    /// (defaults to `git rev-parse --show-toplevel`)
+    #[arg(long)]
+    repo_root: Option<PathBuf>,
+
+    /// Git executable used for file discovery (defaults to `git` on PATH)
+    #[arg(long, env = "PRE_COMMIT_GIT", value_name = "PATH")]
+    git_binary: Option<PathBuf>,
+
+    /// Only run the hooks whose `stages` include this git hook stage
+    #[arg(long, default_value = DEFAULT_STAGE, value_parser = stage_parser())]
+    hook_stage: String,
+
+    /// The commit message file git passes to a `commit-msg` hook; it becomes the
+    /// only file, passed to every hook as its argument even without
+    /// `pass_filenames`
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["files", "all_files", "globs", "from_ref", "rerun_failed"]
+    )]
+    commit_msg_filename: Option<PathBuf>,
+
+    /// Read the `<local ref> <local sha> <remote ref> <remote sha>` lines git
+    /// passes a `pre-push` hook on stdin, and run on the files the pushed
+    /// commits change (every file of a new branch, none of a deleted one)
+    #[arg(
+        long,
+        conflicts_with_all = ["files", "all_files", "globs", "from_ref", "rerun_failed", "commit_msg_filename"]
+    )]
+    pre_push_stdin: bool,
+
+    /// Only run the hooks in this config profile (plus their dependencies)
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Only run the hook with this id (repeatable)
+    #[arg(long = "hook-id", value_name = "ID")]
+    hook_ids: Vec<String>,
+
+    /// With `--hook-id`, also run everything the selected hooks depend on
+    /// (by default their dependencies are ignored)
+    #[arg(long, requires = "hook_ids")]
+    with_deps: bool,
+
+    /// Leave out the hook with this id (repeatable)
+    #[arg(long = "skip-hook", value_name = "ID")]
+    skip_hooks: Vec<String>,
+
+    /// Leave out hooks carrying this tag (repeatable)
+    #[arg(long = "skip-tag", value_name = "TAG")]
+    skip_tags: Vec<String>,
+
+    /// Shell used for `shell: true` hooks, e.g. `bash` (defaults to `sh`, `cmd` on Windows)
+    #[arg(long = "exec", value_name = "SHELL")]
+    shell: Option<String>,
+
+    /// Extra arguments for a hook, as `<id>=<args>` (repeatable)
+    #[arg(long = "hook-args", value_name = "ID=ARGS")]
+    hook_args: Vec<String>,
+
+    /// Shell command to run after the suite finishes, with PRE_COMMIT_RESULT and
+    /// hook counts in its environment (never affects the exit code)
+    #[arg(long, value_name = "COMMAND")]
+    on_complete: Option<String>,
+
+    /// Run each hook attached to a pseudo-terminal so it behaves as if interactive
+    /// (requires the `pty` feature; falls back to pipes otherwise)
+    #[arg(long)]
+    pty: bool,
+
+    /// Fail when no hook actually runs (e.g. every hook was skipped by its filters)
+    #[arg(long)]
+    error_on_no_hooks: bool,
+
+    /// Output format. `json` prints nothing but the result on stdout, with
+    /// progress on stderr and no live display (`--table`, `--verbose` and
+    /// `--tui` are ignored)
+    #[arg(short = 'f', long, value_enum, default_value = "human")]
+    format: OutputFormat,
+
+    /// Print results as a compact table, one aligned row per hook
+    #[arg(long)]
+    table: bool,
+
+    /// Show every hook's command line, file count, exit code and full output,
+    /// even when it passes
+    #[arg(short, long, conflicts_with = "table")]
+    verbose: bool,
+
+    /// Show a full-screen dashboard while hooks run: the plan as a live tree and
+    /// the selected hook's output (falls back to the inline display when stdout
+    /// is not a terminal)
+    #[arg(long, conflicts_with = "sequential")]
+    tui: bool,
+
+    /// When a hook fails, print `git diff` of the working tree to show what the
+    /// hooks changed
+    #[arg(long)]
+    show_diff_on_failure: bool,
+
+    /// Only run the hooks that failed in the previous run, on the same files
+    #[arg(long, conflicts_with = "files")]
+    rerun_failed: bool,
+
+    /// After the run, rank the N slowest hooks with their share of the run's time
+    #[arg(long, value_name = "N")]
+    slowest: Option<usize>,
+
+    /// After the run, print the slowest chain of dependent hooks, which bounds
+    /// the wall time of a parallel run
+    #[arg(long)]
+    critical_path: bool,
+
+    /// Don't record hook durations in `.git/pre-commit-rs/timings.jsonl` or show
+    /// each hook's median from earlier runs
+    #[arg(long)]
+    no_timings: bool,
+
+    /// Reuse a hook's last passing result when its command and matched files
+    /// are unchanged (kept in `.git/pre-commit-rs/cache.json`); hooks with
+    /// `always_run` or no matched files always run
+    #[arg(long)]
+    cache: bool,
+
+    /// Lowest hook severity whose failures fail the run (`error` or `warning`)
+    #[arg(long, default_value = "error", value_name = "SEVERITY")]
+    fail_on: Severity,
+
+    /// Run at most this many hooks at once (0 or unset: one per logical CPU)
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<usize>,
+
+    /// Stop after the first hook failing at or above `--fail-on`; hooks already
+    /// running finish, but no later level starts
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// Print each hook's plan level and the dependency that put it there,
+    /// then exit without running anything
+    #[arg(long)]
+    explain_plan: bool,
+
+    /// Print the ids `--hook-id` accepts (this stage's hooks, narrowed by
+    /// `--profile`) one per line in config order, then exit without running
+    /// anything; meant for shell completion
+    #[arg(long)]
+    list_ids: bool,
+
+    /// Print the exact command line each hook would run, level by level, with
+    /// the files it matched, then exit without running anything
+    #[arg(long, conflicts_with_all = ["format", "tui"])]
+    dry_run: bool,
+
+    /// Files to check, a directory standing for the files git tracks under it
+    /// (if not provided, checks all staged files)
+    files: Vec<PathBuf>,
+}
+
+/// Accepts only the known git hook stages, listing them in `--help`
+fn stage_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(STAGES.iter().copied())
+}
+
+/// Directory for state kept between runs (`<git dir>/pre-commit-rs`)
+fn cache_dir(git: &Path, repo_root: Option<&Path>) -> Result<PathBuf> {
+    let output = git_command(git, repo_root)
+        .args(["rev-parse", "--git-dir"])
         .output()?;
 
     if !output.status.success() {
-        anyhow::bail!("Failed to get staged files from git");
+        anyhow::bail!("Failed to locate the git directory");
     }
 
-    let files = String::from_utf8(output.stdout)?
-        .lines()
-        .map(PathBuf::from)
-        .collect();
+    let git_dir = PathBuf::from(String::from_utf8(output.stdout)?.trim());
+    let git_dir = match repo_root {
+        Some(root) if git_dir.is_relative() => root.join(git_dir),
+        _ => git_dir,
+    };
 
-    Ok(files)
+    Ok(git_dir.join("pre-commit-rs"))
 }
 
-fn get_all_files() -> Result<Vec<PathBuf>> {
-    let output = process::Command::new("git").args(["ls-files"]).output()?;
+/// `--show-diff-on-failure`: print the working tree's unstaged changes, if any.
+/// Does nothing when git can't produce a diff, e.g. outside a repository.
+fn print_diff(git: &Path, repo_root: Option<&Path>, color: bool, out: &mut dyn Write) {
+    let color = if color {
+        "--color=always"
+    } else {
+        "--color=never"
+    };
+    let output = git_command(git, repo_root)
+        .args(["--no-pager", "diff", "--no-ext-diff", color])
+        .output();
+
+    if let Ok(output) = output {
+        if output.status.success() && !output.stdout.is_empty() {
+            writeln!(
+                out,
+                "\n{}",
+                "All changes made by hooks:".if_supports_color(Stdout, |t| t.bold())
+            )
+            .ok();
+            out.write_all(&output.stdout).ok();
+        }
+    }
+}
 
-    if !output.status.success() {
-        anyhow::bail!("Failed to get all files from git");
+/// `--slowest`: the `n` slowest hooks with their share of the run's time, or
+/// of the summed hook time when hooks ran in parallel and overlapped
+fn print_slowest(
+    result: &pre_commit_core::ExecutionResult,
+    n: usize,
+    parallel: bool,
+    out: &mut dyn Write,
+) {
+    let (of_total_ms, share) = if parallel {
+        (
+            result.summed_duration_ms(),
+            "share of summed hook time, not wall time, as hooks ran in parallel",
+        )
+    } else {
+        (result.total_duration_ms, "share of total time")
+    };
+    let slowest = result.slowest_hooks(n, of_total_ms);
+    if slowest.is_empty() {
+        return;
     }
 
-    let files = String::from_utf8(output.stdout)?
-        .lines()
-        .map(PathBuf::from)
-        .collect();
+    writeln!(
+        out,
+        "\n{} {}",
+        "Slowest hooks".if_supports_color(Stdout, |t| t.style(Style::new().bright_blue().bold())),
+        format!("({})", share).if_supports_color(Stdout, |t| t.dimmed())
+    )
+    .ok();
+    let id_width = slowest
+        .iter()
+        .map(|(hook, _)| hook.hook_id.len())
+        .max()
+        .unwrap_or(0);
+    for (rank, (hook, percent)) in slowest.iter().enumerate() {
+        writeln!(
+            out,
+            "  {}. {:<id_width$}  {:>6}ms  {:>5.1}%",
+            rank + 1,
+            hook.hook_id,
+            hook.duration_ms,
+            percent
+        )
+        .ok();
+    }
+}
+
+/// `--critical-path`: each hook on the path with its own and cumulative time
+fn print_critical_path(path: &[CriticalStep], out: &mut dyn Write) {
+    let Some(last) = path.last() else {
+        return;
+    };
+    writeln!(
+        out,
+        "\n{} ({}ms)",
+        "Critical path:".if_supports_color(Stdout, |t| t.style(Style::new().bright_blue().bold())),
+        last.cumulative_ms
+    )
+    .ok();
+
+    let id_width = path
+        .iter()
+        .map(|step| step.hook_id.len())
+        .max()
+        .unwrap_or(0);
+    for (index, step) in path.iter().enumerate() {
+        writeln!(
+            out,
+            "  {}. {:<id_width$}  {:>6}ms  {}",
+            index + 1,
+            step.hook_id,
+            step.duration_ms,
+            format!("(at {}ms)", step.cumulative_ms).if_supports_color(Stdout, |t| t.dimmed())
+        )
+        .ok();
+    }
+}
 
-    Ok(files)
+/// `--format json`: the result, pretty-printed, as the only thing on stdout
+fn print_json(result: &pre_commit_core::ExecutionResult) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(result)?);
+    Ok(())
 }
 
 const SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
-fn print_dag(hooks: &[Hook]) {
-    println!("{}", "Dependency Graph:".bright_blue().bold());
+/// Print the dependency graph; hooks in `skipped` are shown but marked as not running
+fn print_dag(hooks: &[Hook], skipped: &HashSet<String>) {
+    println!(
+        "{}",
+        "Dependency Graph:"
+            .if_supports_color(Stdout, |t| t.style(Style::new().bright_blue().bold()))
+    );
     println!();
 
     // Build dependency map and reverse dependency map
@@ -113,12 +602,21 @@ fn print_dag(hooks: &[Hook]) {
         let prefix = if is_last { "└─" } else { "├─" };
 
         // Print the hook
-        println!(
-            "{} {} {}",
-            prefix.cyan(),
-            "●".green().bold(),
-            hook.name.bold()
-        );
+        if skipped.contains(&hook.id) {
+            println!(
+                "{} {} {}",
+                prefix.if_supports_color(Stdout, |t| t.cyan()),
+                "⏭️".if_supports_color(Stdout, |t| t.dimmed()),
+                format!("{} (skipped)", hook.name).if_supports_color(Stdout, |t| t.dimmed())
+            );
+        } else {
+            println!(
+                "{} {} {}",
+                prefix.if_supports_color(Stdout, |t| t.cyan()),
+                "●".if_supports_color(Stdout, |t| t.style(Style::new().green().bold())),
+                hook.name.if_supports_color(Stdout, |t| t.bold())
+            );
+        }
 
         // Print dependencies (what this hook depends on)
         if !hook.depends_on.is_empty() {
@@ -139,7 +637,11 @@ fn print_dag(hooks: &[Hook]) {
                     "│  ├──▶"
                 };
 
-                println!("{}  {}", connector.cyan(), dep_name.yellow());
+                println!(
+                    "{}  {}",
+                    connector.if_supports_color(Stdout, |t| t.cyan()),
+                    dep_name.if_supports_color(Stdout, |t| t.yellow())
+                );
             }
         }
     }
@@ -147,190 +649,800 @@ fn print_dag(hooks: &[Hook]) {
     println!();
 }
 
+#[derive(Clone, Copy)]
 enum HookStatus {
     Pending,
     Running,
     Success,
     Failed,
+    /// Not run because a dependency failed
+    Skipped,
 }
 
-fn run_hooks(
-    config_path: PathBuf,
-    sequential: bool,
-    all_files: bool,
-    files: Vec<PathBuf>,
-) -> Result<()> {
+fn run_hooks(args: RunArgs, allow_unknown_fields: bool, color: bool) -> Result<()> {
+    // With `--format json`, stdout is reserved for the result
+    let json = args.format == OutputFormat::Json;
+    let say = |message: &str| {
+        if json {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
+    };
+
     // Parse and validate config
-    let config = parse_config_file(&config_path)?;
+    let config = load_config(args.config.clone(), allow_unknown_fields)?;
     validate_config(&config)?;
 
     // Extract hooks
-    let hooks = extract_hooks(&config);
+    let mut hooks = select_stage(&resolved_hooks(&config)?, &args.hook_stage)?;
+    if let Some(profile) = &args.profile {
+        hooks = select_profile(&config, &hooks, profile)?;
+    }
+    if args.list_ids {
+        for hook in &hooks {
+            println!("{}", hook.id);
+        }
+        return Ok(());
+    }
+    if !args.hook_ids.is_empty() {
+        hooks = select_hooks(&hooks, &args.hook_ids, args.with_deps)?;
+    }
+    let (kept, mut skipped) = skip_hooks(&hooks, &args.skip_hooks, &args.skip_tags)?;
+    hooks = kept;
+
+    // `SKIP=id1,id2` bypasses hooks for one run, like upstream pre-commit
+    let skip_env = skip_env_ids(std::env::var("SKIP").ok().as_deref());
+    for id in &skip_env {
+        if !extract_hooks(&config).iter().any(|h| h.is_named(id)) {
+            eprintln!("Warning: ignoring unknown hook '{}' in SKIP", id);
+        }
+    }
+    let mut dag_hooks = hooks.clone();
+    let (kept, env_skipped) = skip_env_hooks(&hooks, &skip_env);
+    hooks = kept;
+    skipped.extend(env_skipped);
 
     if hooks.is_empty() {
-        println!("No hooks to run");
+        if args.error_on_no_hooks {
+            anyhow::bail!("No hooks to run");
+        }
+        say("No hooks to run");
+        if json {
+            print_json(&pre_commit_core::ExecutionResult {
+                hooks: skipped,
+                total_duration_ms: 0,
+                all_passed: true,
+                levels: Vec::new(),
+            })?;
+        }
         return Ok(());
     }
 
+    if args.explain_plan {
+        return print_plan_explanation(&hooks);
+    }
+
+    if let Some(shell) = &args.shell {
+        command::check_shell(shell)
+            .map_err(|e| anyhow::anyhow!("Shell '{}' is not usable: {}", shell, e))?;
+    }
+    let git = resolve_git_binary(args.git_binary)?;
+    let repo_root = resolve_repo_root(args.repo_root, &git)?;
+    let options = ExecutionOptions {
+        repo_root: repo_root.clone(),
+        exclude: config.exclude.clone(),
+        extra_args: parse_hook_args(&args.hook_args, &hooks)?,
+        pty: args.pty,
+        jobs: Some(resolve_jobs(args.jobs)),
+        shell: args.shell.clone(),
+        fail_fast: args.fail_fast || config.fail_fast,
+        no_color: !color,
+        fail_on: args.fail_on,
+        trap_ctrl_c: true,
+        ..Default::default()
+    };
+    if args.pty && !cfg!(all(feature = "pty", unix)) {
+        eprintln!("Warning: built without pty support, running hooks with pipes");
+    }
+    // Kept for the results display, which re-applies each hook's file filters
+    let display_options = options.clone();
+    let cache_dir = cache_dir(&git, repo_root.as_deref()).ok();
+
+    let previous_run = match (&cache_dir, args.rerun_failed) {
+        (Some(dir), true) => last_run::load(dir).unwrap_or_else(|e| {
+            eprintln!("Warning: ignoring the unreadable previous run: {}", e);
+            None
+        }),
+        _ => None,
+    };
+    let timing_history = match &cache_dir {
+        Some(dir) if !args.no_timings => timings::load(dir).unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    if args.rerun_failed && previous_run.is_none() {
+        say("No previous run found, running all hooks\n");
+    }
+
     // Get files to check
-    let files_to_check = if !files.is_empty() {
-        files
-    } else if all_files {
-        get_all_files()?
+    let files_to_check = if let Some(previous_run) = previous_run {
+        hooks = last_run::failed_hooks(&hooks, &previous_run.result);
+        if hooks.is_empty() {
+            say("No hooks failed in the previous run");
+            if json {
+                print_json(&pre_commit_core::ExecutionResult {
+                    hooks: Vec::new(),
+                    total_duration_ms: 0,
+                    all_passed: true,
+                    levels: Vec::new(),
+                })?;
+            }
+            return Ok(());
+        }
+        previous_run.files
+    } else if args.pre_push_stdin {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        pushed_files(&git, repo_root.as_deref(), &parse_push_refs(&input)?)?
+    } else if let Some(message_file) = &args.commit_msg_filename {
+        for hook in &mut hooks {
+            hook.pass_filenames = true;
+        }
+        vec![message_file.clone()]
+    } else if !args.files.is_empty() {
+        expand_dirs(&git, repo_root.as_deref(), &args.files)?
+    } else if !args.globs.is_empty() {
+        let root = repo_root.clone().unwrap_or_else(|| PathBuf::from("."));
+        glob_files(&root, &args.globs)?
+    } else if let (Some(from_ref), Some(to_ref)) = (&args.from_ref, &args.to_ref) {
+        changed_files(&git, repo_root.as_deref(), from_ref, to_ref)?
+    } else if args.all_files {
+        tracked_files(&git, repo_root.as_deref())?
     } else {
-        get_staged_files()?
+        staged_files(&git, repo_root.as_deref())?
     };
 
-    println!(
+    if args.dry_run {
+        let plan = DagBuilder::new().build_plan(&hooks)?;
+        print_dry_run(&plan, &files_to_check, &options);
+        return Ok(());
+    }
+
+    say(&format!(
         "Running {} hooks on {} files...\n",
         hooks.len(),
         files_to_check.len()
-    );
+    ));
+
+    // Display DAG, including the hooks bypassed by SKIP
+    if !json {
+        dag_hooks.retain(|hook| {
+            skip_env.contains(&hook.id) || hooks.iter().any(|kept| kept.id == hook.id)
+        });
+        print_dag(&dag_hooks, &skip_env);
+    }
 
-    // Display DAG
-    print_dag(&hooks);
+    let mut result_cache = match (&cache_dir, args.cache) {
+        (Some(dir), true) => Some(ResultCache::in_file(dir.join("cache.json"))),
+        (None, true) => {
+            eprintln!("Warning: --cache needs a git repository, running every hook");
+            None
+        }
+        _ => None,
+    };
+    let (cached, hooks_to_run, cache_keys) = match &result_cache {
+        Some(cache) => cache.partition(&hooks, &files_to_check, &options),
+        None => (Vec::new(), hooks.clone(), HashMap::new()),
+    };
 
     // Build execution plan
-    let plan = DagBuilder::new().build_plan(&hooks)?;
+    let plan = DagBuilder::new().build_plan(&hooks_to_run)?;
 
     // Execute hooks with live status (parallel by default)
-    let result = if sequential {
-        let executor = SyncExecutor::new();
-        executor.execute(&hooks, &files_to_check)?
+    let mut result = if hooks_to_run.is_empty() {
+        pre_commit_core::ExecutionResult {
+            hooks: Vec::new(),
+            total_duration_ms: 0,
+            all_passed: true,
+            levels: Vec::new(),
+        }
+    } else if args.sequential {
+        let executor = SyncExecutor::with_options(options);
+        executor.execute(&hooks_to_run, &files_to_check)?
+    } else if json {
+        ParallelExecutor::new(plan)
+            .with_options(options)
+            .execute(&hooks_to_run, &files_to_check)?
     } else {
-        execute_with_live_status(plan, &hooks, &files_to_check)?
+        execute_with_live_status(plan, &hooks_to_run, &files_to_check, options, args.tui)?
     };
+    if let Some(cache) = &mut result_cache {
+        cache.record(&cache_keys, &result);
+        cache.save().ok();
+    }
+    // Report every hook in plan order, whether it ran, was cached or skipped
+    result.hooks.extend(skipped.into_iter().chain(cached));
+    DagBuilder::new()
+        .build_plan(&dag_hooks)?
+        .sort_results(&mut result.hooks);
+    result.all_passed = result.passes(args.fail_on);
+
+    // Remember this run for `--rerun-failed` and the timing history; failing to
+    // record either is not fatal
+    if let Some(dir) = &cache_dir {
+        let record = last_run::LastRun {
+            files: files_to_check.clone(),
+            result: result.clone(),
+        };
+        last_run::save(dir, &record).ok();
+        if !args.no_timings {
+            timings::append(dir, &timings::RunTimings::from_result(&result)).ok();
+        }
+    }
 
-    // Display results (only show output for failing hooks)
-    for hook_result in &result.hooks {
-        let status = if hook_result.success { "✅" } else { "❌" };
-        println!(
-            "{} {} ({}ms)",
-            status, hook_result.hook_id, hook_result.duration_ms
-        );
+    // Display results (only show output for failing or verbose hooks)
+    if json {
+        print_json(&result)?;
+    } else if args.table {
+        for row in results_table(&result, &hooks, &files_to_check, &display_options) {
+            println!("{}", row);
+        }
+    } else {
+        let details = args
+            .verbose
+            .then_some((files_to_check.as_slice(), &display_options));
+        print_results(&result, &hooks, details, &timings::medians(&timing_history));
+    }
 
-        // Only show output for failed hooks
-        if !hook_result.success {
-            if !hook_result.stdout.is_empty() {
-                println!("  stdout: {}", hook_result.stdout.trim());
-            }
-            if !hook_result.stderr.is_empty() {
-                println!("  stderr: {}", hook_result.stderr.trim());
-            }
+    if !json {
+        println!("\nTotal time: {}ms", result.total_duration_ms);
+    }
+
+    if let Some(n) = args.slowest {
+        if json {
+            print_slowest(&result, n, !args.sequential, &mut io::stderr());
+        } else {
+            print_slowest(&result, n, !args.sequential, &mut io::stdout());
+        }
+    }
+
+    if args.critical_path {
+        let path = DagBuilder::new().critical_path(&hooks, &result.durations())?;
+        if json {
+            print_critical_path(&path, &mut io::stderr());
+        } else {
+            print_critical_path(&path, &mut io::stdout());
+        }
+    }
+
+    if args.show_diff_on_failure && !result.all_passed {
+        if json {
+            print_diff(&git, repo_root.as_deref(), color, &mut io::stderr());
+        } else {
+            print_diff(&git, repo_root.as_deref(), color, &mut io::stdout());
+        }
+    }
+
+    if let Some(command) = &args.on_complete {
+        if let Err(e) = run_on_complete(command, &result, json) {
+            eprintln!("Warning: --on-complete command failed: {}", e);
         }
     }
 
-    println!("\nTotal time: {}ms", result.total_duration_ms);
+    if args.error_on_no_hooks && result.executed_count() == 0 {
+        eprint!("{}", result.no_hooks_ran_report());
+        anyhow::bail!("No hooks ran");
+    }
 
     if result.all_passed {
-        println!("All hooks passed!");
+        if !json {
+            println!("All hooks passed!");
+        }
         Ok(())
     } else {
         anyhow::bail!("Some hooks failed");
     }
 }
 
+/// Hook ids named in the `SKIP` environment variable (comma-separated)
+fn skip_env_ids(value: Option<&str>) -> HashSet<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Remove the hooks named in `SKIP` (a bare id names that id in every repo),
+/// returning the remaining hooks and a skipped result for each removed one.
+/// Unlike `--skip-hook`, a skipped hook still satisfies its dependents, which
+/// run without waiting for it.
+fn skip_env_hooks(
+    hooks: &[Hook],
+    ids: &HashSet<String>,
+) -> (Vec<Hook>, Vec<pre_commit_core::HookResult>) {
+    let (skipped, kept): (Vec<_>, Vec<_>) = hooks
+        .iter()
+        .cloned()
+        .partition(|hook| ids.iter().any(|id| hook.is_named(id)));
+
+    let skipped_ids: HashSet<&str> = skipped.iter().map(|hook| hook.id.as_str()).collect();
+    let kept = kept
+        .into_iter()
+        .map(|mut hook| {
+            hook.depends_on
+                .retain(|dep| !skipped_ids.contains(dep.as_str()));
+            hook
+        })
+        .collect();
+    let skipped = skipped
+        .iter()
+        .map(|hook| pre_commit_core::HookResult::skipped(&hook.id, "SKIP"))
+        .collect();
+
+    (kept, skipped)
+}
+
+/// `--dry-run`: each level's hooks with the argv of every invocation they
+/// would make, or why they would be skipped
+fn print_dry_run(plan: &ExecutionPlan, files: &[PathBuf], options: &ExecutionOptions) {
+    println!(
+        "{} {} hooks on {} files, nothing is run",
+        "Dry run:".if_supports_color(Stdout, |t| t.style(Style::new().bright_blue().bold())),
+        plan.levels.iter().map(Vec::len).sum::<usize>(),
+        files.len()
+    );
+    for (index, level) in plan.levels.iter().enumerate() {
+        println!(
+            "\n{}",
+            format!("Level {}:", index + 1).if_supports_color(Stdout, |t| t.bold())
+        );
+        for hook in level {
+            match command::dry_run(hook, files, options) {
+                command::DryRun::Skip(reason) => {
+                    println!(
+                        "  {} {}",
+                        hook.id,
+                        format!("(skipped: {})", reason).if_supports_color(Stdout, |t| t.dimmed())
+                    )
+                }
+                command::DryRun::Run(argvs) => {
+                    for argv in argvs {
+                        match argv {
+                            Ok(argv) => println!(
+                                "  {} {}",
+                                hook.id,
+                                shell::join(&argv).if_supports_color(Stdout, |t| t.cyan())
+                            ),
+                            Err(e) => println!(
+                                "  {} {}",
+                                hook.id,
+                                e.to_string().if_supports_color(Stdout, |t| t.red())
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `--explain-plan`: one line per hook with its level and what determined it
+fn print_plan_explanation(hooks: &[Hook]) -> Result<()> {
+    let explanations = DagBuilder::new().explain(hooks)?;
+    let width = explanations
+        .iter()
+        .map(|e| e.hook_id.len())
+        .max()
+        .unwrap_or(0);
+
+    for explanation in &explanations {
+        let reason = match &explanation.determined_by {
+            Some(parent) => format!("after {}", parent),
+            None => "no dependencies".to_string(),
+        };
+        println!(
+            "{:width$}  level {}  {}",
+            explanation.hook_id,
+            explanation.level,
+            reason.if_supports_color(Stdout, |t| t.dimmed())
+        );
+    }
+
+    Ok(())
+}
+
+/// Parse `--hook-args` values into extra arguments keyed by hook id
+fn parse_hook_args(values: &[String], hooks: &[Hook]) -> Result<HashMap<String, Vec<String>>> {
+    let mut extra_args: HashMap<String, Vec<String>> = HashMap::new();
+
+    for value in values {
+        let Some((id, args)) = value.split_once('=') else {
+            anyhow::bail!("Invalid --hook-args '{}', expected <id>=<args>", value);
+        };
+        let id = id.trim();
+        if !hooks.iter().any(|hook| hook.id == id) {
+            anyhow::bail!("--hook-args refers to unknown hook '{}'", id);
+        }
+
+        let args = shell::split(args)
+            .map_err(|e| anyhow::anyhow!("Invalid --hook-args '{}': {}", value, e))?;
+        extra_args.entry(id.to_string()).or_default().extend(args);
+    }
+
+    Ok(extra_args)
+}
+
+/// Build the `--on-complete` command, exposing the outcome through the environment
+fn on_complete_command(
+    command: &str,
+    result: &pre_commit_core::ExecutionResult,
+) -> process::Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = process::Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = process::Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+
+    let count = |predicate: fn(&pre_commit_core::HookResult) -> bool| {
+        result
+            .hooks
+            .iter()
+            .filter(|h| predicate(h))
+            .count()
+            .to_string()
+    };
+
+    shell
+        .env(
+            "PRE_COMMIT_RESULT",
+            if result.all_passed {
+                "success"
+            } else {
+                "failure"
+            },
+        )
+        .env("PRE_COMMIT_TOTAL", result.hooks.len().to_string())
+        .env("PRE_COMMIT_PASSED", count(|h| h.success && !h.is_skipped()))
+        .env("PRE_COMMIT_FAILED", count(|h| h.is_failure()))
+        .env("PRE_COMMIT_SKIPPED", count(|h| h.is_skipped()))
+        .env(
+            "PRE_COMMIT_DURATION_MS",
+            result.total_duration_ms.to_string(),
+        );
+    shell
+}
+
+/// Run the `--on-complete` command; with `to_stderr`, its stdout goes to our
+/// stderr so it can't mix with `--format json` output
+fn run_on_complete(
+    command: &str,
+    result: &pre_commit_core::ExecutionResult,
+    to_stderr: bool,
+) -> Result<()> {
+    let mut command = on_complete_command(command, result);
+    if to_stderr {
+        command.stdout(io::stderr());
+    }
+    let status = command.status()?;
+    if !status.success() {
+        anyhow::bail!("exited with {}", status);
+    }
+    Ok(())
+}
+
+/// Output is shown for failing hooks and for hooks marked `verbose`. With
+/// `--verbose` (`details` holds the run's files and options) every hook shows its
+/// command line, file count, exit code and output. A hook with recorded timings
+/// also shows its median duration from earlier runs.
+fn print_results(
+    result: &pre_commit_core::ExecutionResult,
+    hooks: &[Hook],
+    details: Option<(&[PathBuf], &ExecutionOptions)>,
+    medians: &HashMap<String, u64>,
+) {
+    for hook_result in &result.hooks {
+        if let Some(reason) = &hook_result.skip_reason {
+            println!(
+                "⏭️  {} (skipped: {})",
+                hook_result.hook_id,
+                reason.if_supports_color(Stdout, |t| t.dimmed())
+            );
+            continue;
+        }
+
+        let status = match (hook_result.success, hook_result.severity) {
+            (true, _) => "✅",
+            (false, Severity::Warning) => "⚠️",
+            (false, Severity::Error) => "❌",
+        };
+        match medians.get(&hook_result.hook_id) {
+            _ if hook_result.cached => println!(
+                "{} {} ({})",
+                status,
+                hook_result.hook_id,
+                "cached".if_supports_color(Stdout, |t| t.dimmed())
+            ),
+            Some(median) => println!(
+                "{} {} ({}ms, {})",
+                status,
+                hook_result.hook_id,
+                hook_result.duration_ms,
+                format!("median {}ms", median).if_supports_color(Stdout, |t| t.dimmed())
+            ),
+            None => println!(
+                "{} {} ({}ms)",
+                status, hook_result.hook_id, hook_result.duration_ms
+            ),
+        }
+
+        let hook = hooks.iter().find(|hook| hook.id == hook_result.hook_id);
+        if let (Some((files, options)), Some(hook)) = (details, hook) {
+            for line in verbose_lines(hook, hook_result, files, options) {
+                println!("  {}", line);
+            }
+        }
+
+        if details.is_some() || shows_output(hook_result, hooks) {
+            if let Some(stdout) = hook_result.visible_stdout() {
+                println!("  stdout: {}", stdout.trim());
+            }
+            if let Some(stderr) = hook_result.visible_stderr() {
+                println!("  stderr: {}", stderr.trim());
+            }
+        }
+    }
+}
+
+/// `--verbose` details for a hook that ran: the resolved argv of each
+/// invocation, how many files it matched and its exit code
+fn verbose_lines(
+    hook: &Hook,
+    hook_result: &pre_commit_core::HookResult,
+    files: &[PathBuf],
+    options: &ExecutionOptions,
+) -> Vec<String> {
+    let filtered = hook.filter_files(files, options);
+    let mut lines: Vec<_> = command::batch_argvs(hook, &filtered, options)
+        .into_iter()
+        .map(|argv| match argv {
+            Ok(argv) => format!(
+                "{} {}",
+                "argv:".if_supports_color(Stdout, |t| t.dimmed()),
+                shell::join(&argv).if_supports_color(Stdout, |t| t.cyan())
+            ),
+            Err(e) => format!(
+                "{} {}",
+                "argv:".if_supports_color(Stdout, |t| t.dimmed()),
+                e.to_string().if_supports_color(Stdout, |t| t.red())
+            ),
+        })
+        .collect();
+
+    lines.push(format!(
+        "{} {}",
+        "files:".if_supports_color(Stdout, |t| t.dimmed()),
+        filtered.len()
+    ));
+    let exit_code = match hook_result.exit_code {
+        Some(0) => "0".if_supports_color(Stdout, |t| t.green()).to_string(),
+        Some(code) => code
+            .to_string()
+            .if_supports_color(Stdout, |t| t.red())
+            .to_string(),
+        None => "none".if_supports_color(Stdout, |t| t.red()).to_string(),
+    };
+    lines.push(format!(
+        "{} {}",
+        "exit code:".if_supports_color(Stdout, |t| t.dimmed()),
+        exit_code
+    ));
+    lines
+}
+
+/// Longest output excerpt shown in a `--table` row
+const TABLE_OUTPUT_WIDTH: usize = 60;
+
+/// Rows for `--table`: status, hook id, duration, files processed and the
+/// first line of output, with columns sized to fit the data
+fn results_table(
+    result: &pre_commit_core::ExecutionResult,
+    hooks: &[Hook],
+    files: &[PathBuf],
+    options: &ExecutionOptions,
+) -> Vec<String> {
+    let rows: Vec<_> = result
+        .hooks
+        .iter()
+        .map(|hook_result| {
+            let status = match (&hook_result.skip_reason, hook_result.success) {
+                (Some(_), _) => "⏭️",
+                (None, true) => "✅",
+                (None, false) => "❌",
+            };
+            let file_count = hooks
+                .iter()
+                .find(|hook| hook.id == hook_result.hook_id)
+                .map(|hook| hook.filter_files(files, options).len())
+                .unwrap_or_default();
+            let output = hook_result
+                .skip_reason
+                .as_deref()
+                .or_else(|| hook_result.stdout.lines().find(|l| !l.trim().is_empty()))
+                .or_else(|| hook_result.stderr.lines().find(|l| !l.trim().is_empty()))
+                .unwrap_or_default()
+                .trim();
+            let output = if output.chars().count() > TABLE_OUTPUT_WIDTH {
+                let truncated: String = output.chars().take(TABLE_OUTPUT_WIDTH - 1).collect();
+                format!("{}…", truncated)
+            } else {
+                output.to_string()
+            };
+
+            let time = if hook_result.cached {
+                "cached".to_string()
+            } else {
+                format!("{}ms", hook_result.duration_ms)
+            };
+
+            (
+                status,
+                hook_result.hook_id.as_str(),
+                time,
+                file_count.to_string(),
+                output,
+            )
+        })
+        .collect();
+
+    let id_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0).max(4);
+    let time_width = rows.iter().map(|r| r.2.len()).max().unwrap_or(0).max(4);
+    let files_width = rows.iter().map(|r| r.3.len()).max().unwrap_or(0).max(5);
+
+    let header = format!(
+        "   {:<id_width$}  {:>time_width$}  {:>files_width$}  OUTPUT",
+        "HOOK", "TIME", "FILES"
+    );
+
+    std::iter::once(header)
+        .chain(rows.into_iter().map(|(status, id, time, count, output)| {
+            format!(
+                "{} {:<id_width$}  {:>time_width$}  {:>files_width$}  {}",
+                status, id, time, count, output
+            )
+            .trim_end()
+            .to_string()
+        }))
+        .collect()
+}
+
+fn shows_output(hook_result: &pre_commit_core::HookResult, hooks: &[Hook]) -> bool {
+    !hook_result.success
+        || hooks
+            .iter()
+            .any(|hook| hook.id == hook_result.hook_id && hook.verbose)
+}
+
+/// Progress events sent by the executing thread to whichever display consumes them
 enum StatusUpdate {
     Running(String),
     Completed(String, bool), // hook_id, success
+    Skipped(String),
+    /// A line of a running hook's stdout or stderr, as it is printed
+    Output(String, Vec<u8>),
+    /// Every hook has finished (or the run was cancelled)
     Stop,
 }
 
+/// Run the plan in parallel while a live display consumes its status updates:
+/// the `--tui` dashboard, or the inline spinner list when `tui` is off or
+/// stdout is not a terminal
 fn execute_with_live_status(
     plan: pre_commit_core::ExecutionPlan,
     hooks: &[Hook],
     files: &[PathBuf],
+    mut options: ExecutionOptions,
+    tui: bool,
 ) -> Result<pre_commit_core::ExecutionResult> {
-    use futures::stream::{FuturesUnordered, StreamExt};
+    use std::io::IsTerminal;
     use std::sync::mpsc;
     use std::time::{Duration, Instant};
 
     let start = Instant::now();
+    let tui = tui && io::stdout().is_terminal();
 
     // Channel for status updates
     let (status_tx, status_rx) = mpsc::channel::<StatusUpdate>();
+    let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel::<()>();
 
-    // Print initial status (all pending) to reserve space
-    print_initial_status(hooks);
+    let output_tx = status_tx.clone();
+    options.on_output = Some(OutputCallback::new(move |hook_id, _, chunk| {
+        output_tx
+            .send(StatusUpdate::Output(hook_id.to_string(), chunk))
+            .ok();
+    }));
 
-    // Start animation thread
-    let hooks_clone = hooks.to_vec();
-    let animation_thread = std::thread::spawn(move || {
-        let mut statuses: HashMap<String, HookStatus> = HashMap::new();
-        for hook in &hooks_clone {
-            statuses.insert(hook.id.clone(), HookStatus::Pending);
-        }
+    let levels = plan.levels.clone();
+    let execution_thread = spawn_execution(plan, files.to_vec(), options, status_tx, cancel_rx);
 
-        loop {
-            // Check for status updates (non-blocking)
-            while let Ok(update) = status_rx.try_recv() {
-                match update {
-                    StatusUpdate::Running(hook_id) => {
-                        statuses.insert(hook_id, HookStatus::Running);
-                    }
-                    StatusUpdate::Completed(hook_id, success) => {
-                        statuses.insert(
-                            hook_id,
-                            if success {
-                                HookStatus::Success
-                            } else {
-                                HookStatus::Failed
-                            },
-                        );
+    let all_results = if tui {
+        let outcome = tui::run(&levels, &status_rx);
+        if !matches!(outcome, Ok(tui::Outcome::Finished)) {
+            cancel_tx.send(()).ok();
+        }
+        let results = execution_thread.join().unwrap();
+        outcome?;
+        results
+    } else {
+        // Print initial status (all pending) to reserve space
+        print_initial_status(hooks);
+
+        // Start animation thread; it returns how many lines it last drew
+        let hooks_clone = hooks.to_vec();
+        let animation_thread = std::thread::spawn(move || {
+            let mut statuses: HashMap<String, HookStatus> = HashMap::new();
+            for hook in &hooks_clone {
+                statuses.insert(hook.id.clone(), HookStatus::Pending);
+            }
+            // The latest line each running hook printed, shown under its status
+            let mut latest_output: HashMap<String, String> = HashMap::new();
+            let mut drawn = hooks_clone.len() + 1;
+
+            loop {
+                // Check for status updates (non-blocking)
+                while let Ok(update) = status_rx.try_recv() {
+                    match update {
+                        StatusUpdate::Running(hook_id) => {
+                            statuses.insert(hook_id, HookStatus::Running);
+                        }
+                        StatusUpdate::Completed(hook_id, success) => {
+                            latest_output.remove(&hook_id);
+                            statuses.insert(
+                                hook_id,
+                                if success {
+                                    HookStatus::Success
+                                } else {
+                                    HookStatus::Failed
+                                },
+                            );
+                        }
+                        StatusUpdate::Skipped(hook_id) => {
+                            statuses.insert(hook_id, HookStatus::Skipped);
+                        }
+                        StatusUpdate::Output(hook_id, chunk) => {
+                            if let Some(line) = last_output_line(&chunk) {
+                                latest_output.insert(hook_id, line);
+                            }
+                        }
+                        StatusUpdate::Stop => return drawn,
                     }
-                    StatusUpdate::Stop => return,
                 }
-            }
-
-            // Update display
-            display_inline_status(&statuses, &hooks_clone);
-
-            std::thread::sleep(Duration::from_millis(100));
-        }
-    });
-
-    // Execute hooks in separate thread
-    let files = files.to_vec();
-    let status_tx_clone = status_tx.clone();
-    let execution_thread = std::thread::spawn(move || {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        let mut all_results = Vec::new();
-
-        rt.block_on(async {
-            // Execute each level sequentially
-            for level in &plan.levels {
-                let mut futures = FuturesUnordered::new();
 
-                // Mark all hooks in this level as running
-                for hook in level {
-                    status_tx_clone
-                        .send(StatusUpdate::Running(hook.id.clone()))
-                        .ok();
-                    futures.push(execute_hook_with_id(hook.clone(), files.clone()));
-                }
+                // Update display
+                drawn = display_inline_status(&statuses, &latest_output, &hooks_clone, drawn);
 
-                // Execute all hooks in this level in parallel
-                while let Some((hook_id, result)) = futures.next().await {
-                    status_tx_clone
-                        .send(StatusUpdate::Completed(hook_id.clone(), result.success))
-                        .ok();
-                    all_results.push(result);
-                }
+                std::thread::sleep(Duration::from_millis(100));
             }
         });
 
-        all_results
-    });
-
-    // Wait for execution to complete
-    let all_results = execution_thread.join().unwrap();
+        // Wait for execution to complete, then for the display to catch up
+        let results = execution_thread.join().unwrap();
+        let drawn = animation_thread.join().unwrap();
 
-    // Stop animation thread
-    status_tx.send(StatusUpdate::Stop).ok();
-    animation_thread.join().unwrap();
+        // Clear the inline display
+        clear_inline_status(drawn);
+        results
+    };
 
-    // Clear the inline display
-    clear_inline_status(hooks.len());
+    let Some(all_results) = all_results else {
+        anyhow::bail!("Run cancelled");
+    };
 
     let total_duration = start.elapsed();
     let all_passed = all_results.iter().all(|r| r.success);
@@ -339,101 +1451,189 @@ fn execute_with_live_status(
         hooks: all_results,
         total_duration_ms: total_duration.as_millis() as u64,
         all_passed,
+        levels: Vec::new(),
     })
 }
 
-async fn execute_hook_with_id(
-    hook: Hook,
+/// Execute the plan level by level on its own thread, reporting progress on
+/// `status_tx` and finishing with [`StatusUpdate::Stop`]. Returns `None` when
+/// cancelled from the dashboard; running hooks are killed. On Ctrl-C, running
+/// hooks are killed and the rest reported as cancelled.
+fn spawn_execution(
+    plan: pre_commit_core::ExecutionPlan,
     files: Vec<PathBuf>,
-) -> (String, pre_commit_core::HookResult) {
-    use regex::Regex;
-    use std::time::Instant;
-    use tokio::process::Command;
+    options: ExecutionOptions,
+    status_tx: std::sync::mpsc::Sender<StatusUpdate>,
+    mut cancel_rx: tokio::sync::oneshot::Receiver<()>,
+) -> std::thread::JoinHandle<Option<Vec<pre_commit_core::HookResult>>> {
+    use futures::stream::{FuturesUnordered, StreamExt};
 
-    let hook_id = hook.id.clone();
-    let start = Instant::now();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
 
-    // Filter files based on hook's file pattern
-    let filtered_files = if let Some(pattern) = &hook.files {
-        if let Ok(regex) = Regex::new(pattern) {
-            files
-                .iter()
-                .filter(|f| f.to_str().map(|s| regex.is_match(s)).unwrap_or(false))
-                .cloned()
-                .collect()
-        } else {
-            files.clone()
-        }
-    } else {
-        files.clone()
-    };
+        let run = async {
+            let mut all_results = Vec::new();
+            let mut failures = DependencyFailures::new(options.fail_on);
+            let budget = JobBudget::new(options.jobs);
+            let on_ctrl_c = tokio::spawn(cancel_on_ctrl_c(options.cancel.clone()));
+
+            // Execute each level sequentially
+            for level in &plan.levels {
+                if options.cancel.is_cancelled() {
+                    for hook in level {
+                        status_tx.send(StatusUpdate::Skipped(hook.id.clone())).ok();
+                        all_results.push(pre_commit_core::HookResult::cancelled(hook));
+                    }
+                    continue;
+                }
 
-    // Build command
-    let mut parts = shell_words::split(&hook.entry).unwrap_or_else(|_| vec![hook.entry.clone()]);
+                let mut level_failed = false;
 
-    if hook.pass_filenames && !filtered_files.is_empty() {
-        for file in &filtered_files {
-            if let Some(s) = file.to_str() {
-                parts.push(s.to_string());
+                // Hooks whose dependencies failed are skipped rather than run
+                let mut runnable = Vec::new();
+                for hook in level {
+                    match failures.skip(hook) {
+                        Some(skipped) => {
+                            status_tx.send(StatusUpdate::Skipped(hook.id.clone())).ok();
+                            all_results.push(skipped);
+                        }
+                        None => runnable.push(hook.clone()),
+                    }
+                }
+
+                // Hooks that rewrite shared files run in separate waves
+                for wave in ParallelExecutor::conflict_free_waves(&runnable, &files, &options) {
+                    let mut futures = FuturesUnordered::new();
+
+                    // Hooks show as running once they fit in the job budget
+                    for hook in &wave {
+                        let (budget, status_tx, files, options) =
+                            (&budget, &status_tx, files.clone(), options.clone());
+                        futures.push(async move {
+                            let _permit = budget.acquire(hook).await;
+                            status_tx.send(StatusUpdate::Running(hook.id.clone())).ok();
+                            execute_hook_with_id(hook.clone(), files, options).await
+                        });
+                    }
+
+                    // Execute all hooks in this wave in parallel
+                    while let Some((hook_id, result)) = futures.next().await {
+                        status_tx
+                            .send(StatusUpdate::Completed(hook_id.clone(), result.success))
+                            .ok();
+                        level_failed |= result.fails_under(options.fail_on);
+                        failures.record(&result);
+                        all_results.push(result);
+                    }
+                }
+
+                if level_failed && options.fail_fast {
+                    break;
+                }
             }
-        }
-    }
 
-    // Execute command
-    let result = if parts.is_empty() {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            "Empty command",
-        ))
-    } else {
-        Command::new(&parts[0])
-            .args(&parts[1..])
-            .env("FORCE_COLOR", "1")
-            .env("CLICOLOR_FORCE", "1")
-            .output()
-            .await
-    };
+            on_ctrl_c.abort();
 
-    let duration = start.elapsed();
+            // Hooks finish in any order; report them in plan order
+            plan.sort_results(&mut all_results);
+            all_results
+        };
 
-    let hook_result = match result {
-        Ok(output) => pre_commit_core::HookResult {
-            hook_id: hook.id.clone(),
-            success: output.status.success(),
-            exit_code: output.status.code(),
-            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-            duration_ms: duration.as_millis() as u64,
-        },
-        Err(e) => pre_commit_core::HookResult {
-            hook_id: hook.id.clone(),
-            success: false,
-            exit_code: None,
-            stdout: String::new(),
-            stderr: format!("Failed to execute command: {}", e),
-            duration_ms: duration.as_millis() as u64,
-        },
-    };
+        // Dropping `run` on cancel drops the hooks' futures, which kills their processes
+        let results = rt.block_on(async {
+            tokio::select! {
+                results = run => Some(results),
+                Ok(()) = &mut cancel_rx => None,
+            }
+        });
+        status_tx.send(StatusUpdate::Stop).ok();
+        if results.is_none() {
+            // Don't wait for `--pty` hooks, which run on blocking threads
+            rt.shutdown_background();
+        }
+        results
+    })
+}
 
-    (hook_id, hook_result)
+async fn execute_hook_with_id(
+    hook: Hook,
+    files: Vec<PathBuf>,
+    options: ExecutionOptions,
+) -> (String, pre_commit_core::HookResult) {
+    let result = ParallelExecutor::execute_hook_async(&hook, &files, &options).await;
+    (hook.id, result)
+}
+
+/// Compact summary shown above the per-hook list, e.g. "running 2, done 3/7"
+fn progress_line(statuses: &HashMap<String, HookStatus>, total: usize) -> String {
+    let running = statuses
+        .values()
+        .filter(|status| matches!(status, HookStatus::Running))
+        .count();
+    let done = statuses
+        .values()
+        .filter(|status| {
+            matches!(
+                status,
+                HookStatus::Success | HookStatus::Failed | HookStatus::Skipped
+            )
+        })
+        .count();
+
+    format!("running {}, done {}/{}", running, done, total)
 }
 
 fn print_initial_status(hooks: &[Hook]) {
+    println!(
+        "{}",
+        format!("running 0, done 0/{}", hooks.len()).if_supports_color(Stdout, |t| t.dimmed())
+    );
     for (idx, hook) in hooks.iter().enumerate() {
         let is_last = idx == hooks.len() - 1;
         let prefix = if is_last { "└─" } else { "├─" };
-        println!("{} {} {}", prefix.cyan(), "●".dimmed(), hook.name.dimmed());
+        println!(
+            "{} {} {}",
+            prefix.if_supports_color(Stdout, |t| t.cyan()),
+            "●".if_supports_color(Stdout, |t| t.dimmed()),
+            hook.name.if_supports_color(Stdout, |t| t.dimmed())
+        );
     }
 }
 
-fn display_inline_status(statuses: &HashMap<String, HookStatus>, hooks: &[Hook]) {
+/// Current spinner frame, advancing every 100ms
+fn spinner_frame() -> &'static str {
+    let frame_idx = (std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis()
+        / 100) as usize
+        % SPINNER_FRAMES.len();
+    SPINNER_FRAMES[frame_idx]
+}
+
+/// Redraw the status block over the `drawn` lines printed last time: the
+/// progress line, then each hook with the latest output line of running ones
+/// beneath it. Returns the block's new height, which never shrinks so stale
+/// lines get cleared.
+fn display_inline_status(
+    statuses: &HashMap<String, HookStatus>,
+    latest_output: &HashMap<String, String>,
+    hooks: &[Hook],
+    drawn: usize,
+) -> usize {
     let mut stdout = io::stdout();
+    let width = terminal::size().map_or(80, |(columns, _)| columns as usize);
 
-    // Move cursor up to the start of the status display
-    if !hooks.is_empty() {
-        execute!(stdout, cursor::MoveUp(hooks.len() as u16)).ok();
-        execute!(stdout, cursor::MoveToColumn(0)).ok();
-    }
+    // Move cursor up to the start of the status display (progress line included)
+    execute!(stdout, cursor::MoveUp(drawn as u16)).ok();
+    execute!(stdout, cursor::MoveToColumn(0)).ok();
+
+    execute!(stdout, terminal::Clear(terminal::ClearType::CurrentLine)).ok();
+    println!(
+        "{}",
+        progress_line(statuses, hooks.len()).if_supports_color(Stdout, |t| t.dimmed())
+    );
+    let mut lines = 1;
 
     // Display each hook with its current status
     for (idx, hook) in hooks.iter().enumerate() {
@@ -443,34 +1643,62 @@ fn display_inline_status(statuses: &HashMap<String, HookStatus>, hooks: &[Hook])
 
         let (symbol, color_name) = match status {
             HookStatus::Pending => ("●", "dim"),
-            HookStatus::Running => {
-                let frame_idx = (std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis()
-                    / 100) as usize
-                    % SPINNER_FRAMES.len();
-                (SPINNER_FRAMES[frame_idx], "cyan")
-            }
+            HookStatus::Running => (spinner_frame(), "cyan"),
             HookStatus::Success => ("✅", "green"),
             HookStatus::Failed => ("❌", "red"),
+            HookStatus::Skipped => ("⏭️", "dim"),
         };
 
-        let line = format!("{} {} {}", prefix.cyan(), symbol, hook.name);
+        let line = format!(
+            "{} {} {}",
+            prefix.if_supports_color(Stdout, |t| t.cyan()),
+            symbol,
+            hook.name
+        );
         let colored_line = match color_name {
-            "dim" => line.dimmed().to_string(),
-            "cyan" => line.cyan().to_string(),
-            "green" => line.green().to_string(),
-            "red" => line.red().to_string(),
+            "dim" => line.if_supports_color(Stdout, |t| t.dimmed()).to_string(),
+            "cyan" => line.if_supports_color(Stdout, |t| t.cyan()).to_string(),
+            "green" => line.if_supports_color(Stdout, |t| t.green()).to_string(),
+            "red" => line.if_supports_color(Stdout, |t| t.red()).to_string(),
             _ => line,
         };
 
         // Clear the line and print
         execute!(stdout, terminal::Clear(terminal::ClearType::CurrentLine)).ok();
         println!("{}", colored_line);
+        lines += 1;
+
+        if let (HookStatus::Running, Some(output)) = (status, latest_output.get(&hook.id)) {
+            let indent = if is_last { "     " } else { "│    " };
+            // Wrapped lines would throw off the cursor arithmetic
+            let output: String = output.chars().take(width.saturating_sub(6)).collect();
+            execute!(stdout, terminal::Clear(terminal::ClearType::CurrentLine)).ok();
+            println!(
+                "{}{}",
+                indent.if_supports_color(Stdout, |t| t.cyan()),
+                output.if_supports_color(Stdout, |t| t.dimmed())
+            );
+            lines += 1;
+        }
+    }
+
+    for _ in lines..drawn {
+        execute!(stdout, terminal::Clear(terminal::ClearType::CurrentLine)).ok();
+        println!();
     }
 
     stdout.flush().ok();
+    lines.max(drawn)
+}
+
+/// Last non-blank line of a chunk of hook output, without color codes or
+/// carriage-return redraws
+fn last_output_line(chunk: &[u8]) -> Option<String> {
+    let text = pre_commit_core::diagnostics::strip_ansi(&String::from_utf8_lossy(chunk));
+    text.split(['\n', '\r'])
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .map(str::to_string)
 }
 
 fn clear_inline_status(num_lines: usize) {
@@ -490,49 +1718,40 @@ fn clear_inline_status(num_lines: usize) {
     stdout.flush().ok();
 }
 
-// Helper module for parsing shell commands
-mod shell_words {
-    pub fn split(input: &str) -> Result<Vec<String>, &'static str> {
-        let mut words = Vec::new();
-        let mut current = String::new();
-        let mut in_single_quote = false;
-        let mut in_double_quote = false;
-        let mut chars = input.chars().peekable();
-
-        while let Some(c) = chars.next() {
-            match c {
-                '\'' if !in_double_quote => {
-                    in_single_quote = !in_single_quote;
-                }
-                '"' if !in_single_quote => {
-                    in_double_quote = !in_double_quote;
-                }
-                ' ' | '\t' if !in_single_quote && !in_double_quote => {
-                    if !current.is_empty() {
-                        words.push(current.clone());
-                        current.clear();
-                    }
-                }
-                '\\' if !in_single_quote => {
-                    if let Some(next) = chars.next() {
-                        current.push(next);
-                    }
-                }
-                _ => {
-                    current.push(c);
-                }
-            }
-        }
+/// Stage whose git hook receives the commit message file as `$1`
+const COMMIT_MSG_STAGE: &str = "commit-msg";
 
-        if !current.is_empty() {
-            words.push(current);
-        }
+/// Stage whose git hook receives the refs being pushed on stdin
+const PRE_PUSH_STAGE: &str = "pre-push";
 
-        Ok(words)
-    }
+/// Contents of the git hook script that invokes the runner for `stage`; `exec`
+/// hands it the hook's stdin
+fn hook_script(exe_path: &Path, stage: &str) -> String {
+    let run = match stage {
+        DEFAULT_STAGE => "run".to_string(),
+        COMMIT_MSG_STAGE => format!("run --hook-stage {} --commit-msg-filename \"$1\"", stage),
+        PRE_PUSH_STAGE => format!("run --hook-stage {} --pre-push-stdin", stage),
+        _ => format!("run --hook-stage {}", stage),
+    };
+    format!(
+        r#"#!/usr/bin/env sh
+{}
+#
+# Skip this hook for a single commit with `git commit --no-verify`, or set
+# PRE_COMMIT_RS_SKIP=1 to bypass it without uninstalling.
+if [ "$PRE_COMMIT_RS_SKIP" = "1" ]; then
+    echo "pre-commit-rs: skipped (PRE_COMMIT_RS_SKIP=1)" >&2
+    exit 0
+fi
+exec "{}" {}
+"#,
+        HOOK_MARKER,
+        exe_path.display(),
+        run
+    )
 }
 
-fn install_hook(repo_path: PathBuf) -> Result<()> {
+fn install_hook(repo_path: PathBuf, hook_type: &str) -> Result<()> {
     let git_dir = repo_path.join(".git");
     if !git_dir.exists() {
         anyhow::bail!("Not a git repository");
@@ -543,60 +1762,328 @@ fn install_hook(repo_path: PathBuf) -> Result<()> {
         fs::create_dir(&hooks_dir)?;
     }
 
-    let pre_commit_hook = hooks_dir.join("pre-commit");
+    let hook_path = hooks_dir.join(hook_type);
 
     // Get the absolute path to the current executable
     let current_exe = std::env::current_exe()?;
-    let exe_path = current_exe.display();
 
-    let hook_content = format!(
-        r#"#!/usr/bin/env sh
-# pre-commit-rs hook
-exec "{}" run
-"#,
-        exe_path
-    );
-
-    fs::write(&pre_commit_hook, hook_content)?;
+    fs::write(&hook_path, hook_script(&current_exe, hook_type))?;
 
     // Make executable on Unix
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&pre_commit_hook)?.permissions();
+        let mut perms = fs::metadata(&hook_path)?.permissions();
         perms.set_mode(0o755);
-        fs::set_permissions(&pre_commit_hook, perms)?;
+        fs::set_permissions(&hook_path, perms)?;
     }
 
-    println!("pre-commit hook installed successfully!");
+    println!("{} hook installed successfully!", hook_type);
     Ok(())
 }
 
-fn uninstall_hook(repo_path: PathBuf) -> Result<()> {
-    let pre_commit_hook = repo_path.join(".git").join("hooks").join("pre-commit");
+fn uninstall_hook(repo_path: PathBuf, hook_type: &str) -> Result<()> {
+    let hook_path = repo_path.join(".git").join("hooks").join(hook_type);
 
-    if !pre_commit_hook.exists() {
-        println!("No pre-commit hook found");
+    if !hook_path.exists() {
+        println!("No {} hook found", hook_type);
         return Ok(());
     }
 
-    fs::remove_file(&pre_commit_hook)?;
-    println!("pre-commit hook uninstalled successfully!");
+    fs::remove_file(&hook_path)?;
+    println!("{} hook uninstalled successfully!", hook_type);
+    Ok(())
+}
+
+/// Marker line identifying a hook script written by `install`
+const HOOK_MARKER: &str = "# pre-commit-rs hook";
+
+/// Why an installed hook script needs reinstalling, or `None` if it is current
+fn stale_hook_reason(installed: &str, exe_path: &Path) -> Option<String> {
+    if installed == hook_script(exe_path, DEFAULT_STAGE) {
+        return None;
+    }
+
+    let reason = if !installed.contains(HOOK_MARKER) {
+        "it was not installed by pre-commit-rs".to_string()
+    } else if !installed.contains(&format!("\"{}\"", exe_path.display())) {
+        format!(
+            "it does not run the current executable ({})",
+            exe_path.display()
+        )
+    } else {
+        "it was written by an older version".to_string()
+    };
+    Some(reason)
+}
+
+fn doctor(
+    repo_path: PathBuf,
+    config: PathBuf,
+    git_binary: Option<PathBuf>,
+    allow_unknown_fields: bool,
+) -> Result<()> {
+    let mut problems = 0;
+    let mut report = |check: Result<String>| match check {
+        Ok(message) => println!(
+            "{} {}",
+            "✅".if_supports_color(Stdout, |t| t.green()),
+            message
+        ),
+        Err(e) => {
+            problems += 1;
+            println!("{} {}", "❌".if_supports_color(Stdout, |t| t.red()), e);
+        }
+    };
+
+    report(
+        resolve_git_binary(Some(git_binary.unwrap_or_else(|| PathBuf::from("git"))))
+            .map(|git| format!("git is available ({})", git.display()))
+            .map_err(|e| anyhow::anyhow!("{}", e)),
+    );
+
+    let config_path = repo_path.join(&config);
+    report(
+        parse_config(&config_path, allow_unknown_fields)
+            .and_then(|config| validate_config(&config))
+            .map(|()| format!("{} is valid", config_path.display()))
+            .map_err(|e| anyhow::anyhow!("{}", e)),
+    );
+
+    let hook_path = repo_path.join(".git").join("hooks").join("pre-commit");
+    let current_exe = std::env::current_exe()?;
+    report(match fs::read_to_string(&hook_path) {
+        Err(_) => Err(anyhow::anyhow!(
+            "No pre-commit hook installed; run `pre-commit-rs install`"
+        )),
+        Ok(installed) => match stale_hook_reason(&installed, &current_exe) {
+            None => Ok(format!("{} is up to date", hook_path.display())),
+            Some(reason) => Err(anyhow::anyhow!(
+                "{} needs reinstalling: {}; run `pre-commit-rs install`",
+                hook_path.display(),
+                reason
+            )),
+        },
+    });
+
+    if problems > 0 {
+        anyhow::bail!("{} problem(s) found", problems);
+    }
+    Ok(())
+}
+
+/// Parse the config given with `--config`, or else the one [`find_config`]
+/// finds from the current directory. Errors name the file that was read.
+fn load_config(explicit: Option<PathBuf>, allow_unknown_fields: bool) -> Result<Config> {
+    let path = match explicit {
+        Some(path) => path,
+        None => find_config(&std::env::current_dir()?)?,
+    };
+    match parse_config(&path, allow_unknown_fields) {
+        Ok(config) => Ok(config),
+        Err(e @ PreCommitError::ConfigNotFound(_)) => Err(e.into()),
+        Err(e) => Err(anyhow::anyhow!("{}: {}", path.display(), e)),
+    }
+}
+
+/// Parse a config file. With `--allow-unknown-fields`, unknown keys are
+/// reported as warnings and dropped rather than failing the parse.
+fn parse_config(path: &Path, allow_unknown_fields: bool) -> pre_commit_core::Result<Config> {
+    if !allow_unknown_fields {
+        return parse_config_file(path);
+    }
+    let (config, dropped) = parse_config_file_lenient(path)?;
+    for field in dropped {
+        eprintln!("Warning: ignoring {}", field);
+    }
+    Ok(config)
+}
+
+/// Resolve the config's hooks, warning about (and dropping) repeated
+/// `depends_on` entries
+fn resolved_hooks(config: &Config) -> Result<Vec<Hook>> {
+    let mut hooks = resolve_hooks(config)?;
+    for warning in dedup_dependencies(&mut hooks) {
+        eprintln!("Warning: {}", warning);
+    }
+    Ok(hooks)
+}
+
+fn validate(config_path: Option<PathBuf>, allow_unknown_fields: bool) -> Result<()> {
+    let config = load_config(config_path, allow_unknown_fields)?;
+    validate_config(&config)?;
+    let hooks = resolved_hooks(&config)?;
+    let plan = DagBuilder::new().build_plan(&hooks)?;
+
+    println!("OK: {}", plan.stats());
+    Ok(())
+}
+
+fn list_plan(
+    config_path: Option<PathBuf>,
+    format: ListFormat,
+    allow_unknown_fields: bool,
+) -> Result<()> {
+    let config = load_config(config_path, allow_unknown_fields)?;
+    validate_config(&config)?;
+    let hooks = resolved_hooks(&config)?;
+    let plan = DagBuilder::new().build_plan(&hooks)?;
+
+    match format {
+        ListFormat::Json => println!("{}", serde_json::to_string_pretty(&plan)?),
+        ListFormat::Plain => {
+            for line in plan_lines(&plan) {
+                println!("{}", line);
+            }
+            println!("\n{}", plan.stats());
+        }
+    }
+
+    Ok(())
+}
+
+fn graph(
+    config_path: Option<PathBuf>,
+    format: GraphFormat,
+    allow_unknown_fields: bool,
+) -> Result<()> {
+    let config = load_config(config_path, allow_unknown_fields)?;
+    validate_config(&config)?;
+    let hooks = resolved_hooks(&config)?;
+
+    // A cyclic config can't run, but drawing it is the easiest way to see why
+    let dag = DagBuilder::new();
+    if let Err(e @ PreCommitError::CycleDetected(_)) = dag.build_plan(&hooks) {
+        eprintln!("Warning: {}", e);
+    }
+    match format {
+        GraphFormat::Dot => print!("{}", dag.to_dot(&hooks)?),
+    }
+    Ok(())
+}
+
+/// `list` output, one line per level and per hook, in execution order.
+/// Uncolored so the output can be diffed.
+fn plan_lines(plan: &pre_commit_core::ExecutionPlan) -> Vec<String> {
+    let width = plan
+        .levels
+        .iter()
+        .flatten()
+        .map(|hook| hook.id.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (index, level) in plan.levels.iter().enumerate() {
+        if level.len() > 1 {
+            lines.push(format!(
+                "Level {} ({} hooks in parallel)",
+                index + 1,
+                level.len()
+            ));
+        } else {
+            lines.push(format!("Level {}", index + 1));
+        }
+
+        for hook in level {
+            let mut line = format!("  {:width$}  {}", hook.id, hook.name);
+            if hook.pass_filenames {
+                line.push_str(" [filenames]");
+            }
+            if !hook.depends_on.is_empty() {
+                line.push_str(&format!("  depends on: {}", hook.depends_on.join(", ")));
+            }
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+fn print_schema(output: Option<PathBuf>) -> Result<()> {
+    let schema = config_schema()?;
+
+    match output {
+        Some(path) => {
+            fs::write(&path, schema + "\n")?;
+            println!("Schema written to {}", path.display());
+        }
+        None => println!("{}", schema),
+    }
+
+    Ok(())
+}
+
+/// Generate the completion script for `shell` from the `Cli` definition
+fn print_completions(shell: clap_complete::Shell) -> Result<()> {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+    Ok(())
+}
+
+fn list_languages(format: ListFormat) -> Result<()> {
+    let statuses: Vec<_> = LANGUAGES.iter().map(|language| language.status()).collect();
+
+    match format {
+        ListFormat::Json => println!("{}", serde_json::to_string_pretty(&statuses)?),
+        ListFormat::Plain => {
+            let width = statuses.iter().map(|s| s.language.len()).max().unwrap_or(0);
+            for status in &statuses {
+                let availability = if status.available {
+                    format!("{:9}", "available")
+                        .if_supports_color(Stdout, |t| t.green())
+                        .to_string()
+                } else {
+                    format!("{:9}", "missing")
+                        .if_supports_color(Stdout, |t| t.red())
+                        .to_string()
+                };
+                let path = status
+                    .path
+                    .as_ref()
+                    .map(|path| format!(" ({})", path.display()))
+                    .unwrap_or_default();
+                println!(
+                    "{:width$}  {}  {}{}",
+                    status.language,
+                    availability,
+                    status.description,
+                    path.if_supports_color(Stdout, |t| t.dimmed())
+                );
+            }
+        }
+    }
+
     Ok(())
 }
 
 fn main() -> process::ExitCode {
     let cli = Cli::parse();
+    let color = cli.color.enabled();
+    owo_colors::set_override(color);
 
     let result = match cli.command {
-        Commands::Run {
+        Commands::Run(args) => run_hooks(*args, cli.allow_unknown_fields, color),
+        Commands::Install { repo, hook_type } => install_hook(repo, &hook_type),
+        Commands::Uninstall { repo, hook_type } => uninstall_hook(repo, &hook_type),
+        Commands::Validate { config } => validate(config, cli.allow_unknown_fields),
+        Commands::List { config, format } => list_plan(config, format, cli.allow_unknown_fields),
+        Commands::Graph { config, format } => graph(config, format, cli.allow_unknown_fields),
+        Commands::Doctor {
+            repo,
             config,
-            sequential,
-            all_files,
-            files,
-        } => run_hooks(config, sequential, all_files, files),
-        Commands::Install { repo } => install_hook(repo),
-        Commands::Uninstall { repo } => uninstall_hook(repo),
+            git_binary,
+        } => doctor(repo, config, git_binary, cli.allow_unknown_fields),
+        Commands::Languages { format } => list_languages(format),
+        Commands::Schema { output } => print_schema(output),
+        Commands::SampleConfig { format } => {
+            match format {
+                SampleFormat::Yaml => print!("{}", SAMPLE_CONFIG_YAML),
+                SampleFormat::Toml => print!("{}", SAMPLE_CONFIG_TOML),
+            }
+            Ok(())
+        }
+        Commands::Completions { shell } => print_completions(shell),
     };
 
     match result {
@@ -607,3 +2094,364 @@ fn main() -> process::ExitCode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pre_commit_core::HookResult;
+
+    fn make_result(hook_id: &str, success: bool) -> HookResult {
+        HookResult {
+            hook_id: hook_id.to_string(),
+            success,
+            exit_code: Some(if success { 0 } else { 1 }),
+            stdout: "output".to_string(),
+            stderr: String::new(),
+            duration_ms: 0,
+            skip_reason: None,
+            severity: Default::default(),
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_resolve_repo_root_override() {
+        let root = std::env::temp_dir();
+        assert_eq!(
+            resolve_repo_root(Some(root.clone()), Path::new("git")).unwrap(),
+            Some(root.clone())
+        );
+
+        let missing = root.join("pre-commit-rs-missing-root");
+        assert!(resolve_repo_root(Some(missing), Path::new("git")).is_err());
+    }
+
+    #[test]
+    fn test_resolve_git_binary() {
+        assert_eq!(resolve_git_binary(None).unwrap(), PathBuf::from("git"));
+
+        let missing = std::env::temp_dir().join("pre-commit-rs-missing-git");
+        let err = resolve_git_binary(Some(missing)).unwrap_err();
+        assert!(err.to_string().contains("not executable"));
+    }
+
+    #[test]
+    fn test_stale_hook_reason() {
+        let exe = Path::new("/usr/local/bin/pre-commit-rs");
+        assert_eq!(
+            stale_hook_reason(&hook_script(exe, DEFAULT_STAGE), exe),
+            None
+        );
+
+        let moved = hook_script(Path::new("/old/pre-commit-rs"), DEFAULT_STAGE);
+        assert!(stale_hook_reason(&moved, exe)
+            .unwrap()
+            .contains("current executable"));
+
+        let old_format = format!(
+            "#!/bin/sh\n{}\nexec \"{}\" run\n",
+            HOOK_MARKER,
+            exe.display()
+        );
+        assert!(stale_hook_reason(&old_format, exe)
+            .unwrap()
+            .contains("older version"));
+
+        assert!(stale_hook_reason("#!/bin/sh\nexec husky\n", exe)
+            .unwrap()
+            .contains("not installed by pre-commit-rs"));
+    }
+
+    #[test]
+    fn test_hook_script_skip_env() {
+        let script = hook_script(Path::new("/bin/false"), DEFAULT_STAGE);
+        assert!(script.contains("PRE_COMMIT_RS_SKIP"));
+        assert!(script.contains(r#"exec "/bin/false" run"#));
+
+        let run = |skip: &str| {
+            process::Command::new("sh")
+                .args(["-c", &script])
+                .env("PRE_COMMIT_RS_SKIP", skip)
+                .status()
+                .unwrap()
+        };
+        assert!(run("1").success());
+        assert!(!run("0").success());
+    }
+
+    #[test]
+    fn test_install_hook_type() {
+        let repo = tempfile::tempdir().unwrap();
+        fs::create_dir(repo.path().join(".git")).unwrap();
+
+        install_hook(repo.path().to_path_buf(), "pre-push").unwrap();
+        let hooks_dir = repo.path().join(".git").join("hooks");
+        let script = fs::read_to_string(hooks_dir.join("pre-push")).unwrap();
+        assert!(script.contains(" run --hook-stage pre-push --pre-push-stdin\n"));
+        assert!(!hooks_dir.join("pre-commit").exists());
+
+        uninstall_hook(repo.path().to_path_buf(), "pre-push").unwrap();
+        assert!(!hooks_dir.join("pre-push").exists());
+    }
+
+    #[test]
+    fn test_commit_msg_hook_forwards_message_file() {
+        let repo = tempfile::tempdir().unwrap();
+        fs::create_dir(repo.path().join(".git")).unwrap();
+
+        install_hook(repo.path().to_path_buf(), COMMIT_MSG_STAGE).unwrap();
+        let script = fs::read_to_string(repo.path().join(".git/hooks/commit-msg")).unwrap();
+        assert!(script.contains(r#" run --hook-stage commit-msg --commit-msg-filename "$1""#));
+
+        // With `echo` standing in for the runner, the script prints its argv
+        let script = hook_script(Path::new("echo"), COMMIT_MSG_STAGE);
+        let output = process::Command::new("sh")
+            .args(["-c", &script, "commit-msg", ".git/COMMIT EDITMSG"])
+            .env_remove("PRE_COMMIT_RS_SKIP")
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "run --hook-stage commit-msg --commit-msg-filename .git/COMMIT EDITMSG\n"
+        );
+    }
+
+    #[test]
+    fn test_skip_env_hooks() {
+        let ids = skip_env_ids(Some(" build ,, nope"));
+        assert_eq!(
+            ids,
+            HashSet::from(["build".to_string(), "nope".to_string()])
+        );
+        assert!(skip_env_ids(None).is_empty());
+
+        let hooks = vec![
+            Hook {
+                id: "build".to_string(),
+                ..Default::default()
+            },
+            Hook {
+                id: "test".to_string(),
+                depends_on: vec!["build".to_string()],
+                ..Default::default()
+            },
+        ];
+        let (kept, skipped) = skip_env_hooks(&hooks, &ids);
+        assert_eq!(kept.len(), 1);
+        assert!(kept[0].depends_on.is_empty());
+        assert_eq!(skipped[0].hook_id, "build");
+        assert!(skipped[0].success && skipped[0].is_skipped());
+    }
+
+    #[test]
+    fn test_plan_lines() {
+        let hook = |id: &str, deps: &[&str]| Hook {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            depends_on: deps.iter().map(|dep| dep.to_string()).collect(),
+            ..Default::default()
+        };
+        let levels = |lines: &[String]| lines.iter().filter(|l| l.starts_with("Level")).count();
+
+        let chain = [hook("a", &[]), hook("b", &["a"]), hook("c", &["b"])];
+        let plan = DagBuilder::new().build_plan(&chain).unwrap();
+        let lines = plan_lines(&plan);
+        assert_eq!(levels(&lines), 3);
+        assert_eq!(lines[3], "  b  B  depends on: a");
+
+        let independent = [
+            hook("a", &[]),
+            Hook {
+                pass_filenames: true,
+                ..hook("b", &[])
+            },
+            hook("c", &[]),
+        ];
+        let plan = DagBuilder::new().build_plan(&independent).unwrap();
+        let lines = plan_lines(&plan);
+        assert_eq!(
+            lines,
+            vec![
+                "Level 1 (3 hooks in parallel)",
+                "  a  A",
+                "  b  B [filenames]",
+                "  c  C"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_hook_args() {
+        let hooks = vec![Hook {
+            id: "clippy".to_string(),
+            ..Default::default()
+        }];
+
+        let values = vec![
+            "clippy=--fix".to_string(),
+            "clippy=--allow-dirty 'a b'".to_string(),
+        ];
+        let extra_args = parse_hook_args(&values, &hooks).unwrap();
+        assert_eq!(extra_args["clippy"], vec!["--fix", "--allow-dirty", "a b"]);
+
+        assert!(parse_hook_args(&["missing=--fix".to_string()], &hooks).is_err());
+        assert!(parse_hook_args(&["clippy".to_string()], &hooks).is_err());
+    }
+
+    #[test]
+    fn test_on_complete_environment() {
+        let result = pre_commit_core::ExecutionResult {
+            hooks: vec![make_result("a", true), make_result("b", false)],
+            total_duration_ms: 10,
+            all_passed: false,
+            levels: Vec::new(),
+        };
+
+        let output = on_complete_command(
+            "echo $PRE_COMMIT_RESULT $PRE_COMMIT_TOTAL $PRE_COMMIT_PASSED $PRE_COMMIT_FAILED",
+            &result,
+        )
+        .output()
+        .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            "failure 2 1 1"
+        );
+
+        // A failing notifier is reported but never panics
+        assert!(run_on_complete("exit 3", &result, false).is_err());
+    }
+
+    #[test]
+    fn test_progress_line() {
+        let statuses: HashMap<String, HookStatus> = [
+            ("a".to_string(), HookStatus::Success),
+            ("b".to_string(), HookStatus::Failed),
+            ("c".to_string(), HookStatus::Running),
+            ("d".to_string(), HookStatus::Pending),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(progress_line(&statuses, 4), "running 1, done 2/4");
+    }
+
+    #[test]
+    fn test_live_results_follow_plan_order() {
+        let hook = |id: &str, entry: &str| Hook {
+            id: id.to_string(),
+            entry: entry.to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+        let plan = pre_commit_core::ExecutionPlan::new(vec![vec![
+            hook("slow", "sleep 0.2"),
+            hook("fast", "sleep 0.01"),
+        ]]);
+        let (status_tx, status_rx) = std::sync::mpsc::channel();
+        let (_cancel_tx, cancel_rx) = tokio::sync::oneshot::channel();
+
+        let results = spawn_execution(
+            plan,
+            Vec::new(),
+            ExecutionOptions::default(),
+            status_tx,
+            cancel_rx,
+        )
+        .join()
+        .unwrap()
+        .unwrap();
+
+        // Completion is reported live, in finish order...
+        let completed: Vec<String> = status_rx
+            .try_iter()
+            .filter_map(|update| match update {
+                StatusUpdate::Completed(id, _) => Some(id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(completed, vec!["fast", "slow"]);
+        // ...but the results come back in plan order
+        let ids: Vec<_> = results.iter().map(|r| r.hook_id.as_str()).collect();
+        assert_eq!(ids, vec!["slow", "fast"]);
+    }
+
+    #[test]
+    fn test_results_table_alignment() {
+        let hooks = vec![
+            Hook {
+                id: "fmt".to_string(),
+                files: Some("\\.rs$".to_string()),
+                ..Default::default()
+            },
+            Hook {
+                id: "long-hook-id".to_string(),
+                ..Default::default()
+            },
+        ];
+        let mut failing = make_result("long-hook-id", false);
+        failing.stdout = format!("\n{}\nsecond line", "x".repeat(100));
+        failing.duration_ms = 1234;
+        let result = pre_commit_core::ExecutionResult {
+            hooks: vec![make_result("fmt", true), failing],
+            total_duration_ms: 1234,
+            all_passed: false,
+            levels: Vec::new(),
+        };
+        let files = vec![PathBuf::from("a.rs"), PathBuf::from("b.md")];
+
+        let rows = results_table(&result, &hooks, &files, &ExecutionOptions::default());
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1], "✅ fmt              0ms      1  output");
+        assert!(rows[2].starts_with("❌ long-hook-id  1234ms      2  xxx"));
+        assert!(rows[2].ends_with('…'));
+        assert_eq!(
+            rows[2].rsplit("  ").next().unwrap().chars().count(),
+            TABLE_OUTPUT_WIDTH
+        );
+    }
+
+    #[test]
+    fn test_sample_configs_are_valid() {
+        let yaml = pre_commit_parser::parse_config(SAMPLE_CONFIG_YAML).unwrap();
+        let toml = pre_commit_parser::parse_config_toml(SAMPLE_CONFIG_TOML).unwrap();
+
+        for config in [&yaml, &toml] {
+            validate_config(config).unwrap();
+            let hooks = resolve_hooks(config).unwrap();
+            let plan = DagBuilder::new().build_plan(&hooks).unwrap();
+            assert!(plan.stats().to_string().starts_with("3 hooks, 3 levels"));
+        }
+        assert_eq!(resolve_hooks(&toml).unwrap(), resolve_hooks(&yaml).unwrap());
+    }
+
+    #[test]
+    fn test_last_output_line() {
+        assert_eq!(
+            last_output_line(b"Compiling foo\n\x1b[32m  Finished\x1b[0m dev\n").as_deref(),
+            Some("Finished dev")
+        );
+        assert_eq!(last_output_line(b"10%\r50%\r").as_deref(), Some("50%"));
+        assert_eq!(last_output_line(b" \n\n"), None);
+    }
+
+    #[test]
+    fn test_shows_output_for_verbose_hooks() {
+        let hooks = vec![
+            Hook {
+                id: "quiet".to_string(),
+                ..Default::default()
+            },
+            Hook {
+                id: "loud".to_string(),
+                verbose: true,
+                ..Default::default()
+            },
+        ];
+
+        assert!(!shows_output(&make_result("quiet", true), &hooks));
+        assert!(shows_output(&make_result("quiet", false), &hooks));
+        assert!(shows_output(&make_result("loud", true), &hooks));
+        assert!(shows_output(&make_result("loud", false), &hooks));
+    }
+}
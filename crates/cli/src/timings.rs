@@ -0,0 +1,165 @@
+use anyhow::Result;
+use pre_commit_core::ExecutionResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+const TIMINGS_FILE: &str = "timings.jsonl";
+
+/// Only the most recent runs count towards a hook's median, so it follows
+/// hooks that got faster or slower
+const HISTORY_WINDOW: usize = 20;
+
+/// How long each hook took in one run, keyed by hook id
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunTimings {
+    pub hooks: BTreeMap<String, u64>,
+}
+
+impl RunTimings {
+    /// Durations of the hooks that ran; skipped hooks took no time worth recording
+    pub fn from_result(result: &ExecutionResult) -> Self {
+        Self {
+            hooks: result.durations().into_iter().collect(),
+        }
+    }
+}
+
+/// Append one run as a line of the history file, creating it if needed.
+/// Only the last `HISTORY_WINDOW` runs are kept, since older ones never count
+/// towards a median and would only grow the file.
+pub fn append(cache_dir: &Path, run: &RunTimings) -> Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    let history = load(cache_dir)?;
+    let kept = history.len().saturating_sub(HISTORY_WINDOW - 1);
+    let mut contents = String::new();
+    for recorded in history[kept..].iter().chain([run]) {
+        contents.push_str(&serde_json::to_string(recorded)?);
+        contents.push('\n');
+    }
+    fs::write(cache_dir.join(TIMINGS_FILE), contents)?;
+    Ok(())
+}
+
+/// Every recorded run, oldest first. Lines that don't parse (say, from a run
+/// interrupted mid-write) are ignored.
+pub fn load(cache_dir: &Path) -> Result<Vec<RunTimings>> {
+    let path = cache_dir.join(TIMINGS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let history = fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    Ok(history)
+}
+
+/// Each hook's median duration over the most recent runs it appeared in
+pub fn medians(history: &[RunTimings]) -> HashMap<String, u64> {
+    let mut durations: HashMap<String, Vec<u64>> = HashMap::new();
+    for run in history.iter().rev().take(HISTORY_WINDOW) {
+        for (hook_id, duration_ms) in &run.hooks {
+            durations
+                .entry(hook_id.clone())
+                .or_default()
+                .push(*duration_ms);
+        }
+    }
+    durations
+        .into_iter()
+        .filter_map(|(hook_id, mut values)| Some((hook_id, percentile(&mut values, 50)?)))
+        .collect()
+}
+
+/// The nearest-rank `p`th percentile (0-100) of `values`, or `None` when empty
+pub fn percentile(values: &mut [u64], p: u8) -> Option<u64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let rank = (usize::from(p.min(100)) * values.len()).div_ceil(100);
+    Some(values[rank.saturating_sub(1)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn run(hooks: &[(&str, u64)]) -> RunTimings {
+        RunTimings {
+            hooks: hooks
+                .iter()
+                .map(|(id, duration_ms)| (id.to_string(), *duration_ms))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(percentile(&mut [], 50), None);
+        assert_eq!(percentile(&mut [7], 50), Some(7));
+        assert_eq!(percentile(&mut [30, 10, 20], 50), Some(20));
+        assert_eq!(percentile(&mut [40, 10, 30, 20], 50), Some(20));
+        assert_eq!(percentile(&mut [40, 10, 30, 20], 0), Some(10));
+        assert_eq!(percentile(&mut [40, 10, 30, 20], 100), Some(40));
+        assert_eq!(percentile(&mut [40, 10, 30, 20], 90), Some(40));
+    }
+
+    #[test]
+    fn test_append_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join("pre-commit-rs");
+        assert!(load(&cache_dir).unwrap().is_empty());
+
+        let runs = [
+            run(&[("fmt", 100), ("lint", 900)]),
+            run(&[("fmt", 120)]),
+            run(&[("fmt", 80), ("lint", 1100)]),
+        ];
+        for recorded in &runs {
+            append(&cache_dir, recorded).unwrap();
+        }
+        fs::OpenOptions::new()
+            .append(true)
+            .open(cache_dir.join(TIMINGS_FILE))
+            .unwrap()
+            .write_all(b"{\"hooks\":{\"fmt\":")
+            .unwrap();
+
+        let history = load(&cache_dir).unwrap();
+        assert_eq!(history, runs);
+
+        let medians = medians(&history);
+        assert_eq!(medians["fmt"], 100);
+        assert_eq!(medians["lint"], 900);
+    }
+
+    #[test]
+    fn test_append_keeps_only_the_history_window() {
+        let dir = tempfile::tempdir().unwrap();
+        for duration_ms in 0..HISTORY_WINDOW as u64 + 5 {
+            append(dir.path(), &run(&[("fmt", duration_ms)])).unwrap();
+        }
+
+        let history = load(dir.path()).unwrap();
+        assert_eq!(history.len(), HISTORY_WINDOW);
+        assert_eq!(history[0], run(&[("fmt", 5)]));
+        assert_eq!(
+            history[HISTORY_WINDOW - 1],
+            run(&[("fmt", HISTORY_WINDOW as u64 + 4)])
+        );
+    }
+
+    #[test]
+    fn test_medians_use_recent_runs() {
+        let mut history: Vec<_> = (0..HISTORY_WINDOW).map(|_| run(&[("fmt", 1000)])).collect();
+        history.insert(0, run(&[("fmt", 1), ("old", 5)]));
+
+        let medians = medians(&history);
+        assert_eq!(medians["fmt"], 1000);
+        assert!(!medians.contains_key("old"));
+    }
+}
@@ -0,0 +1,397 @@
+//! `run --tui`: a full-screen dashboard over the live status updates
+
+use crate::{progress_line, spinner_frame, HookStatus, StatusUpdate};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use pre_commit_core::diagnostics::strip_ansi;
+use pre_commit_core::Hook;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::io;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+/// How the dashboard was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Every hook had finished
+    Finished,
+    /// The user quit while hooks were still running
+    Cancelled,
+}
+
+/// Show the dashboard for a plan's `levels` until the user quits, consuming
+/// `updates` as they arrive. The terminal is restored before returning.
+pub fn run(levels: &[Vec<Hook>], updates: &Receiver<StatusUpdate>) -> io::Result<Outcome> {
+    let mut terminal = ratatui::init();
+    let outcome = Dashboard::new(levels).run(&mut terminal, updates);
+    ratatui::restore();
+    outcome
+}
+
+/// One hook's row in the tree and the output collected for it
+struct HookView {
+    id: String,
+    name: String,
+    depends_on: Vec<String>,
+    /// Drawn with `└─` as the last hook of its level
+    last_in_level: bool,
+    status: HookStatus,
+    started: Option<Instant>,
+    elapsed: Option<Duration>,
+    output: Vec<u8>,
+}
+
+impl HookView {
+    fn elapsed(&self) -> Option<Duration> {
+        self.elapsed.or_else(|| self.started.map(|t| t.elapsed()))
+    }
+}
+
+struct Dashboard {
+    /// Hook rows in plan order, with where each level starts
+    hooks: Vec<HookView>,
+    level_starts: Vec<usize>,
+    selected: usize,
+    /// Output pane scroll position; `None` follows the end of the output
+    scroll: Option<u16>,
+    /// Output pane height at the last draw, for paging
+    page_height: u16,
+    finished: bool,
+}
+
+impl Dashboard {
+    fn new(levels: &[Vec<Hook>]) -> Self {
+        let mut hooks = Vec::new();
+        let mut level_starts = Vec::new();
+        for level in levels {
+            level_starts.push(hooks.len());
+            for (idx, hook) in level.iter().enumerate() {
+                hooks.push(HookView {
+                    id: hook.id.clone(),
+                    name: hook.name.clone(),
+                    depends_on: hook.depends_on.clone(),
+                    last_in_level: idx == level.len() - 1,
+                    status: HookStatus::Pending,
+                    started: None,
+                    elapsed: None,
+                    output: Vec::new(),
+                });
+            }
+        }
+
+        Self {
+            hooks,
+            level_starts,
+            selected: 0,
+            scroll: None,
+            page_height: 0,
+            finished: false,
+        }
+    }
+
+    fn run(
+        mut self,
+        terminal: &mut DefaultTerminal,
+        updates: &Receiver<StatusUpdate>,
+    ) -> io::Result<Outcome> {
+        loop {
+            loop {
+                match updates.try_recv() {
+                    Ok(update) => self.apply(update),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.finished = true;
+                        break;
+                    }
+                }
+            }
+
+            terminal.draw(|frame| self.render(frame))?;
+
+            if !event::poll(Duration::from_millis(100))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(self.outcome()),
+                _ if ctrl_c => return Ok(self.outcome()),
+                KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+                KeyCode::Down | KeyCode::Char('j') => self.select(self.selected + 1),
+                KeyCode::PageUp => self.scroll_by(-i32::from(self.page_height.max(1))),
+                KeyCode::PageDown => self.scroll_by(i32::from(self.page_height.max(1))),
+                KeyCode::End => self.scroll = None,
+                _ => {}
+            }
+        }
+    }
+
+    fn outcome(&self) -> Outcome {
+        if self.finished {
+            Outcome::Finished
+        } else {
+            Outcome::Cancelled
+        }
+    }
+
+    fn apply(&mut self, update: StatusUpdate) {
+        let (hook_id, status) = match update {
+            StatusUpdate::Stop => {
+                self.finished = true;
+                return;
+            }
+            StatusUpdate::Output(hook_id, chunk) => {
+                if let Some(hook) = self.hook_mut(&hook_id) {
+                    hook.output.extend_from_slice(&chunk);
+                }
+                return;
+            }
+            StatusUpdate::Running(hook_id) => (hook_id, HookStatus::Running),
+            StatusUpdate::Completed(hook_id, true) => (hook_id, HookStatus::Success),
+            StatusUpdate::Completed(hook_id, false) => (hook_id, HookStatus::Failed),
+            StatusUpdate::Skipped(hook_id) => (hook_id, HookStatus::Skipped),
+        };
+
+        let Some(hook) = self.hook_mut(&hook_id) else {
+            return;
+        };
+        match status {
+            HookStatus::Running => hook.started = Some(Instant::now()),
+            _ => hook.elapsed = Some(hook.started.map_or(Duration::ZERO, |t| t.elapsed())),
+        }
+        hook.status = status;
+    }
+
+    fn hook_mut(&mut self, hook_id: &str) -> Option<&mut HookView> {
+        self.hooks.iter_mut().find(|hook| hook.id == hook_id)
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.hooks.len().saturating_sub(1));
+        if index != self.selected {
+            self.selected = index;
+            self.scroll = None;
+        }
+    }
+
+    /// Scroll the output pane; scrolling past the end follows it again
+    fn scroll_by(&mut self, lines: i32) {
+        let bottom = self.bottom_offset();
+        let current = i32::from(self.scroll.unwrap_or(bottom));
+        let target = (current + lines).max(0);
+        self.scroll = (target < i32::from(bottom)).then_some(target as u16);
+    }
+
+    /// Scroll offset that shows the end of the selected hook's output
+    fn bottom_offset(&self) -> u16 {
+        let lines = self.output_lines().len();
+        lines.saturating_sub(usize::from(self.page_height)) as u16
+    }
+
+    fn output_lines(&self) -> Vec<String> {
+        let Some(hook) = self.hooks.get(self.selected) else {
+            return Vec::new();
+        };
+        let text = String::from_utf8_lossy(&hook.output).replace("\r\n", "\n");
+        strip_ansi(&text)
+            .lines()
+            .map(|line| line.rsplit('\r').next().unwrap_or_default().to_string())
+            .collect()
+    }
+
+    /// Tree rows: a header per level and a row per hook, with the list index
+    /// of the selected hook's row
+    fn tree_items(&self) -> (Vec<ListItem<'static>>, usize) {
+        let mut items = Vec::new();
+        let mut selected_row = 0;
+
+        for (idx, hook) in self.hooks.iter().enumerate() {
+            if let Some(level) = self.level_starts.iter().position(|&start| start == idx) {
+                items.push(ListItem::new(Line::from(
+                    format!("Level {}", level + 1).blue().bold(),
+                )));
+            }
+            if idx == self.selected {
+                selected_row = items.len();
+            }
+
+            let (symbol, color) = match hook.status {
+                HookStatus::Pending => ("●", Color::DarkGray),
+                HookStatus::Running => (spinner_frame(), Color::Cyan),
+                HookStatus::Success => ("✅", Color::Green),
+                HookStatus::Failed => ("❌", Color::Red),
+                HookStatus::Skipped => ("⏭️", Color::DarkGray),
+            };
+            let prefix = if hook.last_in_level {
+                "└─"
+            } else {
+                "├─"
+            };
+            let mut spans = vec![
+                Span::raw(format!("{} ", prefix)).cyan(),
+                Span::styled(format!("{} {}", symbol, hook.name), Style::new().fg(color)),
+            ];
+            if let Some(elapsed) = hook.elapsed() {
+                spans.push(format!("  {:.1}s", elapsed.as_secs_f64()).dark_gray());
+            }
+            if !hook.depends_on.is_empty() {
+                spans.push(format!("  ← {}", hook.depends_on.join(", ")).yellow());
+            }
+            items.push(ListItem::new(Line::from(spans)));
+        }
+
+        (items, selected_row)
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [header, body] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(frame.area());
+        let [tree, output] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(body);
+
+        let statuses: HashMap<String, HookStatus> = self
+            .hooks
+            .iter()
+            .map(|hook| (hook.id.clone(), hook.status))
+            .collect();
+        let state = if self.finished {
+            "finished, q to exit".to_string()
+        } else {
+            format!(
+                "{}, q to cancel",
+                progress_line(&statuses, self.hooks.len())
+            )
+        };
+        frame.render_widget(
+            Line::from(vec![
+                "pre-commit-rs".bold(),
+                format!("  {}  ", state).into(),
+                "↑/↓ select  PgUp/PgDn scroll".dark_gray(),
+            ]),
+            header,
+        );
+
+        let (items, selected_row) = self.tree_items();
+        let mut list_state = ListState::default().with_selected(Some(selected_row));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::bordered().title(" Hooks "))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            tree,
+            &mut list_state,
+        );
+
+        self.page_height = output.height.saturating_sub(2);
+        let title = self
+            .hooks
+            .get(self.selected)
+            .map(|hook| format!(" Output: {} ", hook.id))
+            .unwrap_or_default();
+        let scroll = self.scroll.unwrap_or_else(|| self.bottom_offset());
+        let lines: Vec<Line> = self.output_lines().into_iter().map(Line::from).collect();
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::bordered().title(title))
+                .scroll((scroll, 0)),
+            output,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn hook(id: &str, depends_on: &[&str]) -> Hook {
+        Hook {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn dashboard() -> Dashboard {
+        Dashboard::new(&[
+            vec![hook("fmt", &[]), hook("lint", &[])],
+            vec![hook("test", &["fmt", "lint"])],
+        ])
+    }
+
+    fn screen(dashboard: &mut Dashboard) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal.draw(|frame| dashboard.render(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_renders_levels_and_selected_output() {
+        let mut dashboard = dashboard();
+        dashboard.apply(StatusUpdate::Running("fmt".to_string()));
+        dashboard.apply(StatusUpdate::Output(
+            "fmt".to_string(),
+            b"\x1b[31mDiff in src/lib.rs\x1b[0m\n".to_vec(),
+        ));
+        dashboard.apply(StatusUpdate::Completed("fmt".to_string(), false));
+
+        let screen = screen(&mut dashboard);
+        assert!(screen.contains("Level 1"), "{}", screen);
+        assert!(screen.contains("Level 2"), "{}", screen);
+        assert!(screen.contains("← fmt, lint"), "{}", screen);
+        assert!(screen.contains("Output: fmt"), "{}", screen);
+        assert!(screen.contains("Diff in src/lib.rs"), "{}", screen);
+        assert!(screen.contains("q to cancel"), "{}", screen);
+    }
+
+    #[test]
+    fn test_selection_and_outcome() {
+        let mut dashboard = dashboard();
+        dashboard.apply(StatusUpdate::Output("test".to_string(), b"ok\n".to_vec()));
+
+        dashboard.select(5);
+        assert_eq!(dashboard.selected, 2);
+        assert_eq!(dashboard.output_lines(), vec!["ok"]);
+        let (_, selected_row) = dashboard.tree_items();
+        assert_eq!(selected_row, 4, "two level headers precede the third hook");
+
+        assert_eq!(dashboard.outcome(), Outcome::Cancelled);
+        dashboard.apply(StatusUpdate::Stop);
+        assert_eq!(dashboard.outcome(), Outcome::Finished);
+    }
+
+    #[test]
+    fn test_scrolling_past_the_end_follows_output() {
+        let mut dashboard = dashboard();
+        let output: String = (0..30).map(|i| format!("line {}\n", i)).collect();
+        dashboard.apply(StatusUpdate::Output("fmt".to_string(), output.into_bytes()));
+        dashboard.page_height = 10;
+
+        dashboard.scroll_by(-10);
+        assert_eq!(dashboard.scroll, Some(10));
+        dashboard.scroll_by(5);
+        assert_eq!(dashboard.scroll, Some(15));
+        dashboard.scroll_by(10);
+        assert_eq!(dashboard.scroll, None);
+    }
+}
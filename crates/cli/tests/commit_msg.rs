@@ -0,0 +1,44 @@
+mod common;
+
+use pre_commit_core::ExecutionResult;
+use std::fs;
+
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: message
+        name: Message
+        entry: cat
+        language: system
+        stages: [commit-msg]
+      - id: fmt
+        name: Format
+        entry: "false"
+        language: system
+"#;
+
+#[test]
+fn test_commit_msg_file_is_passed_to_hooks() {
+    let root = common::project(CONFIG);
+    fs::write(root.path().join("MSG"), "feat: add things\n").unwrap();
+
+    let output = common::run(
+        root.path(),
+        &[
+            "--hook-stage",
+            "commit-msg",
+            "--commit-msg-filename",
+            "MSG",
+            "--format",
+            "json",
+        ],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    let result: ExecutionResult = serde_json::from_slice(&output.stdout).unwrap();
+    let ids: Vec<_> = result.hooks.iter().map(|r| r.hook_id.as_str()).collect();
+    assert_eq!(ids, vec!["message"]);
+    // `cat` only prints the message because the path was appended
+    assert_eq!(result.hooks[0].stdout, "feat: add things\n");
+}
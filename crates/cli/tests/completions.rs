@@ -0,0 +1,33 @@
+use std::process::Command;
+
+#[test]
+fn test_bash_completions_cover_subcommands() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pre-commit-rs"))
+        .args(["completions", "bash"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    let script = String::from_utf8_lossy(&output.stdout);
+    assert!(script.contains("complete "), "{}", script);
+    for subcommand in ["run", "install", "uninstall"] {
+        assert!(
+            script.split_whitespace().any(|word| word == subcommand),
+            "{} missing from:\n{}",
+            subcommand,
+            script
+        );
+    }
+}
+
+#[test]
+fn test_unknown_shell_is_rejected() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pre-commit-rs"))
+        .args(["completions", "tcsh"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("possible values"), "{}", stderr);
+}
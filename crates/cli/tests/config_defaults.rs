@@ -0,0 +1,73 @@
+mod common;
+
+use pre_commit_core::ExecutionResult;
+
+/// `lint` fails; `docs` runs in a later level, on pre-push only by default
+const CONFIG: &str = r#"
+fail_fast: true
+default_stages: [pre-push]
+repos:
+  - repo: local
+    hooks:
+      - id: lint
+        name: Lint
+        entry: "false"
+        language: system
+        stages: [pre-commit, pre-push]
+      - id: docs
+        name: Docs
+        entry: echo ran
+        language: system
+        depends_on: [lint]
+"#;
+
+fn hook_ids(output: &std::process::Output) -> Vec<String> {
+    let result: ExecutionResult = serde_json::from_slice(&output.stdout).unwrap();
+    result.hooks.into_iter().map(|r| r.hook_id).collect()
+}
+
+#[test]
+fn test_default_stages_apply_to_hooks_without_stages() {
+    let root = common::project(CONFIG);
+
+    let output = common::run(root.path(), &["--format", "json", "config.yaml"]);
+    assert_eq!(hook_ids(&output), vec!["lint"]);
+}
+
+#[test]
+fn test_config_fail_fast_stops_the_run() {
+    let root = common::project(CONFIG);
+
+    for mode in [&["--sequential"][..], &[]] {
+        let output = common::run(
+            root.path(),
+            &[
+                &[
+                    "--format",
+                    "json",
+                    "--hook-stage",
+                    "pre-push",
+                    "config.yaml",
+                ],
+                mode,
+            ]
+            .concat(),
+        );
+        assert!(!output.status.success());
+        assert_eq!(hook_ids(&output), vec!["lint"]);
+    }
+
+    let config = CONFIG.replace("fail_fast: true", "fail_fast: false");
+    let root = common::project(&config);
+    let output = common::run(
+        root.path(),
+        &[
+            "--format",
+            "json",
+            "--hook-stage",
+            "pre-push",
+            "config.yaml",
+        ],
+    );
+    assert_eq!(hook_ids(&output), vec!["lint", "docs"]);
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use std::fs;
+
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: mark
+        name: Mark
+        entry: touch spawned
+        language: system
+        files: '\.rs$'
+        pass_filenames: true
+      - id: python
+        name: Python
+        entry: touch spawned-python
+        language: system
+        files: '\.py$'
+        pass_filenames: true
+        depends_on: [mark]
+"#;
+
+#[test]
+fn test_dry_run_prints_argv_without_running() {
+    let root = common::project(CONFIG);
+    fs::write(root.path().join("a.rs"), "").unwrap();
+    fs::write(root.path().join("notes.txt"), "").unwrap();
+
+    let output = common::run(root.path(), &["--dry-run", "a.rs", "notes.txt"]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout.contains("2 hooks on 2 files"), "{}", stdout);
+    let mark = stdout.find("mark ").expect(&stdout);
+    let python = stdout.find("python ").expect(&stdout);
+    assert!(mark < python, "{}", stdout);
+    // Only the file matching `files` is appended
+    assert!(stdout.contains("touch spawned a.rs"), "{}", stdout);
+    assert!(!stdout.contains("notes.txt"), "{}", stdout);
+    assert!(stdout.contains("skipped: no files to check"), "{}", stdout);
+
+    assert!(!root.path().join("spawned").exists());
+    assert!(!root.path().join("spawned-python").exists());
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::git;
+use pre_commit_core::ExecutionResult;
+use std::fs;
+use std::process::Output;
+
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: list
+        name: List
+        entry: echo
+        language: system
+        pass_filenames: true
+"#;
+
+/// Committed alongside the config
+const FILES: &[(&str, &str)] = &[
+    ("config.yaml", CONFIG),
+    ("README.md", "old\n"),
+    ("src/lib.rs", ""),
+];
+
+fn listed_files(output: &Output) -> String {
+    assert!(output.status.success(), "{:?}", output);
+    let result: ExecutionResult = serde_json::from_slice(&output.stdout).unwrap();
+    result.hooks[0].stdout.trim().to_string()
+}
+
+#[test]
+fn test_all_files_passes_every_tracked_file() {
+    let root = common::git_repo(FILES);
+    fs::write(root.path().join("README.md"), "new\n").unwrap();
+    git(root.path(), &["add", "README.md"]);
+    fs::write(root.path().join("untracked.txt"), "").unwrap();
+
+    let output = common::run(root.path(), &["--format", "json", "--all-files"]);
+    assert_eq!(listed_files(&output), "README.md config.yaml src/lib.rs");
+
+    // Without the flag only the staged change is checked
+    let output = common::run(root.path(), &["--format", "json"]);
+    assert_eq!(listed_files(&output), "README.md");
+}
+
+#[test]
+fn test_directory_argument_passes_its_tracked_files() {
+    let root = common::git_repo(FILES);
+    fs::write(root.path().join("src/new.rs"), "").unwrap();
+
+    // Resolved against the repo root, not the current directory
+    let output = common::run(root.path(), &["--format", "json", "src"]);
+    assert_eq!(listed_files(&output), "src/lib.rs");
+}
+
+#[test]
+fn test_all_files_conflicts_with_explicit_files() {
+    let root = common::git_repo(FILES);
+
+    let output = common::run(root.path(), &["--all-files", "README.md"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"), "{}", stderr);
+}
+
+#[test]
+fn test_ref_range_selects_only_changed_files() {
+    let root = common::git_repo(FILES);
+    fs::write(root.path().join("README.md"), "new\n").unwrap();
+    git(root.path(), &["commit", "-q", "-am", "second"]);
+
+    let output = common::run(
+        root.path(),
+        &[
+            "--format",
+            "json",
+            "--from-ref",
+            "HEAD~1",
+            "--to-ref",
+            "HEAD",
+        ],
+    );
+    assert_eq!(listed_files(&output), "README.md");
+}
+
+#[test]
+fn test_ref_range_errors() {
+    let root = common::git_repo(FILES);
+
+    let output = common::run(root.path(), &["--from-ref", "HEAD"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--to-ref"), "{}", stderr);
+
+    let output = common::run(
+        root.path(),
+        &["--from-ref", "no-such-ref", "--to-ref", "HEAD"],
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Failed to get changed files from git"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("no-such-ref"), "{}", stderr);
+}
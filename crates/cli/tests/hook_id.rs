@@ -0,0 +1,92 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+/// Three hooks that each leave a marker file behind; `test` depends on `build`
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: lint
+        name: Lint
+        entry: touch lint.ran
+        language: system
+      - id: build
+        name: Build
+        entry: touch build.ran
+        language: system
+      - id: test
+        name: Test
+        entry: touch test.ran
+        language: system
+        depends_on: [build]
+"#;
+
+fn ran(root: &Path) -> Vec<&'static str> {
+    ["lint", "build", "test"]
+        .into_iter()
+        .filter(|id| root.join(format!("{}.ran", id)).exists())
+        .collect()
+}
+
+#[test]
+fn test_hook_id_runs_only_the_named_hook() {
+    for mode in [&["--sequential"][..], &[]] {
+        let root = common::project(CONFIG);
+        let output = common::run(
+            root.path(),
+            &[&["--hook-id", "test", "config.yaml"], mode].concat(),
+        );
+        assert!(output.status.success(), "{:?}", output);
+        assert_eq!(ran(root.path()), vec!["test"]);
+    }
+}
+
+#[test]
+fn test_hook_id_with_deps() {
+    let root = common::project(CONFIG);
+    let output = common::run(
+        root.path(),
+        &["--hook-id", "test", "--with-deps", "config.yaml"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(ran(root.path()), vec!["build", "test"]);
+}
+
+#[test]
+fn test_unknown_hook_id_lists_available_ids() {
+    let root = common::project(CONFIG);
+    let output = common::run(root.path(), &["--hook-id", "tset", "config.yaml"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("No hook with id 'tset' (available: lint, build, test)"),
+        "{}",
+        stderr
+    );
+    assert!(ran(root.path()).is_empty());
+}
+
+#[test]
+fn test_list_ids_prints_only_the_ids() {
+    let root = common::project(CONFIG);
+    let output = common::run(root.path(), &["--list-ids", "config.yaml"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "lint\nbuild\ntest\n"
+    );
+    assert!(ran(root.path()).is_empty());
+}
+
+#[test]
+fn test_list_ids_validates_the_config() {
+    let root = common::project(CONFIG);
+    let broken = CONFIG.replace("depends_on: [build]", "depends_on: [biuld]");
+    fs::write(root.path().join("config.yaml"), broken).unwrap();
+
+    let output = common::run(root.path(), &["--list-ids", "config.yaml"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty(), "{:?}", output);
+}
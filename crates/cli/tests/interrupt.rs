@@ -0,0 +1,130 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// `sleepy` records its pid, then outlives any reasonable test; `later`
+/// depends on it
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: sleepy
+        name: Sleepy
+        entry: sh -c 'echo $$ > sleepy.pid; exec sleep 30'
+        language: system
+        always_run: true
+      - id: later
+        name: Later
+        entry: touch later.ran
+        language: system
+        always_run: true
+        depends_on: [sleepy]
+"#;
+
+fn spawn(root: &Path, config: &str, args: &[&str]) -> Child {
+    fs::write(root.join("config.yaml"), config).unwrap();
+
+    common::run_command(root)
+        .args(args)
+        .arg("config.yaml")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap()
+}
+
+fn wait_until(deadline: Duration, mut done: impl FnMut() -> bool) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < deadline {
+        if done() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+fn kill(signal: &str, pid: &str) -> ExitStatus {
+    Command::new("kill")
+        .args([signal, pid])
+        .stderr(Stdio::null())
+        .status()
+        .unwrap()
+}
+
+/// Send SIGINT once `sleepy` is running; the run must then stop within 5s.
+/// Returns its exit status and stdout, and the hook's pid.
+fn interrupt(root: &Path, config: &str, args: &[&str]) -> (ExitStatus, String, String) {
+    let mut child = spawn(root, config, args);
+    let pid_file = root.join("sleepy.pid");
+    assert!(wait_until(Duration::from_secs(10), || pid_file.exists()));
+
+    kill("-INT", &child.id().to_string());
+    let exited = wait_until(Duration::from_secs(5), || {
+        child.try_wait().unwrap().is_some()
+    });
+    if !exited {
+        child.kill().ok();
+        panic!("run did not stop within 5s of SIGINT");
+    }
+
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let hook_pid = fs::read_to_string(pid_file).unwrap().trim().to_string();
+    (output.status, stdout, hook_pid)
+}
+
+#[test]
+fn test_sigint_kills_running_hooks() {
+    let root = tempfile::tempdir().unwrap();
+    let (status, stdout, hook_pid) = interrupt(root.path(), CONFIG, &["--format", "json"]);
+
+    assert!(!status.success());
+    // Killed and reaped, not orphaned
+    assert!(
+        !kill("-0", &hook_pid).success(),
+        "hook {} still exists",
+        hook_pid
+    );
+    assert!(!root.path().join("later.ran").exists());
+
+    let result: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(result["hooks"][0]["stderr"], "hook killed: run cancelled");
+    assert_eq!(result["hooks"][1]["skip_reason"], "run cancelled");
+}
+
+#[test]
+fn test_sigint_with_live_status_kills_running_hooks() {
+    let root = tempfile::tempdir().unwrap();
+    let (status, stdout, hook_pid) = interrupt(root.path(), CONFIG, &[]);
+
+    assert!(!status.success());
+    assert!(
+        !kill("-0", &hook_pid).success(),
+        "hook {} still exists",
+        hook_pid
+    );
+    assert!(stdout.contains("run cancelled"), "{}", stdout);
+}
+
+#[test]
+fn test_sigint_in_sequential_run_stops_after_the_running_hook() {
+    let root = tempfile::tempdir().unwrap();
+    // Short enough to let the interrupted hook finish
+    let config = CONFIG.replace("exec sleep 30", "sleep 1");
+    let (status, stdout, _) =
+        interrupt(root.path(), &config, &["--sequential", "--format", "json"]);
+
+    assert!(!status.success());
+    assert!(!root.path().join("later.ran").exists());
+
+    let result: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(result["hooks"][0]["success"], true);
+    assert_eq!(result["hooks"][1]["skip_reason"], "run cancelled");
+}
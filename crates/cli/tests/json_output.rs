@@ -0,0 +1,47 @@
+mod common;
+
+use pre_commit_core::ExecutionResult;
+
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: greet
+        name: Greet
+        entry: echo hello
+        language: system
+      - id: broken
+        name: Broken
+        entry: "false"
+        language: system
+        depends_on: [greet]
+"#;
+
+#[test]
+fn test_run_format_json_prints_only_the_result() {
+    for mode in [&["--sequential"][..], &[]] {
+        let root = common::project(CONFIG);
+
+        let output = common::run_command(root.path())
+            .args(["--format", "json", "--on-complete", "echo done"])
+            .args(mode)
+            .arg("config.yaml")
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success());
+        let result: ExecutionResult = serde_json::from_slice(&output.stdout)
+            .unwrap_or_else(|e| panic!("{}: {}", e, String::from_utf8_lossy(&output.stdout)));
+        assert!(!result.all_passed);
+
+        let ids: Vec<_> = result.hooks.iter().map(|r| r.hook_id.as_str()).collect();
+        assert_eq!(ids, vec!["greet", "broken"]);
+        assert_eq!(result.hooks[0].stdout, "hello\n");
+        assert!(result.hooks[1].is_failure());
+
+        // Progress and the --on-complete command's output went to stderr
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Running 2 hooks on 1 files"), "{}", stderr);
+        assert!(stderr.contains("done"), "{}", stderr);
+    }
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::git;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// `files` prints the files it is run on and only runs on push
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: files
+        name: Files
+        entry: sh -c 'echo "$@" >> pushed.log' --
+        language: system
+        files: \.txt$
+        pass_filenames: true
+        stages: [pre-push]
+"#;
+
+/// The first commit; each test then commits a change to `b.txt`
+const FILES: &[(&str, &str)] = &[
+    (".pre-commit-config.yaml", CONFIG),
+    (".gitignore", "pushed.log\n"),
+    ("a.txt", ""),
+    ("b.txt", ""),
+];
+
+fn run_with_stdin(root: &Path, stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pre-commit-rs"))
+        .args(["run", "--hook-stage", "pre-push", "--pre-push-stdin"])
+        .args(["--sequential", "--color", "never"])
+        .current_dir(root)
+        .env_remove("SKIP")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Lines the `files` hook logged, one per run, and clear the log
+fn pushed(root: &Path) -> Vec<String> {
+    let log = root.join("pushed.log");
+    let lines = fs::read_to_string(&log)
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    fs::remove_file(log).ok();
+    lines
+}
+
+#[test]
+fn test_pre_push_stdin_runs_on_the_pushed_range() {
+    let root = common::git_repo(FILES);
+    fs::write(root.path().join("b.txt"), "changed").unwrap();
+    git(root.path(), &["commit", "-q", "-am", "second"]);
+    let (first, second) = (
+        git(root.path(), &["rev-parse", "HEAD~1"]),
+        git(root.path(), &["rev-parse", "HEAD"]),
+    );
+    let zero = "0".repeat(40);
+
+    let update = format!("refs/heads/main {} refs/heads/main {}\n", second, first);
+    let output = run_with_stdin(root.path(), &update);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(pushed(root.path()), vec!["b.txt"]);
+
+    // A new branch has no remote commit to compare with
+    let new_branch = format!("refs/heads/main {} refs/heads/topic {}\n", second, zero);
+    let output = run_with_stdin(root.path(), &new_branch);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(pushed(root.path()), vec!["a.txt b.txt"]);
+
+    // Deleting a branch pushes no files, so the hook is skipped
+    let deletion = format!("(delete) {} refs/heads/topic {}\n", zero, second);
+    let output = run_with_stdin(root.path(), &deletion);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(pushed(root.path()).is_empty());
+
+    let output = run_with_stdin(root.path(), "refs/heads/main\n");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("pre-push stdin"), "{}", stderr);
+}
+
+#[test]
+fn test_installed_pre_push_hook_checks_pushed_files() {
+    let root = common::git_repo(FILES);
+    fs::write(root.path().join("b.txt"), "changed").unwrap();
+    git(root.path(), &["commit", "-q", "-am", "second"]);
+    let remote = tempfile::tempdir().unwrap();
+    git(remote.path(), &["init", "-q", "--bare"]);
+    git(
+        root.path(),
+        &["remote", "add", "origin", remote.path().to_str().unwrap()],
+    );
+
+    let status = Command::new(env!("CARGO_BIN_EXE_pre-commit-rs"))
+        .args(["install", "--hook-type", "pre-push", "--repo"])
+        .arg(root.path())
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success());
+
+    // The first push creates the branch remotely: every file is checked
+    git(
+        root.path(),
+        &["push", "-q", "origin", "HEAD~1:refs/heads/main"],
+    );
+    assert_eq!(pushed(root.path()), vec!["a.txt b.txt"]);
+
+    // Then only what the new commit changed
+    git(root.path(), &["push", "-q", "origin", "main"]);
+    assert_eq!(pushed(root.path()), vec!["b.txt"]);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use std::fs;
+
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: check
+        name: Check
+        entry: "true"
+        language: system
+        always_run: true
+"#;
+
+#[test]
+fn test_corrupt_last_run_is_treated_as_no_previous_run() {
+    let root = common::git_repo(&[("config.yaml", CONFIG)]);
+    let cache_dir = root.path().join(".git/pre-commit-rs");
+    fs::create_dir_all(&cache_dir).unwrap();
+    fs::write(cache_dir.join("last-run.json"), "{\"files\": [").unwrap();
+
+    let output = common::run(root.path(), &["--sequential", "--rerun-failed"]);
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Warning: ignoring the unreadable previous run"),
+        "{}",
+        stderr
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No previous run found"), "{}", stdout);
+}
+
+#[test]
+fn test_rerun_failed_conflicts_with_files() {
+    let root = common::project(CONFIG);
+
+    let output = common::run(root.path(), &["--rerun-failed", "config.yaml"]);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"), "{}", stderr);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+/// `check` logs each run to a file its `files` pattern doesn't match;
+/// `first` always runs, so it is never cached
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: first
+        name: First
+        entry: "true"
+        language: system
+        always_run: true
+      - id: check
+        name: Check
+        entry: sh -c 'echo ran >> runs.log'
+        language: system
+        files: \.rs$
+"#;
+
+/// The project: the config and the one file `check` runs on
+const FILES: &[(&str, &str)] = &[("config.yaml", CONFIG), ("a.rs", "fn a() {}\n")];
+
+fn runs(root: &Path) -> usize {
+    fs::read_to_string(root.join("runs.log"))
+        .unwrap_or_default()
+        .lines()
+        .count()
+}
+
+#[test]
+fn test_cache_reuses_results_until_a_file_changes() {
+    let root = common::git_repo(FILES);
+
+    let output = common::run(root.path(), &["--sequential", "--all-files", "--cache"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(runs(root.path()), 1);
+    assert!(root.path().join(".git/pre-commit-rs/cache.json").exists());
+
+    let output = common::run(root.path(), &["--sequential", "--all-files", "--cache"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(runs(root.path()), 1);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("check (cached)"), "{}", stdout);
+
+    fs::write(root.path().join("a.rs"), "fn a() { changed() }\n").unwrap();
+    let output = common::run(
+        root.path(),
+        &["--sequential", "--all-files", "--cache", "--format", "json"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(runs(root.path()), 2);
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["hooks"][1]["hook_id"], "check");
+    assert!(result["hooks"][1].get("cached").is_none(), "{}", result);
+}
+
+#[test]
+fn test_cached_results_keep_config_order() {
+    let root = common::git_repo(FILES);
+    common::run(root.path(), &["--sequential", "--all-files", "--cache"]);
+
+    let output = common::run(
+        root.path(),
+        &["--sequential", "--all-files", "--cache", "--format", "json"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let ids: Vec<&str> = result["hooks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hook| hook["hook_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["first", "check"]);
+    assert_eq!(result["hooks"][1]["cached"], true);
+}
+
+#[test]
+fn test_without_cache_flag_every_run_executes() {
+    let root = common::git_repo(FILES);
+    common::run(root.path(), &["--sequential", "--all-files", "--cache"]);
+    common::run(root.path(), &["--sequential", "--all-files"]);
+    assert_eq!(runs(root.path()), 2);
+}
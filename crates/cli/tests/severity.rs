@@ -0,0 +1,88 @@
+mod common;
+
+use pre_commit_core::ExecutionResult;
+
+/// `lint` fails at warning severity; `docs` depends on it
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: lint
+        name: Lint
+        entry: "false"
+        language: system
+        severity: warning
+        always_run: true
+      - id: docs
+        name: Docs
+        entry: echo ran
+        language: system
+        always_run: true
+        depends_on: [lint]
+"#;
+
+#[test]
+fn test_warning_failure_does_not_block_dependents() {
+    let root = common::project(CONFIG);
+    let run = |fail_on: &str| {
+        common::run(
+            root.path(),
+            &["--format", "json", "--fail-on", fail_on, "config.yaml"],
+        )
+    };
+
+    let output = run("error");
+    assert!(output.status.success(), "{:?}", output);
+    let result: ExecutionResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(result.hooks[0].is_failure());
+    assert!(result.hooks[1].success);
+    assert_eq!(result.hooks[1].stdout, "ran\n");
+
+    // Counted as a failure, the warning skips its dependent
+    let output = run("warning");
+    assert!(!output.status.success());
+    let result: ExecutionResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        result.hooks[1].skip_reason.as_deref(),
+        Some("dependency 'lint' failed")
+    );
+}
+
+#[test]
+fn test_fail_fast_ignores_failures_below_the_threshold() {
+    let root = common::project(CONFIG);
+    let run = |fail_on: &str, args: &[&str]| {
+        common::run_command(root.path())
+            .args(["--fail-fast", "--fail-on", fail_on])
+            .args(args)
+            .arg("config.yaml")
+            .output()
+            .unwrap()
+    };
+
+    for args in [
+        &["--format", "json"][..],
+        &["--format", "json", "--sequential"],
+    ] {
+        let output = run("error", args);
+        assert!(output.status.success(), "{:?}", output);
+        let result: ExecutionResult = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(result.hooks.len(), 2);
+        assert_eq!(result.hooks[1].stdout, "ran\n");
+
+        // Counted as a failure, the warning stops the run before `docs`
+        let output = run("warning", args);
+        assert!(!output.status.success());
+        let result: ExecutionResult = serde_json::from_slice(&output.stdout).unwrap();
+        let ids: Vec<_> = result.hooks.iter().map(|r| r.hook_id.as_str()).collect();
+        assert_eq!(ids, vec!["lint"]);
+    }
+
+    // The live status display runs the levels itself
+    let output = run("error", &[]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("docs ("));
+    let output = run("warning", &[]);
+    assert!(!output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("docs ("));
+}
@@ -0,0 +1,66 @@
+mod common;
+
+/// Two repos each with a `fmt` hook; `lint` runs after the frontend's
+const CONFIG: &str = r#"
+repos:
+  - repo: frontend
+    hooks:
+      - id: fmt
+        name: Format frontend
+        entry: touch frontend.ran
+        language: system
+  - repo: backend
+    hooks:
+      - id: fmt
+        name: Format backend
+        entry: touch backend.ran
+        language: system
+      - id: lint
+        name: Lint
+        entry: sh -c 'test -e frontend.ran && touch lint.ran'
+        language: system
+        depends_on: [frontend:fmt]
+"#;
+
+#[test]
+fn test_same_id_in_two_repos_runs_both() {
+    for mode in [&["--sequential"][..], &[]] {
+        let root = common::project(CONFIG);
+        let output = common::run(
+            root.path(),
+            &[mode, &["--format", "json", "config.yaml"]].concat(),
+        );
+
+        assert!(output.status.success(), "{:?}", output);
+        for ran in ["frontend.ran", "backend.ran", "lint.ran"] {
+            assert!(root.path().join(ran).exists(), "{} missing", ran);
+        }
+
+        let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let mut ids: Vec<_> = result["hooks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hook| hook["hook_id"].as_str().unwrap())
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec!["backend:fmt", "frontend:fmt", "lint"]);
+    }
+}
+
+#[test]
+fn test_skip_env_with_qualified_id() {
+    let root = common::project(CONFIG);
+    let output = common::run_command(root.path())
+        .args(["--sequential", "config.yaml"])
+        .env("SKIP", "backend:fmt")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(root.path().join("frontend.ran").exists());
+    assert!(!root.path().join("backend.ran").exists());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("ignoring unknown hook"), "{}", stderr);
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::git_repo;
+use std::fs;
+use std::path::Path;
+use std::process::Output;
+
+/// A hook that rewrites the tracked file, then fails or passes
+fn config(exit_code: u8) -> String {
+    format!(
+        r#"
+repos:
+  - repo: local
+    hooks:
+      - id: rewrite
+        name: Rewrite
+        entry: sh -c 'echo new > tracked.txt; exit {}'
+        language: system
+"#,
+        exit_code
+    )
+}
+
+/// Run the `rewrite` hook on `tracked.txt`, reset to its committed content
+fn run_rewrite(root: &Path, exit_code: u8, color: &str) -> Output {
+    fs::write(root.join("config.yaml"), config(exit_code)).unwrap();
+    fs::write(root.join("tracked.txt"), "old\n").unwrap();
+
+    common::run_command_with_color(root, color)
+        .args(["--sequential", "--show-diff-on-failure", "tracked.txt"])
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_diff_shown_when_a_hook_fails() {
+    let root = git_repo(&[("tracked.txt", "old\n")]);
+    let output = run_rewrite(root.path(), 1, "always");
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("All changes made by hooks:"), "{}", stdout);
+    assert!(stdout.contains("-old"), "{}", stdout);
+    // Colored, so the `+` and the text are separate escape sequences
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.contains("+") && line.ends_with("new\x1b[m")),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_no_diff_when_hooks_pass() {
+    let root = git_repo(&[("tracked.txt", "old\n")]);
+    let output = run_rewrite(root.path(), 0, "always");
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("All changes made by hooks:"), "{}", stdout);
+}
+
+#[test]
+fn test_no_diff_outside_a_git_repo() {
+    let root = tempfile::tempdir().unwrap();
+    let output = run_rewrite(root.path(), 1, "always");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Some hooks failed"), "{}", stderr);
+    assert!(!stdout.contains("All changes made by hooks:"), "{}", stdout);
+}
+
+#[test]
+fn test_diff_uncolored_with_color_never() {
+    let root = git_repo(&[("tracked.txt", "old\n")]);
+    let output = run_rewrite(root.path(), 1, "never");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("All changes made by hooks:"), "{}", stdout);
+    assert!(stdout.lines().any(|line| line == "+new"), "{}", stdout);
+    assert!(!stdout.contains('\x1b'), "{}", stdout);
+}
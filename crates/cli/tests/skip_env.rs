@@ -0,0 +1,42 @@
+mod common;
+
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: build
+        name: Build
+        entry: touch build.ran
+        language: system
+      - id: test
+        name: Test
+        entry: touch test.ran
+        language: system
+        depends_on: [build]
+"#;
+
+#[test]
+fn test_skip_env_bypasses_named_hooks() {
+    for mode in [&["--sequential"][..], &[]] {
+        let root = common::project(CONFIG);
+
+        let output = common::run_command(root.path())
+            .args(mode)
+            .arg("config.yaml")
+            .env("SKIP", " build , nope")
+            .output()
+            .unwrap();
+
+        assert!(output.status.success(), "{:?}", output);
+        // `build` never ran, but its dependent still did
+        assert!(!root.path().join("build.ran").exists());
+        assert!(root.path().join("test.ran").exists());
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("ignoring unknown hook 'nope' in SKIP"),
+            "{}",
+            stderr
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::fs;
+use std::process::{Command, Output};
+
+fn validate(config: &str) -> Output {
+    validate_with(config, &[])
+}
+
+fn validate_with(config: &str, args: &[&str]) -> Output {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.yaml");
+    fs::write(&path, config).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_pre-commit-rs"))
+        .arg("validate")
+        .arg("--config")
+        .arg(&path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn config(hooks: &str) -> String {
+    format!("repos:\n  - repo: local\n    hooks:\n{}", hooks)
+}
+
+#[test]
+fn test_validate_good_config() {
+    let output = validate(&config(
+        r#"
+      - {id: fmt, name: Format, entry: cargo fmt, language: system}
+      - {id: lint, name: Lint, entry: cargo clippy, language: system, depends_on: [fmt]}
+      - {id: test, name: Test, entry: cargo test, language: system}
+"#,
+    ));
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("OK: 3 hooks, 2 levels"), "{}", stdout);
+}
+
+#[test]
+fn test_validate_cyclic_config() {
+    let output = validate(&config(
+        r#"
+      - {id: a, name: A, entry: "true", language: system, depends_on: [c]}
+      - {id: b, name: B, entry: "true", language: system, depends_on: [a]}
+      - {id: c, name: C, entry: "true", language: system, depends_on: [b]}
+"#,
+    ));
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("a -> c -> b -> a"), "{}", stderr);
+}
+
+#[test]
+fn test_validate_dangling_dependency() {
+    let output = validate(&config(
+        r#"
+      - {id: lint, name: Lint, entry: cargo clippy, language: system, depends_on: [fmt]}
+"#,
+    ));
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Hook 'lint' depends on non-existent hook 'fmt'"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_validate_self_dependency() {
+    let output = validate(&config(
+        r#"
+      - {id: lint, name: Lint, entry: cargo clippy, language: system, depends_on: [lint]}
+"#,
+    ));
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Hook 'lint' cannot depend on itself"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_validate_repeated_dependency() {
+    let output = validate(&config(
+        r#"
+      - {id: fmt, name: Format, entry: cargo fmt, language: system}
+      - {id: lint, name: Lint, entry: cargo clippy, language: system, depends_on: [fmt, fmt]}
+"#,
+    ));
+
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Warning: hook 'lint' lists dependency 'fmt' more than once"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_validate_duplicate_id() {
+    let output = validate(&config(
+        r#"
+      - {id: lint, name: Lint, entry: cargo clippy, language: system}
+      - {id: lint, name: Lint again, entry: cargo clippy, language: system}
+"#,
+    ));
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Duplicate hook ID: lint"), "{}", stderr);
+}
+
+/// Run `validate` without `--config` from `root/a/b`, where `root` is a git repo
+fn validate_from_subdir(root_config: Option<&str>) -> Output {
+    let root = tempfile::tempdir().unwrap();
+    let cwd = root.path().join("a").join("b");
+    fs::create_dir_all(&cwd).unwrap();
+    fs::create_dir(root.path().join(".git")).unwrap();
+    if let Some(content) = root_config {
+        fs::write(root.path().join(".pre-commit-config.yml"), content).unwrap();
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_pre-commit-rs"))
+        .arg("validate")
+        .current_dir(&cwd)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_validate_finds_config_in_parent_directory() {
+    let output = validate_from_subdir(Some(&config(
+        "      - {id: fmt, name: Format, entry: cargo fmt, language: system}\n",
+    )));
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("OK: 1 hooks"), "{}", stdout);
+}
+
+#[test]
+fn test_validate_distinguishes_missing_and_broken_configs() {
+    let output = validate_from_subdir(None);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No .pre-commit-config.yaml"), "{}", stderr);
+
+    let output = validate_from_subdir(Some("repos: [\n"));
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(".pre-commit-config.yml: Parse error"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_validate_misspelled_key() {
+    let misspelled =
+        config("      - {id: fmt, name: Format, entry: cargo fmt, language: system, file: x}\n");
+
+    let output = validate(&misspelled);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("unknown field `file` in hook 'fmt'; did you mean `files`?"),
+        "{}",
+        stderr
+    );
+
+    let output = validate_with(&misspelled, &["--allow-unknown-fields"]);
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Warning: ignoring unknown field `file` in hook 'fmt'"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,30 @@
+mod common;
+
+const CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: quiet
+        name: Quiet
+        entry: echo checked
+        pass_filenames: true
+        language: system
+"#;
+
+#[test]
+fn test_verbose_shows_argv_and_output() {
+    let root = common::project(CONFIG);
+
+    let output = common::run(root.path(), &["--sequential", "--verbose", "config.yaml"]);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("argv:"), "{}", stdout);
+    assert!(stdout.contains("echo checked config.yaml"), "{}", stdout);
+    assert!(stdout.contains("stdout: checked config.yaml"), "{}", stdout);
+
+    let output = common::run(root.path(), &["--sequential", "config.yaml"]);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("argv:"), "{}", stdout);
+    assert!(!stdout.contains("stdout:"), "{}", stdout);
+}
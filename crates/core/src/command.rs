@@ -0,0 +1,849 @@
+use crate::{language, shell, ExecutionOptions, Hook};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Variables kept even when a hook asks for a clean environment,
+/// since without them the child cannot locate any executables
+const ALWAYS_INHERITED: &[&str] = &["PATH"];
+
+/// Default interpreter for `shell` hooks
+pub const DEFAULT_SHELL: &str = if cfg!(windows) { "cmd" } else { "sh" };
+
+/// Leading argv for running `entry` as a shell script with the configured shell.
+///
+/// Arguments appended afterwards become the script's positional parameters
+/// (`"$@"`) on POSIX shells; `cmd` and PowerShell receive them after the script.
+pub fn shell_argv(entry: &str, options: &ExecutionOptions) -> Vec<String> {
+    let shell = options.shell.as_deref().unwrap_or(DEFAULT_SHELL);
+    let name = std::path::Path::new(shell)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(shell)
+        .to_ascii_lowercase();
+
+    match name.as_str() {
+        "cmd" => vec![shell.to_string(), "/C".to_string(), entry.to_string()],
+        "powershell" | "pwsh" => vec![
+            shell.to_string(),
+            "-NoProfile".to_string(),
+            "-Command".to_string(),
+            entry.to_string(),
+        ],
+        // `$0` is the shell name; arguments that follow fill "$@"
+        _ => vec![
+            shell.to_string(),
+            "-c".to_string(),
+            format!("{} \"$@\"", entry),
+            shell.to_string(),
+        ],
+    }
+}
+
+/// Full argv for running `hook` on `files` (already filtered): `entry` (with
+/// `${VAR}` expanded from the environment and split into words, or run by the
+/// shell as written, leaving expansion to the shell), then `args`, the
+/// `--hook-args` extras and, with `pass_filenames`, the filenames, adapted to
+/// the hook's language. `args` and the extras are passed as written; `${VAR}`
+/// in them is not expanded.
+///
+/// Errors are reported as the hook's failure rather than aborting the run.
+pub fn hook_argv(
+    hook: &Hook,
+    files: &[PathBuf],
+    options: &ExecutionOptions,
+) -> io::Result<Vec<String>> {
+    let mut argv = if hook.shell {
+        shell_argv(&hook.entry, options)
+    } else {
+        let invalid = |e: &dyn std::fmt::Display| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("invalid entry: {}", e))
+        };
+        let entry = shell::expand_vars(&hook.entry, |var| std::env::var(var).ok())
+            .map_err(|e| invalid(&e))?;
+        shell::split(&entry).map_err(|e| invalid(&e))?
+    };
+    argv.extend_from_slice(&hook.args);
+    argv.extend_from_slice(options.extra_args_for(&hook.id));
+
+    if argv.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Empty command"));
+    }
+
+    if let (Some(_), Some(dir)) = (&hook.working_dir, working_dir(hook, options)) {
+        if !dir.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("working_dir {} is not a directory", dir.display()),
+            ));
+        }
+    }
+
+    let files = if hook.pass_filenames {
+        files_for_working_dir(hook, files, options)
+    } else {
+        Vec::new()
+    };
+    language::wrap_argv(hook, argv, &files, options)
+}
+
+/// Directory the hook runs in: its `working_dir` resolved against the repo
+/// root, else the repo root itself (`None` means the current directory)
+pub fn working_dir(hook: &Hook, options: &ExecutionOptions) -> Option<PathBuf> {
+    let root = options.repo_root.as_deref();
+    match &hook.working_dir {
+        Some(dir) => Some(root.map_or_else(|| dir.clone(), |root| root.join(dir))),
+        None => root.map(Path::to_path_buf),
+    }
+}
+
+/// `files` (relative to the repo root, or absolute) as the hook sees them from
+/// its `working_dir`: relative to it when inside it, absolute otherwise
+fn files_for_working_dir(
+    hook: &Hook,
+    files: &[PathBuf],
+    options: &ExecutionOptions,
+) -> Vec<PathBuf> {
+    let Some(dir) = &hook.working_dir else {
+        return files.to_vec();
+    };
+    let dir: PathBuf = dir
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect();
+    let root = options.repo_root.as_deref().unwrap_or(Path::new("."));
+    let root = std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf());
+    let absolute_dir = root.join(&dir);
+
+    files
+        .iter()
+        .map(|file| {
+            let inside = if file.is_absolute() {
+                file.strip_prefix(&absolute_dir)
+            } else {
+                file.strip_prefix(&dir)
+            };
+            match inside {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => root.join(file),
+            }
+        })
+        .collect()
+}
+
+/// Argv size at which filenames are split across invocations when
+/// `ExecutionOptions::max_argv_bytes` is unset; well below common `ARG_MAX`
+/// limits, which also count the environment
+pub const DEFAULT_MAX_ARGV_BYTES: usize = 100_000;
+
+/// Split `files` (already filtered) into batches whose argv stays within
+/// `max_argv_bytes`, xargs-style, so each batch can be run as one invocation.
+///
+/// There is always at least one batch. Hooks without `pass_filenames` or with
+/// `require_serial` get everything in one batch, as does a single file that is
+/// too long by itself.
+pub fn file_batches<'a>(
+    hook: &Hook,
+    files: &'a [PathBuf],
+    options: &ExecutionOptions,
+) -> Vec<&'a [PathBuf]> {
+    if !hook.pass_filenames || hook.require_serial || files.is_empty() {
+        return vec![files];
+    }
+    // An argv that cannot be built fails the hook when run, whatever the batching
+    let Ok(base) = hook_argv(hook, &[], options) else {
+        return vec![files];
+    };
+
+    let limit = options.max_argv_bytes.unwrap_or(DEFAULT_MAX_ARGV_BYTES);
+    let base_len: usize = base.iter().map(|arg| arg.len() + 1).sum();
+
+    let mut batches = Vec::new();
+    let (mut start, mut len) = (0, base_len);
+    for (i, file) in files.iter().enumerate() {
+        let file_len = file.as_os_str().len() + 1;
+        if i > start && len + file_len > limit {
+            batches.push(&files[start..i]);
+            (start, len) = (i, base_len);
+        }
+        len += file_len;
+    }
+    batches.push(&files[start..]);
+    batches
+}
+
+/// The argv of each invocation running `hook` on `files` (already filtered)
+/// makes, one per batch; an argv that can't be built is the error the hook
+/// fails with
+pub fn batch_argvs(
+    hook: &Hook,
+    files: &[PathBuf],
+    options: &ExecutionOptions,
+) -> Vec<io::Result<Vec<String>>> {
+    file_batches(hook, files, options)
+        .into_iter()
+        .map(|batch| hook_argv(hook, batch, options))
+        .collect()
+}
+
+/// What the executors would do with `hook`, worked out without spawning anything
+#[derive(Debug)]
+pub enum DryRun {
+    /// The hook would be skipped, for this reason
+    Skip(String),
+    /// The hook would run these invocations (see [`batch_argvs`])
+    Run(Vec<io::Result<Vec<String>>>),
+}
+
+/// Preview running `hook` on the run's `files`: filter them the way the
+/// executors do, then either the skip reason or each invocation's argv
+pub fn dry_run(hook: &Hook, files: &[PathBuf], options: &ExecutionOptions) -> DryRun {
+    let filtered = hook.filter_files(files, options);
+    match hook.file_count_skip_reason(filtered.len()) {
+        Some(reason) => DryRun::Skip(reason),
+        None => DryRun::Run(batch_argvs(hook, &filtered, options)),
+    }
+}
+
+/// How often a hook with a timeout is checked for having exited
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The error for a hook killed after exceeding `timeout_secs`;
+/// `HookResult::from_output` reports its message as the hook's stderr
+pub fn timed_out_error(timeout: Duration) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("hook timed out after {}s", timeout.as_secs()),
+    )
+}
+
+/// The error for a hook killed because the run was cancelled (Ctrl-C);
+/// reported like a [`timed_out_error`]
+pub fn cancelled_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "hook killed: run cancelled")
+}
+
+/// Check that `shell` can run a trivial script, so a bad `--exec` fails up front
+pub fn check_shell(shell: &str) -> io::Result<()> {
+    let options = ExecutionOptions {
+        shell: Some(shell.to_string()),
+        ..Default::default()
+    };
+    let argv = shell_argv("exit 0", &options);
+    let status = Command::new(&argv[0])
+        .args(&argv[1..])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} exited with {}",
+            shell, status
+        )))
+    }
+}
+
+/// Longest `PRE_COMMIT_FILES` value passed on; Linux rejects longer single
+/// environment strings (`MAX_ARG_STRLEN`, 128 KiB)
+const MAX_FILES_ENV_LEN: usize = 128 * 1024 - 64;
+
+/// `files` newline-separated, as the hook sees them from its `working_dir`
+fn file_list(hook: &Hook, files: &[PathBuf], options: &ExecutionOptions) -> String {
+    files_for_working_dir(hook, files, options)
+        .iter()
+        .map(|file| file.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Write the hook's whole filtered file list to a temporary file when
+/// `PRE_COMMIT_FILES` can't carry it: the list is too long, or the hook runs
+/// in `batches` invocations that each see only their own files. Its path is
+/// what [`build_command`] exports as `PRE_COMMIT_FILES_PATH`; the file is
+/// removed when the returned path is dropped.
+pub fn write_file_list(
+    hook: &Hook,
+    files: &[PathBuf],
+    options: &ExecutionOptions,
+    batches: usize,
+) -> io::Result<Option<tempfile::TempPath>> {
+    let list = file_list(hook, files, options);
+    if batches <= 1 && list.len() <= MAX_FILES_ENV_LEN {
+        return Ok(None);
+    }
+
+    let mut file = tempfile::Builder::new()
+        .prefix("pre-commit-files-")
+        .tempfile()?;
+    file.write_all(list.as_bytes())?;
+    file.write_all(b"\n")?;
+    Ok(Some(file.into_temp_path()))
+}
+
+/// Build the process command for a hook invocation.
+///
+/// `argv` is the fully resolved argument vector (program first) and `files` the
+/// invocation's files, exposed as `PRE_COMMIT_FILES` (newline-separated,
+/// relative to the hook's `working_dir`) and `PRE_COMMIT_FILE_COUNT`. A list
+/// too long for one environment variable is left out, as is any inherited
+/// value. `files_path` is the hook's whole file list from [`write_file_list`],
+/// exported as `PRE_COMMIT_FILES_PATH`. The returned command carries the hook's
+/// environment and working directory settings; async executors can convert it
+/// with `tokio::process::Command::from`.
+///
+/// `${VAR}` in `env` values is expanded by the same rules as in `entry` (see
+/// [`shell::expand_vars`]), so referencing an unset variable is an error.
+pub fn build_command(
+    hook: &Hook,
+    argv: &[String],
+    files: &[PathBuf],
+    files_path: Option<&Path>,
+    options: &ExecutionOptions,
+) -> io::Result<Command> {
+    let mut command = Command::new(&argv[0]);
+    command.args(&argv[1..]);
+
+    if hook.clean_env {
+        command.env_clear();
+        for name in ALWAYS_INHERITED
+            .iter()
+            .copied()
+            .chain(hook.passthrough_env.iter().map(String::as_str))
+        {
+            if let Some(value) = std::env::var_os(name) {
+                command.env(name, value);
+            }
+        }
+    }
+
+    if options.no_color {
+        command
+            .env_remove("FORCE_COLOR")
+            .env_remove("CLICOLOR_FORCE")
+            .env("NO_COLOR", "1");
+    } else {
+        command.env("FORCE_COLOR", "1").env("CLICOLOR_FORCE", "1");
+    }
+    for (name, value) in &hook.env {
+        let value = shell::expand_vars(value, |var| std::env::var(var).ok()).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid env {}: {}", name, e),
+            )
+        })?;
+        command.env(name, value);
+    }
+
+    // Too long a list would make spawning fail; hooks can compare the count
+    let file_list = file_list(hook, files, options);
+    if file_list.len() <= MAX_FILES_ENV_LEN {
+        command.env("PRE_COMMIT_FILES", file_list);
+    } else {
+        command.env_remove("PRE_COMMIT_FILES");
+    }
+    command.env("PRE_COMMIT_FILE_COUNT", files.len().to_string());
+    match files_path {
+        Some(path) => command.env("PRE_COMMIT_FILES_PATH", path),
+        None => command.env_remove("PRE_COMMIT_FILES_PATH"),
+    };
+
+    if let Some(dir) = working_dir(hook, options) {
+        command.current_dir(dir);
+    }
+
+    Ok(command)
+}
+
+/// Run a hook invocation to completion and capture its output.
+///
+/// With `options.pty` the hook is attached to a pseudo-terminal, so tools that
+/// check `isatty` behave as they would interactively; both streams then land in
+/// `stdout`. When no pty is available (or the `pty` feature is off) this falls
+/// back to pipes. A hook exceeding its `timeout_secs` is killed and reported
+/// as a [`timed_out_error`].
+pub fn output(
+    hook: &Hook,
+    argv: &[String],
+    files: &[PathBuf],
+    files_path: Option<&Path>,
+    options: &ExecutionOptions,
+) -> io::Result<Output> {
+    let mut command = build_command(hook, argv, files, files_path, options)?;
+
+    if options.pty {
+        if let Some(output) = output_in_pty(hook, &command) {
+            return output;
+        }
+    }
+
+    match hook.timeout() {
+        Some(timeout) => output_with_timeout(command, timeout),
+        None => command.output(),
+    }
+}
+
+/// Like `Command::output`, but kill the child once `timeout` has passed
+fn output_with_timeout(mut command: Command, timeout: Duration) -> io::Result<Output> {
+    fn read_all(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            if let Some(mut pipe) = pipe {
+                pipe.read_to_end(&mut buffer).ok();
+            }
+            buffer
+        })
+    }
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = read_all(child.stdout.take());
+    let stderr = read_all(child.stderr.take());
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Output {
+                status,
+                stdout: stdout.join().unwrap_or_default(),
+                stderr: stderr.join().unwrap_or_default(),
+            });
+        }
+        if Instant::now() >= deadline {
+            // The readers are left behind: a grandchild may still hold the pipes
+            child.kill().ok();
+            child.wait().ok();
+            return Err(timed_out_error(timeout));
+        }
+        thread::sleep(TIMEOUT_POLL_INTERVAL);
+    }
+}
+
+/// Run `command` attached to a pseudo-terminal, or `None` if one can't be opened
+#[cfg(all(feature = "pty", unix))]
+fn output_in_pty(hook: &Hook, command: &Command) -> Option<io::Result<Output>> {
+    use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    let size = PtySize {
+        rows: 24,
+        cols: 120,
+        pixel_width: 0,
+        pixel_height: 0,
+    };
+    let pair = native_pty_system().openpty(size).ok()?;
+
+    let mut builder = CommandBuilder::new(command.get_program());
+    builder.args(command.get_args());
+    if hook.clean_env {
+        builder.env_clear();
+    }
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => builder.env(key, value),
+            None => builder.env_remove(key),
+        }
+    }
+    // Without an explicit cwd the pty child would start in $HOME
+    match command.get_current_dir() {
+        Some(dir) => builder.cwd(dir),
+        None => builder.cwd(std::env::current_dir().ok()?),
+    }
+
+    let timeout = hook.timeout();
+    let run = move || -> io::Result<Output> {
+        let mut child = pair
+            .slave
+            .spawn_command(builder)
+            .map_err(io::Error::other)?;
+        // Close our copy of the child's end so reads see EOF when it exits
+        drop(pair.slave);
+
+        // A watchdog kills the child at the deadline unless `finished` is dropped first
+        let (finished, done) = std::sync::mpsc::channel::<()>();
+        let watchdog = timeout.map(|timeout| {
+            let mut killer = child.clone_killer();
+            thread::spawn(move || {
+                let expired = done
+                    .recv_timeout(timeout)
+                    .is_err_and(|e| e == std::sync::mpsc::RecvTimeoutError::Timeout);
+                if expired {
+                    killer.kill().ok();
+                }
+                expired
+            })
+        });
+
+        let mut reader = pair.master.try_clone_reader().map_err(io::Error::other)?;
+        let mut stdout = Vec::new();
+        // Linux reports EIO rather than EOF once the child side closes
+        reader.read_to_end(&mut stdout).ok();
+
+        let status = child.wait()?;
+        drop(finished);
+        if let (Some(watchdog), Some(timeout)) = (watchdog, timeout) {
+            if watchdog.join().unwrap_or(false) {
+                return Err(timed_out_error(timeout));
+            }
+        }
+        Ok(Output {
+            status: ExitStatus::from_raw((status.exit_code() as i32) << 8),
+            stdout,
+            stderr: Vec::new(),
+        })
+    };
+
+    Some(run())
+}
+
+#[cfg(not(all(feature = "pty", unix)))]
+fn output_in_pty(_hook: &Hook, _command: &Command) -> Option<io::Result<Output>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_dry_run() {
+        let hook = Hook {
+            entry: "lint --fix".to_string(),
+            files: Some(r"\.rs$".to_string()),
+            pass_filenames: true,
+            ..Default::default()
+        };
+        let options = ExecutionOptions::default();
+
+        let files = [PathBuf::from("a.rs"), PathBuf::from("b.txt")];
+        let DryRun::Run(argvs) = dry_run(&hook, &files, &options) else {
+            panic!("expected the hook to run");
+        };
+        let argvs: Vec<_> = argvs.into_iter().map(Result::unwrap).collect();
+        assert_eq!(argvs, vec![vec!["lint", "--fix", "a.rs"]]);
+
+        let files = [PathBuf::from("b.txt")];
+        assert!(matches!(
+            dry_run(&hook, &files, &options),
+            DryRun::Skip(reason) if reason == "no files to check"
+        ));
+    }
+
+    #[test]
+    fn test_file_batches() {
+        let hook = Hook {
+            entry: "lint".to_string(),
+            pass_filenames: true,
+            ..Default::default()
+        };
+        // "lint" plus each 9-byte name costs 5 + 10 bytes per file
+        let files: Vec<PathBuf> = (0..10)
+            .map(|i| PathBuf::from(format!("file{}.rs", i)))
+            .collect();
+        let options = ExecutionOptions {
+            max_argv_bytes: Some(35),
+            ..Default::default()
+        };
+
+        let batches = file_batches(&hook, &files, &options);
+        let sizes: Vec<usize> = batches.iter().map(|batch| batch.len()).collect();
+        assert_eq!(sizes, vec![3, 3, 3, 1]);
+        assert_eq!(batches.concat(), files);
+
+        // A name longer than the limit still runs, alone
+        let tight = ExecutionOptions {
+            max_argv_bytes: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(file_batches(&hook, &files, &tight).len(), 10);
+
+        let serial = Hook {
+            require_serial: true,
+            ..hook.clone()
+        };
+        assert_eq!(file_batches(&serial, &files, &options), vec![&files[..]]);
+        assert_eq!(file_batches(&hook, &[], &options).len(), 1);
+    }
+
+    fn env_of(command: &Command, name: &str) -> Option<String> {
+        command
+            .get_envs()
+            .find(|(key, _)| *key == name)
+            .and_then(|(_, value)| value.map(|v| v.to_string_lossy().to_string()))
+    }
+
+    #[test]
+    fn test_shell_argv_passes_arguments_positionally() {
+        let options = ExecutionOptions {
+            shell: Some("bash".to_string()),
+            ..Default::default()
+        };
+        let mut argv = shell_argv("a=(x y); echo ${a[1]}", &options);
+        argv.push("file with space.rs".to_string());
+
+        let output = build_command(&Hook::default(), &argv, &[], None, &options)
+            .unwrap()
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            "y file with space.rs"
+        );
+
+        let cmd = ExecutionOptions {
+            shell: Some("cmd".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(shell_argv("dir", &cmd), vec!["cmd", "/C", "dir"]);
+    }
+
+    #[test]
+    fn test_file_list_env() {
+        let files = vec![PathBuf::from("src/a.rs"), PathBuf::from("b c.rs")];
+        let argv = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "printf '%s\\n' \"$PRE_COMMIT_FILES\" | wc -l; echo \"$PRE_COMMIT_FILE_COUNT\"; echo \"$PRE_COMMIT_FILES\""
+                .to_string(),
+        ];
+        let output = build_command(
+            &Hook::default(),
+            &argv,
+            &files,
+            None,
+            &ExecutionOptions::default(),
+        )
+        .unwrap()
+        .output()
+        .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let lines: Vec<&str> = stdout.lines().map(str::trim).collect();
+
+        assert_eq!(lines, vec!["2", "2", "src/a.rs", "b c.rs"]);
+    }
+
+    #[test]
+    fn test_oversized_file_list_is_omitted() {
+        let files: Vec<PathBuf> = (0..20_000)
+            .map(|i| PathBuf::from(format!("dir/file-{}.rs", i)))
+            .collect();
+        let command = build_command(
+            &Hook::default(),
+            &["true".to_string()],
+            &files,
+            None,
+            &ExecutionOptions::default(),
+        )
+        .unwrap();
+
+        // Removed, so a value inherited from the parent doesn't reach the hook
+        assert!(command
+            .get_envs()
+            .any(|(key, value)| key == "PRE_COMMIT_FILES" && value.is_none()));
+        assert_eq!(
+            env_of(&command, "PRE_COMMIT_FILE_COUNT").as_deref(),
+            Some("20000")
+        );
+
+        let path = write_file_list(&Hook::default(), &files, &Default::default(), 1)
+            .unwrap()
+            .unwrap();
+        let command = build_command(
+            &Hook::default(),
+            &["true".to_string()],
+            &files,
+            Some(&path),
+            &ExecutionOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            env_of(&command, "PRE_COMMIT_FILES_PATH").as_deref(),
+            path.to_str()
+        );
+        let list = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(list.lines().count(), 20_000);
+        assert_eq!(list.lines().last(), Some("dir/file-19999.rs"));
+    }
+
+    #[test]
+    fn test_file_list_is_written_only_when_needed() {
+        let files = vec![PathBuf::from("src/a.rs"), PathBuf::from("b.rs")];
+        let hook = Hook {
+            working_dir: Some(PathBuf::from("src")),
+            ..Default::default()
+        };
+        let options = ExecutionOptions::default();
+
+        assert!(write_file_list(&hook, &files, &options, 1)
+            .unwrap()
+            .is_none());
+
+        // A batched hook gets the whole list, as seen from its working_dir
+        let path = write_file_list(&hook, &files, &options, 2)
+            .unwrap()
+            .unwrap();
+        let list = std::fs::read_to_string(&path).unwrap();
+        let root = std::path::absolute(".").unwrap();
+        assert_eq!(list, format!("a.rs\n{}\n", root.join("b.rs").display()));
+
+        let kept = path.to_path_buf();
+        drop(path);
+        assert!(!kept.exists());
+    }
+
+    #[test]
+    fn test_build_command_argv() {
+        let hook = Hook::default();
+        let argv = vec!["echo".to_string(), "a b".to_string()];
+        let command = build_command(&hook, &argv, &[], None, &ExecutionOptions::default()).unwrap();
+
+        assert_eq!(command.get_program(), "echo");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), vec!["a b"]);
+        assert_eq!(env_of(&command, "FORCE_COLOR").as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_build_command_without_color() {
+        let options = ExecutionOptions {
+            no_color: true,
+            ..Default::default()
+        };
+        let command =
+            build_command(&Hook::default(), &["true".to_string()], &[], None, &options).unwrap();
+
+        assert_eq!(env_of(&command, "FORCE_COLOR"), None);
+        assert_eq!(env_of(&command, "CLICOLOR_FORCE"), None);
+        assert_eq!(env_of(&command, "NO_COLOR").as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_hook_env_expands_vars() {
+        let path = std::env::var("PATH").unwrap();
+        let mut hook = Hook {
+            env: HashMap::from([("MY_PATH".to_string(), "${PATH}:$$x".to_string())]),
+            ..Default::default()
+        };
+        let command =
+            build_command(&hook, &["true".to_string()], &[], None, &Default::default()).unwrap();
+        assert_eq!(env_of(&command, "MY_PATH"), Some(format!("{}:$x", path)));
+
+        // Unset is an error, as in `entry`
+        hook.env = HashMap::from([(
+            "MY_VAR".to_string(),
+            "${PRE_COMMIT_RS_TEST_UNSET}".to_string(),
+        )]);
+        let err = build_command(&hook, &["true".to_string()], &[], None, &Default::default())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid env MY_VAR: environment variable 'PRE_COMMIT_RS_TEST_UNSET' is not set"
+        );
+    }
+
+    #[test]
+    fn test_hook_env_overrides_inherited() {
+        let hook = Hook {
+            env: HashMap::from([
+                ("FORCE_COLOR".to_string(), "0".to_string()),
+                ("RUSTFLAGS".to_string(), "-D warnings".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let command =
+            build_command(&hook, &["true".to_string()], &[], None, &Default::default()).unwrap();
+
+        assert_eq!(env_of(&command, "FORCE_COLOR").as_deref(), Some("0"));
+        assert_eq!(
+            env_of(&command, "RUSTFLAGS").as_deref(),
+            Some("-D warnings")
+        );
+    }
+
+    #[test]
+    fn test_clean_env_with_passthrough() {
+        std::env::set_var("PRE_COMMIT_RS_TEST_PASSTHROUGH", "kept");
+        std::env::set_var("PRE_COMMIT_RS_TEST_DROPPED", "dropped");
+
+        let hook = Hook {
+            clean_env: true,
+            passthrough_env: vec!["PRE_COMMIT_RS_TEST_PASSTHROUGH".to_string()],
+            ..Default::default()
+        };
+        let argv = vec!["env".to_string()];
+        let output = build_command(&hook, &argv, &[], None, &ExecutionOptions::default())
+            .unwrap()
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert!(stdout.contains("PRE_COMMIT_RS_TEST_PASSTHROUGH=kept"));
+        assert!(!stdout.contains("PRE_COMMIT_RS_TEST_DROPPED"));
+        assert!(stdout.contains("PATH="));
+    }
+
+    #[cfg(all(feature = "pty", unix))]
+    #[test]
+    fn test_output_in_pty_sees_a_terminal() {
+        let options = ExecutionOptions {
+            pty: true,
+            ..Default::default()
+        };
+        let argv = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "test -t 1 && echo tty; exit 3".to_string(),
+        ];
+        let output = output(&Hook::default(), &argv, &[], None, &options).unwrap();
+
+        assert_eq!(output.status.code(), Some(3));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("tty"));
+    }
+
+    #[cfg(all(feature = "pty", unix))]
+    #[test]
+    fn test_output_in_pty_times_out() {
+        let options = ExecutionOptions {
+            pty: true,
+            ..Default::default()
+        };
+        let hook = Hook {
+            timeout_secs: Some(1),
+            ..Default::default()
+        };
+        let argv = vec!["sleep".to_string(), "5".to_string()];
+        let start = Instant::now();
+        let error = output(&hook, &argv, &[], None, &options).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_output_without_pty_uses_pipes() {
+        let argv = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "test -t 1 || echo pipe".to_string(),
+        ];
+        let output = output(
+            &Hook::default(),
+            &argv,
+            &[],
+            None,
+            &ExecutionOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "pipe");
+    }
+}
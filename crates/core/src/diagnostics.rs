@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+/// A single problem a hook reported against a location in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+/// Extract diagnostics from hook output.
+///
+/// Recognizes the `path:line[:column]: message` shape shared by most linters
+/// and compilers; lines that don't match are ignored. ANSI color codes are
+/// stripped first since hooks run with color forced on.
+pub fn parse_diagnostics(output: &str) -> Vec<Diagnostic> {
+    output
+        .lines()
+        .filter_map(|line| parse_line(&strip_ansi(line)))
+        .collect()
+}
+
+fn parse_line(line: &str) -> Option<Diagnostic> {
+    let mut parts = line.splitn(3, ':');
+    let file = parts.next()?.trim();
+    let line_number = parts.next()?.trim().parse().ok()?;
+    let rest = parts.next()?;
+
+    if file.is_empty() || file.contains(char::is_whitespace) {
+        return None;
+    }
+
+    // An optional column precedes the message; the message may contain colons
+    let (column, message) = match rest.split_once(':') {
+        Some((column, message)) => match column.trim().parse() {
+            Ok(column) => (Some(column), message),
+            Err(_) => (None, rest),
+        },
+        None => (None, rest),
+    };
+
+    Some(Diagnostic {
+        file: PathBuf::from(file),
+        line: line_number,
+        column,
+        message: message.trim().to_string(),
+    })
+}
+
+/// Remove ANSI CSI escape sequences (colors, cursor movement) from `line`
+pub fn strip_ansi(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip a CSI sequence: ESC '[' params... final byte in '@'..='~'
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        stripped.push(c);
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let output = "\
+src/lib.rs:12:5: unused variable: `x`
+checking 3 files...
+src/main.rs:40: line too long
+\u{1b}[1msrc/lib.rs\u{1b}[0m:3:1: missing docs
+";
+
+        let diagnostics = parse_diagnostics(output);
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic {
+                    file: PathBuf::from("src/lib.rs"),
+                    line: 12,
+                    column: Some(5),
+                    message: "unused variable: `x`".to_string(),
+                },
+                Diagnostic {
+                    file: PathBuf::from("src/main.rs"),
+                    line: 40,
+                    column: None,
+                    message: "line too long".to_string(),
+                },
+                Diagnostic {
+                    file: PathBuf::from("src/lib.rs"),
+                    line: 3,
+                    column: Some(1),
+                    message: "missing docs".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_diagnostics_ignores_prose() {
+        assert!(parse_diagnostics("error: could not compile\nnote: see above").is_empty());
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{ExecutionOptions, Hook};
+use serde::Serialize;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A value accepted in a hook's `language` field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Language {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Program that must be on `PATH` for hooks in this language to run,
+    /// or `None` when `entry` is run as-is
+    pub requires: Option<&'static str>,
+}
+
+/// The supported `language` values
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        name: "system",
+        description: "Run `entry` as an installed command",
+        requires: None,
+    },
+    Language {
+        name: "script",
+        description: "Run `entry` as a script path relative to the repository",
+        requires: None,
+    },
+    Language {
+        name: "shell",
+        description: "Run `entry` with the POSIX shell",
+        requires: Some("sh"),
+    },
+    Language {
+        name: "python",
+        description: "Run `entry` with a Python interpreter",
+        requires: Some("python3"),
+    },
+    Language {
+        name: "node",
+        description: "Run `entry` with Node.js",
+        requires: Some("node"),
+    },
+    Language {
+        name: "docker",
+        description: "Run `entry` inside the hook's `image` with Docker",
+        requires: Some("docker"),
+    },
+];
+
+/// Whether a language can run on this machine, for `pre-commit-rs languages`
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageStatus {
+    pub language: &'static str,
+    pub description: &'static str,
+    pub available: bool,
+    /// Where the required program was found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl Language {
+    pub fn status(&self) -> LanguageStatus {
+        let path = self.requires.and_then(find_executable);
+        LanguageStatus {
+            language: self.name,
+            description: self.description,
+            available: self.requires.is_none() || path.is_some(),
+            path,
+        }
+    }
+}
+
+/// Where `docker` hooks see the repository inside their container
+pub const DOCKER_WORKDIR: &str = "/src";
+
+/// The interpreter a `python` or `node` hook's `language_version` selects:
+/// `python3.11` and `3.11` both name `python3.11`, `node18` and `18` name
+/// `node18`. `None` for other languages or without a version.
+pub fn interpreter_name(hook: &Hook) -> Option<String> {
+    let version = hook.language_version.as_deref()?;
+    match hook.language.as_str() {
+        language @ ("python" | "node") if version.starts_with(language) => {
+            Some(version.to_string())
+        }
+        language @ ("python" | "node") => Some(format!("{}{}", language, version)),
+        _ => None,
+    }
+}
+
+/// Finish a hook's argv for its language by appending the filenames; `docker`
+/// hooks are additionally wrapped to run inside their `image`.
+///
+/// A `python` or `node` hook with a `language_version` needs the interpreter
+/// it names on `PATH`, and an `entry` starting with the plain interpreter
+/// (`python`, `python3` or `node`) runs that version instead.
+pub fn wrap_argv(
+    hook: &Hook,
+    mut argv: Vec<String>,
+    files: &[PathBuf],
+    options: &ExecutionOptions,
+) -> io::Result<Vec<String>> {
+    if let Some(name) = interpreter_name(hook) {
+        let interpreter = find_executable(&name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "language_version {} is not installed: no `{}` on PATH",
+                    hook.language_version.as_deref().unwrap_or_default(),
+                    name
+                ),
+            )
+        })?;
+        let plain: &[&str] = match hook.language.as_str() {
+            "python" => &["python", "python3"],
+            _ => &["node"],
+        };
+        if argv
+            .first()
+            .is_some_and(|program| plain.contains(&program.as_str()))
+        {
+            argv[0] = interpreter.to_string_lossy().into_owned();
+        }
+    }
+
+    if hook.language != "docker" {
+        argv.extend(
+            files
+                .iter()
+                .filter_map(|file| file.to_str())
+                .map(String::from),
+        );
+        return Ok(argv);
+    }
+
+    let image = hook.image.as_deref().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "docker hooks need an `image` to run in",
+        )
+    })?;
+    if find_executable("docker").is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "docker is not installed or not on PATH",
+        ));
+    }
+    // The working directory is mounted, so files relative to it line up
+    let root = match crate::command::working_dir(hook, options) {
+        Some(dir) => std::path::absolute(dir)?,
+        None => env::current_dir()?,
+    };
+
+    Ok(docker_argv(image, &root, argv, files))
+}
+
+/// `docker run` argv that mounts `root` at [`DOCKER_WORKDIR`] and runs `argv`
+/// there, with `files` rewritten to their paths inside the container
+pub fn docker_argv(image: &str, root: &Path, argv: Vec<String>, files: &[PathBuf]) -> Vec<String> {
+    let container_path = |file: &PathBuf| {
+        // Relative paths already resolve against the working directory
+        let relative = file.strip_prefix(root).unwrap_or(file);
+        if relative.is_absolute() {
+            relative.to_str().map(String::from)
+        } else {
+            Path::new(DOCKER_WORKDIR)
+                .join(relative)
+                .to_str()
+                .map(String::from)
+        }
+    };
+
+    let mut docker = vec![
+        "docker".to_string(),
+        "run".to_string(),
+        "--rm".to_string(),
+        "-v".to_string(),
+        format!("{}:{}", root.display(), DOCKER_WORKDIR),
+        "-w".to_string(),
+        DOCKER_WORKDIR.to_string(),
+        image.to_string(),
+    ];
+    docker.extend(argv);
+    docker.extend(files.iter().filter_map(container_path));
+    docker
+}
+
+/// Locate `program` on `PATH` the way a shell would, without running it
+pub fn find_executable(program: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path).find_map(|dir| {
+        let candidate = dir.join(program);
+        if is_executable(&candidate) {
+            return Some(candidate);
+        }
+        // Windows resolves `python3` to `python3.exe`
+        let candidate = candidate.with_extension(env::consts::EXE_EXTENSION);
+        (cfg!(windows) && is_executable(&candidate)).then_some(candidate)
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_executable() {
+        assert!(find_executable("sh").is_some_and(|path| path.is_absolute()));
+        assert_eq!(find_executable("definitely-not-a-real-program-xyz"), None);
+    }
+
+    #[test]
+    fn test_docker_argv() {
+        let argv = docker_argv(
+            "rust:1.80",
+            Path::new("/home/me/repo"),
+            vec!["cargo".to_string(), "fmt".to_string()],
+            &[
+                PathBuf::from("src/main.rs"),
+                PathBuf::from("/home/me/repo/docs/a b.md"),
+                PathBuf::from("/elsewhere/x.rs"),
+            ],
+        );
+        assert_eq!(
+            argv,
+            vec![
+                "docker",
+                "run",
+                "--rm",
+                "-v",
+                "/home/me/repo:/src",
+                "-w",
+                "/src",
+                "rust:1.80",
+                "cargo",
+                "fmt",
+                "/src/src/main.rs",
+                "/src/docs/a b.md",
+                "/elsewhere/x.rs",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrap_argv() {
+        let options = ExecutionOptions::default();
+        let files = [PathBuf::from("a.rs")];
+
+        let system = Hook {
+            language: "system".to_string(),
+            ..Default::default()
+        };
+        let argv = wrap_argv(&system, vec!["echo".to_string()], &files, &options).unwrap();
+        assert_eq!(argv, vec!["echo", "a.rs"]);
+
+        let no_image = Hook {
+            language: "docker".to_string(),
+            ..Default::default()
+        };
+        let err = wrap_argv(&no_image, vec!["echo".to_string()], &files, &options).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("`image`"));
+    }
+
+    #[test]
+    fn test_interpreter_name() {
+        let hook = |language: &str, version: Option<&str>| Hook {
+            language: language.to_string(),
+            language_version: version.map(str::to_string),
+            ..Default::default()
+        };
+        assert_eq!(
+            interpreter_name(&hook("python", Some("python3.11"))).as_deref(),
+            Some("python3.11")
+        );
+        assert_eq!(
+            interpreter_name(&hook("python", Some("3.11"))).as_deref(),
+            Some("python3.11")
+        );
+        assert_eq!(
+            interpreter_name(&hook("node", Some("18"))).as_deref(),
+            Some("node18")
+        );
+        assert_eq!(interpreter_name(&hook("python", None)), None);
+        assert_eq!(interpreter_name(&hook("system", Some("3.11"))), None);
+    }
+
+    #[test]
+    fn test_wrap_argv_uses_the_language_version() {
+        let options = ExecutionOptions::default();
+        let argv = vec!["python3".to_string(), "-c".to_string(), "pass".to_string()];
+
+        let missing = Hook {
+            language: "python".to_string(),
+            language_version: Some("python0.1".to_string()),
+            ..Default::default()
+        };
+        let err = wrap_argv(&missing, argv.clone(), &[], &options).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(
+            err.to_string(),
+            "language_version python0.1 is not installed: no `python0.1` on PATH"
+        );
+
+        // The plain interpreter is swapped for the selected one
+        let Some(python3) = find_executable("python3") else {
+            return;
+        };
+        let installed = Hook {
+            language_version: Some("python3".to_string()),
+            ..missing
+        };
+        let wrapped = wrap_argv(&installed, argv, &[], &options).unwrap();
+        assert_eq!(wrapped[0], python3.to_string_lossy());
+        assert_eq!(wrapped[1..], ["-c", "pass"]);
+    }
+
+    #[test]
+    fn test_language_status() {
+        let system = LANGUAGES.iter().find(|l| l.name == "system").unwrap();
+        let status = system.status();
+        assert!(status.available);
+        assert_eq!(status.path, None);
+
+        let missing = Language {
+            name: "missing",
+            description: "",
+            requires: Some("definitely-not-a-real-program-xyz"),
+        };
+        assert!(!missing.status().available);
+    }
+}
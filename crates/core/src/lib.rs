@@ -1,5 +1,20 @@
+pub mod command;
+pub mod diagnostics;
+pub mod language;
+pub mod result_cache;
+pub mod shell;
+pub mod tags;
+
+use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::path::PathBuf;
+use std::process::Output;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,10 +23,24 @@ pub enum PreCommitError {
     Io(#[from] std::io::Error),
     #[error("Parse error: {0}")]
     Parse(String),
+    #[error(
+        "Config file not found: {}\nRun `pre-commit-rs sample-config > .pre-commit-config.yaml` to create one",
+        .0.display()
+    )]
+    ConfigNotFound(PathBuf),
+    /// No config in the directory the search started from or any parent up
+    /// to the git root
+    #[error(
+        "No .pre-commit-config.yaml or .pre-commit-config.yml found in {} or any parent directory up to the git root\nRun `pre-commit-rs sample-config > .pre-commit-config.yaml` to create one",
+        .0.display()
+    )]
+    NoConfigFound(PathBuf),
     #[error("Execution error: {0}")]
     Execution(String),
-    #[error("Cycle detected in hook dependencies")]
-    CycleDetected,
+    /// Hook ids around the cycle, each depending on the next; the first id is
+    /// repeated at the end
+    #[error("Cycle detected in hook dependencies: {}", .0.join(" -> "))]
+    CycleDetected(Vec<String>),
     #[error("Hook not found: {0}")]
     HookNotFound(String),
 }
@@ -19,35 +48,401 @@ pub enum PreCommitError {
 pub type Result<T> = std::result::Result<T, PreCommitError>;
 
 /// Represents a single hook configuration
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Hook {
+    /// Unique within a resolved config: when several repos use the same id, the
+    /// parser qualifies each as `repo:id`
     pub id: String,
+    /// The `repo` this hook was listed under, filled in by the parser
+    #[serde(skip)]
+    pub repo: String,
     pub name: String,
+    /// Command to run; may be omitted when inherited from a `template`.
+    /// Unless `shell` is set, `${VAR}` is expanded from the environment
+    /// (an unset variable fails the hook) and `$$` is a literal `$`.
+    #[serde(default)]
     pub entry: String,
     pub language: String,
     #[serde(default)]
     pub files: Option<String>,
+    /// Regex of files to leave out, applied after `files`
+    #[serde(default)]
+    pub exclude: Option<String>,
+    /// File type tags (see [`tags`]) a file must all have to be passed
+    #[serde(default)]
+    pub types: Vec<String>,
+    /// File type tags a file must have at least one of to be passed
+    #[serde(default)]
+    pub types_or: Vec<String>,
+    /// Container image `docker` hooks run in
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Always pass every filename to a single invocation, for tools that must
+    /// see all files at once; otherwise long file lists are split into batches
+    #[serde(default)]
+    pub require_serial: bool,
+    /// Arguments appended after `entry` as-is, without shell splitting, so
+    /// values may contain spaces or quotes
+    #[serde(default)]
+    pub args: Vec<String>,
     #[serde(default)]
     pub pass_filenames: bool,
+    /// Hooks that must pass before this one runs, by id or as `repo:id`
     #[serde(default)]
     pub depends_on: Vec<String>,
+    /// Soft dependencies: run after these hooks when they are in the plan. Ids
+    /// of hooks that aren't (disabled, skipped or filtered out) are ignored,
+    /// and unlike `depends_on` a failure of these hooks doesn't skip this one.
+    #[serde(default)]
+    pub soft_depends_on: Vec<String>,
+    /// Soft ordering: when this hook shares a level with any of these hooks, start
+    /// it after them. Unlike `depends_on` this adds no edge, so it never moves
+    /// the hook to a later level or waits for the other hooks to finish.
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Always print this hook's output, even when it passes
+    #[serde(default)]
+    pub verbose: bool,
+    /// Run the hook with an empty environment (only `PATH` is kept)
+    #[serde(default)]
+    pub clean_env: bool,
+    /// Variables copied from the parent environment even under `clean_env`
+    #[serde(default)]
+    pub passthrough_env: Vec<String>,
+    /// Extra environment variables for this hook. They override inherited
+    /// variables; values may reference the parent environment as `${VAR}`,
+    /// which is an error when the variable is unset, as in `entry`.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Directory to run the hook in, relative to the repository root (e.g. a
+    /// package in a monorepo); filenames are passed relative to it
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    /// Run the hook even when no files match its filters (e.g. a repo-wide
+    /// lockfile check); no filenames are passed in that case
+    #[serde(default)]
+    pub always_run: bool,
+    /// Skip the hook when fewer files than this match its filters
+    #[serde(default)]
+    pub min_files: Option<usize>,
+    /// Skip the hook when more files than this match its filters
+    #[serde(default)]
+    pub max_files: Option<usize>,
+    /// The hook rewrites files, so it must not run alongside hooks touching the same files
+    #[serde(default)]
+    pub mutates_files: bool,
+    /// Treat any stderr output as a failure, even when the exit code is zero
+    #[serde(default)]
+    pub fail_on_stderr: bool,
+    /// Interpreter version for `python` and `node` hooks (e.g. `python3.11`,
+    /// `node18`), which must then be on `PATH`; falls back to the config's
+    /// `default_language_version` for the hook's language. See
+    /// [`language::wrap_argv`].
+    #[serde(default)]
+    pub language_version: Option<String>,
+    /// Name of a config-level template whose fields this hook inherits
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Run `entry` as a shell script (`sh -c`) instead of splitting it into argv;
+    /// extra arguments and filenames become the script's positional parameters
+    #[serde(default)]
+    pub shell: bool,
+    /// Free-form labels for selecting or skipping groups of hooks
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Share of the parallel job budget this hook occupies while running
+    /// (0 is treated as 1)
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// How serious a failure of this hook is; see [`ExecutionResult::passes`]
+    #[serde(default)]
+    pub severity: Severity,
+    /// Kill the hook and fail it when it runs longer than this many seconds;
+    /// falls back to the repo's, then the config's `default_timeout`
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Git hook stages this hook runs in (see [`STAGES`]); when empty, the
+    /// repo's or the config's `default_stages`, else `pre-commit`
+    #[serde(default)]
+    pub stages: Vec<String>,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// The stage hooks run in when none is given
+pub const DEFAULT_STAGE: &str = "pre-commit";
+
+/// Git hook stages a hook can target, each also the name of its git hook file
+pub const STAGES: &[&str] = &[
+    "pre-commit",
+    "pre-merge-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+    "post-checkout",
+    "post-merge",
+    "post-rewrite",
+    "pre-rebase",
+    "pre-push",
+];
+
+impl Hook {
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_secs.map(Duration::from_secs)
+    }
+
+    /// The id as written in the config, without any `repo:` qualifier
+    pub fn bare_id(&self) -> &str {
+        if self.repo.is_empty() {
+            return &self.id;
+        }
+        self.id
+            .strip_prefix(self.repo.as_str())
+            .and_then(|rest| rest.strip_prefix(':'))
+            .unwrap_or(&self.id)
+    }
+
+    /// Whether `reference` names this hook: its id, its bare id or `repo:id`
+    pub fn is_named(&self, reference: &str) -> bool {
+        let bare_id = self.bare_id();
+        let qualified = !self.repo.is_empty()
+            && reference
+                .strip_prefix(self.repo.as_str())
+                .and_then(|rest| rest.strip_prefix(':'))
+                == Some(bare_id);
+        reference == self.id || reference == bare_id || qualified
+    }
+
+    /// Whether this hook runs when git invokes the given stage
+    pub fn runs_in_stage(&self, stage: &str) -> bool {
+        if self.stages.is_empty() {
+            return stage == DEFAULT_STAGE;
+        }
+        self.stages.iter().any(|s| s == stage)
+    }
+
+    /// The files this hook runs on: what is left after the run-wide
+    /// `options.exclude`, narrowed to those matching `files` (all when unset or
+    /// invalid), minus those matching `exclude`, and then only those with every
+    /// tag in `types` and any tag in `types_or`. Relative paths are looked up
+    /// under `options.repo_root` to read their tags.
+    pub fn filter_files(&self, files: &[PathBuf], options: &ExecutionOptions) -> Vec<PathBuf> {
+        let global_exclude = options.exclude.as_deref().and_then(|p| Regex::new(p).ok());
+        let include = self.files.as_deref().and_then(|p| Regex::new(p).ok());
+        let exclude = self.exclude.as_deref().and_then(|p| Regex::new(p).ok());
+        let root = options.repo_root.as_deref();
+        let matches =
+            |regex: &Regex, file: &PathBuf| file.to_str().is_some_and(|s| regex.is_match(s));
+        let has_types = |file: &PathBuf| {
+            if self.types.is_empty() && self.types_or.is_empty() {
+                return true;
+            }
+            let tags = tags::tags_from_path(&root.map_or_else(|| file.clone(), |r| r.join(file)));
+            self.types.iter().all(|tag| tags.contains(tag.as_str()))
+                && (self.types_or.is_empty()
+                    || self.types_or.iter().any(|tag| tags.contains(tag.as_str())))
+        };
+
+        files
+            .iter()
+            .filter(|file| {
+                !global_exclude
+                    .as_ref()
+                    .is_some_and(|regex| matches(regex, file))
+            })
+            .filter(|file| include.as_ref().is_none_or(|regex| matches(regex, file)))
+            .filter(|file| !exclude.as_ref().is_some_and(|regex| matches(regex, file)))
+            .filter(|file| has_types(file))
+            .cloned()
+            .collect()
+    }
+
+    /// Reason to skip this hook given the number of files that matched its filters,
+    /// or `None` when it should run: `always_run` is set, or at least one file
+    /// matched and the count is within `min_files..=max_files`
+    pub fn file_count_skip_reason(&self, count: usize) -> Option<String> {
+        if self.always_run {
+            return None;
+        }
+        match (self.min_files, self.max_files) {
+            _ if count == 0 => Some("no files to check".to_string()),
+            (Some(min), _) if count < min => Some(format!(
+                "{} matching files is below min_files ({})",
+                count, min
+            )),
+            (_, Some(max)) if count > max => Some(format!(
+                "{} matching files is above max_files ({})",
+                count, max
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// How serious a hook failure is, ordered from least to most severe
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    #[default]
+    Error,
+}
+
+impl Severity {
+    pub fn is_error(&self) -> bool {
+        *self == Severity::Error
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "warning" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            _ => Err(format!(
+                "unknown severity '{}', expected 'error' or 'warning'",
+                s
+            )),
+        }
+    }
+}
+
+/// When to color output (`--color`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and `NO_COLOR` is unset or empty
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether output should be colored, looking at the environment for `Auto`
+    pub fn enabled(self) -> bool {
+        use std::io::IsTerminal;
+        self.resolve(
+            std::env::var_os("NO_COLOR").as_deref(),
+            std::io::stdout().is_terminal(),
+        )
+    }
+
+    fn resolve(self, no_color: Option<&std::ffi::OsStr>, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            // https://no-color.org: set to anything non-empty
+            ColorChoice::Auto => no_color.is_none_or(|value| value.is_empty()) && is_terminal,
+        }
+    }
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "unknown color choice '{}', expected 'auto', 'always' or 'never'",
+                s
+            )),
+        }
+    }
+}
+
+/// Reusable hook fields, referenced from a hook with `template: <name>`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct HookTemplate {
+    /// Another template this one extends
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub entry: Option<String>,
+    #[serde(default)]
+    pub files: Option<String>,
+    /// Used when the hook sets no `args` of its own
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
 }
 
 /// Represents a repository with hooks
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Repo {
     pub repo: String,
+    /// With a `manifest`, these entries select and override manifest hooks by id
+    #[serde(default)]
     pub hooks: Vec<Hook>,
+    /// Hook manifest (`.pre-commit-hooks.yaml` format) to load hook definitions from,
+    /// relative to the config file
+    #[serde(default)]
+    pub manifest: Option<PathBuf>,
+    /// `stages` for this repo's hooks that set none, overriding the config's
+    #[serde(default)]
+    pub default_stages: Option<Vec<String>>,
+    /// `timeout_secs` for this repo's hooks that set none, overriding the config's
+    #[serde(default)]
+    pub default_timeout: Option<u64>,
+    /// Default `language_version` per language for this repo's hooks,
+    /// overriding the config's
+    #[serde(default)]
+    pub default_language_version: HashMap<String, String>,
 }
 
 /// The complete pre-commit configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pub repos: Vec<Repo>,
+    /// Default `language_version` per language, used by hooks that don't set one
+    #[serde(default)]
+    pub default_language_version: HashMap<String, String>,
+    /// `stages` for hooks that set none (`pre-commit` when unset)
+    #[serde(default)]
+    pub default_stages: Option<Vec<String>>,
+    /// `timeout_secs` for hooks that set none
+    #[serde(default)]
+    pub default_timeout: Option<u64>,
+    /// Stop the run after the first failing hook, as `--fail-fast` does
+    #[serde(default)]
+    pub fail_fast: bool,
+    /// Named subsets of hook ids, selected at run time with `--profile`
+    #[serde(default)]
+    pub profiles: HashMap<String, Vec<String>>,
+    /// Named hook templates that hooks can inherit from
+    #[serde(default)]
+    pub templates: HashMap<String, HookTemplate>,
+    /// Regex of files to leave out of every hook, applied before each hook's
+    /// own `files` and `exclude`
+    #[serde(default)]
+    pub exclude: Option<String>,
+    /// Oldest pre-commit-rs version (semver, e.g. `0.2.0`) that can run this config
+    #[serde(default)]
+    pub minimum_version: Option<String>,
 }
 
 /// Result of executing a single hook
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HookResult {
     pub hook_id: String,
     pub success: bool,
@@ -55,14 +450,384 @@ pub struct HookResult {
     pub stdout: String,
     pub stderr: String,
     pub duration_ms: u64,
+    /// Why the hook was not executed, if it was skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<String>,
+    /// The hook's configured severity
+    #[serde(default, skip_serializing_if = "Severity::is_error")]
+    pub severity: Severity,
+    /// Reused from an earlier run with the same command and input files
+    /// instead of executed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+}
+
+impl HookResult {
+    /// Build a result from a finished (or failed-to-spawn) process
+    pub fn from_output(hook: &Hook, output: std::io::Result<Output>, duration: Duration) -> Self {
+        match output {
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+                let stderr_failure = hook.fail_on_stderr && !stderr.trim().is_empty();
+
+                Self {
+                    hook_id: hook.id.clone(),
+                    success: output.status.success() && !stderr_failure,
+                    exit_code: output.status.code(),
+                    stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                    stderr,
+                    duration_ms: duration.as_millis() as u64,
+                    skip_reason: None,
+                    severity: hook.severity,
+                    cached: false,
+                }
+            }
+            Err(e) => Self {
+                hook_id: hook.id.clone(),
+                success: false,
+                exit_code: None,
+                stdout: String::new(),
+                stderr: if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::TimedOut | std::io::ErrorKind::Interrupted
+                ) {
+                    e.to_string()
+                } else {
+                    format!("Failed to execute command: {}", e)
+                },
+                duration_ms: duration.as_millis() as u64,
+                skip_reason: None,
+                severity: hook.severity,
+                cached: false,
+            },
+        }
+    }
+
+    /// A hook that was not executed; skipping does not count as a failure
+    pub fn skipped(hook_id: &str, reason: impl Into<String>) -> Self {
+        Self {
+            hook_id: hook_id.to_string(),
+            success: true,
+            exit_code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration_ms: 0,
+            skip_reason: Some(reason.into()),
+            severity: Severity::default(),
+            cached: false,
+        }
+    }
+
+    /// A hook not executed because `blocker`, one of its (transitive)
+    /// dependencies, failed. Unlike [`HookResult::skipped`] this is not a pass.
+    pub fn dependency_failed(hook: &Hook, blocker: &str) -> Self {
+        let reason = format!("dependency '{}' failed", blocker);
+        Self {
+            hook_id: hook.id.clone(),
+            success: false,
+            exit_code: None,
+            stdout: String::new(),
+            stderr: reason.clone(),
+            duration_ms: 0,
+            skip_reason: Some(reason),
+            severity: hook.severity,
+            cached: false,
+        }
+    }
+
+    /// A hook not started because the run was cancelled; like
+    /// [`HookResult::dependency_failed`], this is not a pass
+    pub fn cancelled(hook: &Hook) -> Self {
+        let reason = CANCELLED_REASON.to_string();
+        Self {
+            hook_id: hook.id.clone(),
+            success: false,
+            exit_code: None,
+            stdout: String::new(),
+            stderr: reason.clone(),
+            duration_ms: 0,
+            skip_reason: Some(reason),
+            severity: hook.severity,
+            cached: false,
+        }
+    }
+
+    /// Combine the results of running one hook on successive batches of files:
+    /// it passes only if every batch passed, and output and durations add up.
+    /// The exit code is that of the first failing batch.
+    pub fn merge(mut self, batch: HookResult) -> Self {
+        if self.success && !batch.success {
+            self.exit_code = batch.exit_code;
+        }
+        self.success &= batch.success;
+        self.stdout.push_str(&batch.stdout);
+        self.stderr.push_str(&batch.stderr);
+        self.duration_ms += batch.duration_ms;
+        self
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        self.skip_reason.is_some()
+    }
+
+    /// Whether the hook ran and failed (as opposed to being skipped)
+    pub fn is_failure(&self) -> bool {
+        !self.success && !self.is_skipped()
+    }
+
+    /// Whether the hook failed with a severity at or above `fail_on`, so its
+    /// failure fails the run
+    pub fn fails_under(&self, fail_on: Severity) -> bool {
+        self.is_failure() && self.severity >= fail_on
+    }
+
+    /// Stdout for display without surrounding blank lines, or `None` when it is
+    /// only whitespace (tools like `cargo fmt --check` print nothing on success)
+    pub fn visible_stdout(&self) -> Option<&str> {
+        visible(&self.stdout)
+    }
+
+    /// Stderr for display, like [`HookResult::visible_stdout`]
+    pub fn visible_stderr(&self) -> Option<&str> {
+        visible(&self.stderr)
+    }
+
+    /// Append stderr to stdout (after a separator) and empty stderr,
+    /// for consumers that only read a single log field
+    pub fn merge_streams(&mut self) {
+        if self.stderr.is_empty() {
+            return;
+        }
+
+        let stderr = std::mem::take(&mut self.stderr);
+        if !self.stdout.is_empty() {
+            if !self.stdout.ends_with('\n') {
+                self.stdout.push('\n');
+            }
+            self.stdout.push_str("--- stderr ---\n");
+        }
+        self.stdout.push_str(&stderr);
+    }
+}
+
+fn visible(output: &str) -> Option<&str> {
+    if output.trim().is_empty() {
+        return None;
+    }
+    Some(output.trim_start_matches(['\r', '\n']).trim_end())
+}
+
+/// When one level of the plan ran, relative to the start of the run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelTiming {
+    /// Hooks of the level that ran (not skipped for a failed dependency)
+    pub hooks: Vec<String>,
+    pub start_ms: u64,
+    pub duration_ms: u64,
 }
 
+/// Skip reason of hooks not started because the run was cancelled
+const CANCELLED_REASON: &str = "run cancelled";
+
 /// Result of executing all hooks
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
     pub hooks: Vec<HookResult>,
     pub total_duration_ms: u64,
     pub all_passed: bool,
+    /// Per-level timings, recorded only by executors that run a level at a time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub levels: Vec<LevelTiming>,
+}
+
+impl ExecutionResult {
+    /// Number of hooks that actually ran (were not skipped)
+    pub fn executed_count(&self) -> usize {
+        self.hooks.iter().filter(|hook| !hook.is_skipped()).count()
+    }
+
+    /// How long each hook that ran took, keyed by hook id; cached results
+    /// didn't run
+    pub fn durations(&self) -> HashMap<String, u64> {
+        self.hooks
+            .iter()
+            .filter(|hook| !hook.is_skipped() && !hook.cached)
+            .map(|hook| (hook.hook_id.clone(), hook.duration_ms))
+            .collect()
+    }
+
+    /// Total time of the hooks that ran. Without overlap this is about
+    /// `total_duration_ms`; in a parallel run it can be much more.
+    pub fn summed_duration_ms(&self) -> u64 {
+        self.hooks
+            .iter()
+            .filter(|hook| !hook.is_skipped())
+            .map(|hook| hook.duration_ms)
+            .sum()
+    }
+
+    /// Up to `n` of the hooks that ran, slowest first (ties keep result order),
+    /// each with its percentage of `of_total_ms` (0 when that is 0)
+    pub fn slowest_hooks(&self, n: usize, of_total_ms: u64) -> Vec<(&HookResult, f64)> {
+        let mut ran: Vec<&HookResult> = self.hooks.iter().filter(|h| !h.is_skipped()).collect();
+        ran.sort_by_key(|hook| std::cmp::Reverse(hook.duration_ms));
+        ran.into_iter()
+            .take(n)
+            .map(|hook| {
+                let percent = match of_total_ms {
+                    0 => 0.0,
+                    total => hook.duration_ms as f64 * 100.0 / total as f64,
+                };
+                (hook, percent)
+            })
+            .collect()
+    }
+
+    /// Whether the run passes when only failures at or above `fail_on` count;
+    /// with `Severity::Warning` every failure counts. Hooks skipped because a
+    /// dependency failed don't count on their own, but a cancelled run fails.
+    pub fn passes(&self, fail_on: Severity) -> bool {
+        self.failure_count(fail_on) == 0 && !self.was_cancelled()
+    }
+
+    /// How many hooks ran and failed with a severity at or above `fail_on`.
+    /// Hooks not run because a dependency failed or the run was cancelled
+    /// don't count.
+    pub fn failure_count(&self, fail_on: Severity) -> usize {
+        self.hooks
+            .iter()
+            .filter(|hook| hook.fails_under(fail_on))
+            .count()
+    }
+
+    /// Whether the run was cancelled before every hook could start
+    pub fn was_cancelled(&self) -> bool {
+        self.hooks
+            .iter()
+            .any(|hook| hook.skip_reason.as_deref() == Some(CANCELLED_REASON))
+    }
+
+    /// Why nothing ran: a heading, then one line per skipped hook with its
+    /// reason, for `--error-on-no-hooks`
+    pub fn no_hooks_ran_report(&self) -> String {
+        let mut report = String::from("No hooks ran:\n");
+        for hook in &self.hooks {
+            if let Some(reason) = &hook.skip_reason {
+                report.push_str(&format!("  {}: {}\n", hook.hook_id, reason));
+            }
+        }
+        report
+    }
+}
+
+/// Runtime settings shared by the executors
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionOptions {
+    /// Directory hooks run from; relative entries resolve against it.
+    /// When unset, hooks inherit the current directory.
+    pub repo_root: Option<PathBuf>,
+    /// Regex of files no hook sees, removed before each hook's own filters
+    /// (the config's top-level `exclude`)
+    pub exclude: Option<String>,
+    /// Extra arguments per hook id, inserted before any filenames
+    pub extra_args: HashMap<String, Vec<String>>,
+    /// Attach hooks to a pseudo-terminal instead of pipes (needs the `pty` feature)
+    pub pty: bool,
+    /// Cap on the total `weight` of hooks running at once; unlimited when unset
+    pub jobs: Option<usize>,
+    /// Receives raw output chunks from each hook as they arrive
+    pub on_output: Option<OutputCallback>,
+    /// Interpreter for `shell` hooks; `sh` (`cmd` on Windows) when unset
+    pub shell: Option<String>,
+    /// Split a `pass_filenames` hook into several invocations once its argv
+    /// would exceed this many bytes; see [`command::DEFAULT_MAX_ARGV_BYTES`]
+    pub max_argv_bytes: Option<usize>,
+    /// Stop starting hooks after the first failure that counts under `fail_on`.
+    /// Hooks already running (the rest of a parallel level) finish, but no
+    /// later hook or level starts.
+    pub fail_fast: bool,
+    /// Don't force color on hooks: `FORCE_COLOR` and `CLICOLOR_FORCE` are
+    /// removed from their environment and `NO_COLOR=1` is set instead
+    pub no_color: bool,
+    /// Lowest severity whose failures count: only those skip the failed hook's
+    /// dependents
+    pub fail_on: Severity,
+    /// Set on Ctrl-C (see `trap_ctrl_c`); executors then start no more hooks
+    pub cancel: CancelFlag,
+    /// Have executors set `cancel` on Ctrl-C while they run. Off by default,
+    /// so a library caller's own Ctrl-C handling is left alone.
+    pub trap_ctrl_c: bool,
+}
+
+/// `--jobs` value to use: the requested count, or one per logical CPU when
+/// it is 0 or not given
+pub fn resolve_jobs(requested: Option<usize>) -> usize {
+    match requested {
+        Some(jobs) if jobs > 0 => jobs,
+        _ => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    }
+}
+
+/// Which of a hook's output streams a chunk came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Callback invoked with `(hook_id, stream, chunk)` for every chunk of child output,
+/// for consumers that render output live (web sockets, GUIs). The parallel
+/// executor delivers one line per chunk, newline included, as soon as the hook
+/// prints it. Without a callback, output is buffered until the hook exits.
+#[derive(Clone)]
+pub struct OutputCallback(Arc<OutputFn>);
+
+type OutputFn = dyn Fn(&str, OutputStream, Vec<u8>) + Send + Sync;
+
+impl OutputCallback {
+    pub fn new(callback: impl Fn(&str, OutputStream, Vec<u8>) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    pub fn call(&self, hook_id: &str, stream: OutputStream, chunk: Vec<u8>) {
+        (self.0)(hook_id, stream, chunk)
+    }
+}
+
+/// Shared flag that stops a run early once set, e.g. by a Ctrl-C handler.
+/// Clones share the flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// The underlying flag, for signal handlers that set it themselves
+    pub fn as_atomic(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.0)
+    }
+}
+
+impl fmt::Debug for OutputCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OutputCallback")
+    }
+}
+
+impl ExecutionOptions {
+    /// Extra arguments supplied at run time for the given hook
+    pub fn extra_args_for(&self, hook_id: &str) -> &[String] {
+        self.extra_args
+            .get(hook_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
 }
 
 /// Trait for executing hooks
@@ -75,8 +840,49 @@ pub trait PlanBuilder {
     fn build_plan(&self, hooks: &[Hook]) -> Result<ExecutionPlan>;
 }
 
+/// Tracks failed hooks while a plan runs, so their dependents can be skipped
+#[derive(Debug, Default)]
+pub struct DependencyFailures {
+    /// Failures below this severity don't block dependents
+    fail_on: Severity,
+    /// Hook id -> the failed hook it is blocked by (itself, if it failed)
+    blockers: HashMap<String, String>,
+}
+
+impl DependencyFailures {
+    /// Only failures at or above `fail_on`, which fail the run, block dependents
+    pub fn new(fail_on: Severity) -> Self {
+        Self {
+            fail_on,
+            blockers: HashMap::new(),
+        }
+    }
+
+    /// Record a finished hook; a failure that counts under `fail_on` blocks
+    /// everything depending on it
+    pub fn record(&mut self, result: &HookResult) {
+        if result.fails_under(self.fail_on) {
+            self.blockers
+                .insert(result.hook_id.clone(), result.hook_id.clone());
+        }
+    }
+
+    /// A skipped result for `hook` if any dependency failed or was itself
+    /// skipped for that reason, or `None` if it can run
+    pub fn skip(&mut self, hook: &Hook) -> Option<HookResult> {
+        let blocker = hook
+            .depends_on
+            .iter()
+            .find_map(|dep| self.blockers.get(dep))?
+            .clone();
+        let result = HookResult::dependency_failed(hook, &blocker);
+        self.blockers.insert(hook.id.clone(), blocker);
+        Some(result)
+    }
+}
+
 /// Execution plan with dependency ordering
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExecutionPlan {
     /// Hooks grouped by execution level (all hooks in a level can run in parallel)
     pub levels: Vec<Vec<Hook>>,
@@ -94,6 +900,88 @@ impl ExecutionPlan {
             .flat_map(|level| level.iter().cloned())
             .collect()
     }
+
+    /// Sort results into plan order (level by level, and within a level in
+    /// the level's order) rather than the order hooks happened to finish in.
+    /// Results for hooks not in the plan keep their relative order at the end.
+    pub fn sort_results(&self, results: &mut [HookResult]) {
+        let positions: HashMap<&str, usize> = self
+            .levels
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(position, hook)| (hook.id.as_str(), position))
+            .collect();
+        results.sort_by_key(|result| {
+            positions
+                .get(result.hook_id.as_str())
+                .copied()
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    /// Total number of hooks across all levels
+    pub fn hook_count(&self) -> usize {
+        self.levels.iter().map(Vec::len).sum()
+    }
+
+    /// Number of hooks in the largest level (the peak parallelism)
+    pub fn widest_level(&self) -> usize {
+        self.levels.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// Number of hooks in the longest dependency chain.
+    /// Levels are assigned by depth, so this equals the number of levels.
+    pub fn longest_chain(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Hooks that neither depend on nor are depended upon by another hook
+    pub fn isolated_hooks(&self) -> Vec<&Hook> {
+        let depended_on: HashSet<&str> = self
+            .levels
+            .iter()
+            .flatten()
+            .flat_map(|hook| hook.depends_on.iter().map(String::as_str))
+            .collect();
+
+        self.levels
+            .iter()
+            .flatten()
+            .filter(|hook| hook.depends_on.is_empty() && !depended_on.contains(hook.id.as_str()))
+            .collect()
+    }
+
+    /// Summary statistics describing the shape of the plan
+    pub fn stats(&self) -> PlanStats {
+        PlanStats {
+            hooks: self.hook_count(),
+            levels: self.levels.len(),
+            widest_level: self.widest_level(),
+            longest_chain: self.longest_chain(),
+            isolated_hooks: self.isolated_hooks().len(),
+        }
+    }
+}
+
+/// Shape of an execution plan, useful for reasoning about parallelism
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PlanStats {
+    pub hooks: usize,
+    pub levels: usize,
+    pub widest_level: usize,
+    pub longest_chain: usize,
+    pub isolated_hooks: usize,
+}
+
+impl fmt::Display for PlanStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hooks, {} levels, widest level {}, longest chain {}, {} isolated",
+            self.hooks, self.levels, self.widest_level, self.longest_chain, self.isolated_hooks
+        )
+    }
 }
 
 #[cfg(test)]
@@ -110,6 +998,7 @@ mod tests {
             files: Some("\\.rs$".to_string()),
             pass_filenames: false,
             depends_on: vec![],
+            ..Default::default()
         };
         assert_eq!(hook.id, "test");
         assert!(!hook.pass_filenames);
@@ -125,6 +1014,7 @@ mod tests {
             files: None,
             pass_filenames: false,
             depends_on: vec![],
+            ..Default::default()
         };
         let hook2 = Hook {
             id: "hook2".to_string(),
@@ -134,6 +1024,7 @@ mod tests {
             files: None,
             pass_filenames: false,
             depends_on: vec![],
+            ..Default::default()
         };
 
         let plan = ExecutionPlan::new(vec![vec![hook1.clone()], vec![hook2.clone()]]);
@@ -142,4 +1033,375 @@ mod tests {
         assert_eq!(sequential[0].id, "hook1");
         assert_eq!(sequential[1].id, "hook2");
     }
+
+    #[test]
+    fn test_execution_plan_stats() {
+        let make_hook = |id: &str, depends_on: &[&str]| Hook {
+            id: id.to_string(),
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        };
+
+        // a -> b, a -> c, plus isolated d and e
+        let plan = ExecutionPlan::new(vec![
+            vec![
+                make_hook("a", &[]),
+                make_hook("d", &[]),
+                make_hook("e", &[]),
+            ],
+            vec![make_hook("b", &["a"]), make_hook("c", &["a"])],
+        ]);
+
+        assert_eq!(
+            plan.stats(),
+            PlanStats {
+                hooks: 5,
+                levels: 2,
+                widest_level: 3,
+                longest_chain: 2,
+                isolated_hooks: 2,
+            }
+        );
+
+        let isolated: Vec<_> = plan.isolated_hooks().iter().map(|h| &h.id).collect();
+        assert_eq!(isolated, vec!["d", "e"]);
+    }
+
+    #[test]
+    fn test_file_count_skip_reason_boundaries() {
+        let hook = Hook {
+            min_files: Some(2),
+            max_files: Some(4),
+            ..Default::default()
+        };
+
+        assert!(hook.file_count_skip_reason(1).is_some());
+        assert!(hook.file_count_skip_reason(2).is_none());
+        assert!(hook.file_count_skip_reason(4).is_none());
+        assert!(hook.file_count_skip_reason(5).is_some());
+
+        assert_eq!(
+            Hook::default().file_count_skip_reason(0).as_deref(),
+            Some("no files to check")
+        );
+        assert!(Hook::default().file_count_skip_reason(1).is_none());
+
+        let always_run = Hook {
+            always_run: true,
+            ..hook
+        };
+        assert!(always_run.file_count_skip_reason(0).is_none());
+        assert!(always_run.file_count_skip_reason(5).is_none());
+    }
+
+    #[test]
+    fn test_merge_streams() {
+        let mut result = HookResult::skipped("lint", "unused");
+        result.stdout = "checked 3 files".to_string();
+        result.stderr = "warning: slow\n".to_string();
+
+        result.merge_streams();
+        assert_eq!(
+            result.stdout,
+            "checked 3 files\n--- stderr ---\nwarning: slow\n"
+        );
+        assert!(result.stderr.is_empty());
+
+        let mut stderr_only = HookResult::skipped("lint", "unused");
+        stderr_only.stderr = "boom".to_string();
+        stderr_only.merge_streams();
+        assert_eq!(stderr_only.stdout, "boom");
+        assert!(stderr_only.stderr.is_empty());
+    }
+
+    #[test]
+    fn test_executed_count_ignores_skipped() {
+        let mut ran = HookResult::skipped("ran", "unused");
+        ran.skip_reason = None;
+        let result = ExecutionResult {
+            hooks: vec![ran, HookResult::skipped("skipped", "no files")],
+            total_duration_ms: 0,
+            all_passed: true,
+            levels: Vec::new(),
+        };
+        assert_eq!(result.executed_count(), 1);
+
+        let none_ran = ExecutionResult {
+            hooks: vec![HookResult::skipped("skipped", "no files")],
+            ..result
+        };
+        assert_eq!(none_ran.executed_count(), 0);
+    }
+
+    #[test]
+    fn test_no_hooks_ran_report() {
+        let result = ExecutionResult {
+            hooks: vec![
+                HookResult::skipped("fmt", "no files to check"),
+                HookResult::skipped("lint", "skipped via SKIP"),
+            ],
+            total_duration_ms: 0,
+            all_passed: true,
+            levels: Vec::new(),
+        };
+        assert_eq!(
+            result.no_hooks_ran_report(),
+            "No hooks ran:\n  fmt: no files to check\n  lint: skipped via SKIP\n"
+        );
+    }
+
+    #[test]
+    fn test_slowest_hooks() {
+        let ran = |id: &str, duration_ms: u64| {
+            let mut hook = HookResult::skipped(id, "unused");
+            hook.skip_reason = None;
+            hook.duration_ms = duration_ms;
+            hook
+        };
+        let result = ExecutionResult {
+            hooks: vec![
+                ran("fmt", 100),
+                ran("lint", 500),
+                HookResult::skipped("docs", "no files"),
+                ran("test", 300),
+                ran("check", 100),
+            ],
+            total_duration_ms: 600,
+            all_passed: true,
+            levels: Vec::new(),
+        };
+        assert_eq!(result.summed_duration_ms(), 1000);
+
+        let ranked = |n: usize, total: u64| -> Vec<(String, f64)> {
+            result
+                .slowest_hooks(n, total)
+                .into_iter()
+                .map(|(hook, percent)| (hook.hook_id.clone(), percent))
+                .collect()
+        };
+        // Ties keep result order
+        assert_eq!(
+            ranked(10, 1000),
+            vec![
+                ("lint".to_string(), 50.0),
+                ("test".to_string(), 30.0),
+                ("fmt".to_string(), 10.0),
+                ("check".to_string(), 10.0),
+            ]
+        );
+        assert_eq!(
+            ranked(2, 500),
+            vec![("lint".to_string(), 100.0), ("test".to_string(), 60.0)]
+        );
+        assert_eq!(ranked(1, 0), vec![("lint".to_string(), 0.0)]);
+        assert!(ranked(0, 1000).is_empty());
+    }
+
+    #[test]
+    fn test_filter_files_by_types() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("lib.rs"), "").unwrap();
+        std::fs::write(root.path().join("setup.py"), "").unwrap();
+        std::fs::write(root.path().join("notes.md"), "").unwrap();
+        let files = vec![
+            PathBuf::from("lib.rs"),
+            PathBuf::from("setup.py"),
+            PathBuf::from("notes.md"),
+            PathBuf::from("deleted.rs"),
+        ];
+        let filter = |types: &[&str], types_or: &[&str]| {
+            let hook = Hook {
+                types: types.iter().map(|t| t.to_string()).collect(),
+                types_or: types_or.iter().map(|t| t.to_string()).collect(),
+                ..Default::default()
+            };
+            hook.filter_files(
+                &files,
+                &ExecutionOptions {
+                    repo_root: Some(root.path().to_path_buf()),
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert_eq!(filter(&[], &[]), files);
+        assert_eq!(filter(&["rust"], &[]), vec![PathBuf::from("lib.rs")]);
+        assert_eq!(
+            filter(&["file", "text"], &["rust", "python"]),
+            vec![PathBuf::from("lib.rs"), PathBuf::from("setup.py")]
+        );
+        assert!(filter(&["rust", "python"], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_filter_files_include_and_exclude() {
+        let files = vec![
+            PathBuf::from("src/lib.rs"),
+            PathBuf::from("src/generated/api.rs"),
+            PathBuf::from("README.md"),
+        ];
+        let filter = |include: Option<&str>, exclude: Option<&str>| {
+            let hook = Hook {
+                files: include.map(str::to_string),
+                exclude: exclude.map(str::to_string),
+                ..Default::default()
+            };
+            hook.filter_files(&files, &ExecutionOptions::default())
+        };
+
+        assert_eq!(
+            filter(Some(r"\.rs$"), None),
+            vec![
+                PathBuf::from("src/lib.rs"),
+                PathBuf::from("src/generated/api.rs")
+            ]
+        );
+        assert_eq!(
+            filter(None, Some("^src/generated/")),
+            vec![PathBuf::from("src/lib.rs"), PathBuf::from("README.md")]
+        );
+        assert_eq!(
+            filter(Some(r"\.rs$"), Some("^src/generated/")),
+            vec![PathBuf::from("src/lib.rs")]
+        );
+    }
+
+    #[test]
+    fn test_visible_output_ignores_whitespace_only() {
+        let mut result = HookResult::skipped("fmt", "unused");
+        result.stdout = " \n\t\n".to_string();
+        result.stderr = "\n\n  indented\nnext\n\n".to_string();
+
+        assert_eq!(result.visible_stdout(), None);
+        assert_eq!(result.visible_stderr(), Some("  indented\nnext"));
+    }
+
+    #[test]
+    fn test_color_choice() {
+        use std::ffi::OsStr;
+
+        assert_eq!("never".parse(), Ok(ColorChoice::Never));
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+
+        assert!(ColorChoice::Auto.resolve(None, true));
+        assert!(ColorChoice::Auto.resolve(Some(OsStr::new("")), true));
+        assert!(!ColorChoice::Auto.resolve(Some(OsStr::new("1")), true));
+        assert!(!ColorChoice::Auto.resolve(None, false));
+        assert!(ColorChoice::Always.resolve(Some(OsStr::new("1")), false));
+        assert!(!ColorChoice::Never.resolve(None, true));
+    }
+
+    #[test]
+    fn test_passes_respects_severity_threshold() {
+        let failed = |id: &str, severity: Severity| {
+            let mut hook = HookResult::skipped(id, "unused");
+            hook.skip_reason = None;
+            hook.success = false;
+            hook.severity = severity;
+            hook
+        };
+        let warning_only = ExecutionResult {
+            hooks: vec![
+                HookResult::skipped("passed", "no files"),
+                failed("lint", Severity::Warning),
+            ],
+            total_duration_ms: 0,
+            all_passed: false,
+            levels: Vec::new(),
+        };
+        assert!(warning_only.passes(Severity::Error));
+        assert!(!warning_only.passes(Severity::Warning));
+
+        let mut mixed = warning_only.clone();
+        mixed.hooks.push(failed("test", Severity::Error));
+        assert!(!mixed.passes(Severity::Error));
+        assert!(!mixed.passes(Severity::Warning));
+        assert_eq!(mixed.failure_count(Severity::Error), 1);
+        assert_eq!(mixed.failure_count(Severity::Warning), 2);
+
+        // Hooks skipped for a failed dependency don't count on their own
+        let hook = Hook {
+            id: "docs".to_string(),
+            ..Default::default()
+        };
+        let mut blocked = warning_only.clone();
+        blocked
+            .hooks
+            .push(HookResult::dependency_failed(&hook, "lint"));
+        assert!(blocked.passes(Severity::Error));
+        mixed
+            .hooks
+            .push(HookResult::dependency_failed(&hook, "test"));
+        assert!(!mixed.passes(Severity::Error));
+        assert_eq!(mixed.failure_count(Severity::Error), 1);
+        assert!(!mixed.was_cancelled());
+
+        // A cancelled run fails without counting hooks that never started
+        blocked.hooks.push(HookResult::cancelled(&hook));
+        assert_eq!(blocked.failure_count(Severity::Error), 0);
+        assert!(blocked.was_cancelled());
+        assert!(!blocked.passes(Severity::Error));
+    }
+
+    #[test]
+    fn test_only_counted_failures_block_dependents() {
+        let dependent = Hook {
+            id: "docs".to_string(),
+            depends_on: vec!["lint".to_string()],
+            ..Default::default()
+        };
+        let mut lint = HookResult::skipped("lint", "unused");
+        lint.skip_reason = None;
+        lint.success = false;
+        lint.severity = Severity::Warning;
+
+        let mut failures = DependencyFailures::new(Severity::Error);
+        failures.record(&lint);
+        assert!(failures.skip(&dependent).is_none());
+
+        let mut failures = DependencyFailures::new(Severity::Warning);
+        failures.record(&lint);
+        let skipped = failures.skip(&dependent).unwrap();
+        assert_eq!(
+            skipped.skip_reason.as_deref(),
+            Some("dependency 'lint' failed")
+        );
+    }
+
+    #[test]
+    fn test_result_json_with_invalid_utf8_and_control_chars() {
+        let mut output = std::process::Command::new("true").output().unwrap();
+        output.stdout = b"ok \xff\xfe bytes\x1b[31m\x00\x07\ttab\r\n".to_vec();
+        output.stderr = vec![0xc3, 0x28, b'"', b'\\'];
+
+        let result = ExecutionResult {
+            hooks: vec![HookResult::from_output(
+                &Hook::default(),
+                Ok(output),
+                Duration::ZERO,
+            )],
+            total_duration_ms: 0,
+            all_passed: true,
+            levels: Vec::new(),
+        };
+
+        for json in [
+            serde_json::to_string(&result).unwrap(),
+            serde_json::to_string_pretty(&result).unwrap(),
+        ] {
+            let parsed: ExecutionResult = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed.hooks[0].stdout, result.hooks[0].stdout);
+            assert_eq!(parsed.hooks[0].stderr, result.hooks[0].stderr);
+        }
+        assert!(!serde_json::to_string(&result).unwrap().contains('\n'));
+        assert!(result.hooks[0].stdout.contains('\u{fffd}'));
+    }
+
+    #[test]
+    fn test_empty_plan_stats() {
+        let stats = ExecutionPlan::new(vec![]).stats();
+        assert_eq!(stats.hooks, 0);
+        assert_eq!(stats.widest_level, 0);
+        assert_eq!(stats.longest_chain, 0);
+    }
 }
@@ -0,0 +1,463 @@
+//! Reusing passing hook results across runs when nothing they depend on changed
+
+use crate::{command, ExecutionOptions, ExecutionResult, Hook, HookResult, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The last successful result of each hook, keyed by what it ran on, so a run
+/// can skip hooks whose command and input files are unchanged.
+///
+/// Entries live either in one JSON file holding the latest entry per hook
+/// ([`ResultCache::in_file`]) or as one file per cache key in a directory
+/// ([`ResultCache::in_dir`]), which keeps results for several versions of a
+/// hook's inputs side by side, e.g. across CI branches sharing a cache.
+#[derive(Debug)]
+pub struct ResultCache {
+    store: Store,
+    /// Latest entry per hook id: the whole cache for [`Store::File`], only
+    /// the entries recorded this run for [`Store::Dir`]
+    hooks: HashMap<String, Entry>,
+}
+
+#[derive(Debug)]
+enum Store {
+    File(PathBuf),
+    Dir(PathBuf),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    result: HookResult,
+}
+
+impl ResultCache {
+    /// The cache stored in the JSON file at `path`; a missing or unreadable
+    /// file is an empty cache
+    pub fn in_file(path: PathBuf) -> Self {
+        let hooks = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<StoredFile>(&json).ok())
+            .map(|stored| stored.hooks)
+            .unwrap_or_default();
+        Self {
+            store: Store::File(path),
+            hooks,
+        }
+    }
+
+    /// A cache with one file per key in `dir`
+    pub fn in_dir(dir: PathBuf) -> Self {
+        Self {
+            store: Store::Dir(dir),
+            hooks: HashMap::new(),
+        }
+    }
+
+    /// Split `hooks` into results reused from the cache (marked `cached`) and
+    /// the hooks left to run, whose dependencies on cached hooks are dropped
+    /// since those already passed. A result is only reused when every hook it
+    /// depends on is reused too, as a dependency that runs may rewrite files or
+    /// fail. Also returns the cache key of every cacheable hook, for
+    /// [`ResultCache::record`].
+    pub fn partition(
+        &self,
+        hooks: &[Hook],
+        files: &[PathBuf],
+        options: &ExecutionOptions,
+    ) -> (Vec<HookResult>, Vec<Hook>, HashMap<String, String>) {
+        let keys: HashMap<String, String> = hooks
+            .iter()
+            .filter_map(|hook| Some((hook.id.clone(), key(hook, files, options)?)))
+            .collect();
+
+        let mut hits: HashMap<&str, HookResult> = hooks
+            .iter()
+            .filter_map(|hook| {
+                let result = self.lookup(&hook.id, keys.get(&hook.id)?)?;
+                Some((hook.id.as_str(), result))
+            })
+            .collect();
+
+        // Drop hits that depend on a hook that will run, until none do
+        let ids: HashSet<&str> = hooks.iter().map(|hook| hook.id.as_str()).collect();
+        loop {
+            let stale: Vec<&str> = hooks
+                .iter()
+                .filter(|hook| hits.contains_key(hook.id.as_str()))
+                .filter(|hook| {
+                    hook.depends_on
+                        .iter()
+                        .chain(&hook.soft_depends_on)
+                        .any(|dep| ids.contains(dep.as_str()) && !hits.contains_key(dep.as_str()))
+                })
+                .map(|hook| hook.id.as_str())
+                .collect();
+            if stale.is_empty() {
+                break;
+            }
+            for id in stale {
+                hits.remove(id);
+            }
+        }
+
+        let cached: Vec<HookResult> = hooks
+            .iter()
+            .filter_map(|hook| {
+                let result = hits.remove(hook.id.as_str())?;
+                Some(HookResult {
+                    duration_ms: 0,
+                    cached: true,
+                    ..result
+                })
+            })
+            .collect();
+
+        let cached_ids: HashSet<&str> = cached.iter().map(|r| r.hook_id.as_str()).collect();
+        let remaining = hooks
+            .iter()
+            .filter(|hook| !cached_ids.contains(hook.id.as_str()))
+            .map(|hook| {
+                let mut hook = hook.clone();
+                hook.depends_on
+                    .retain(|dep| !cached_ids.contains(dep.as_str()));
+                hook
+            })
+            .collect();
+
+        (cached, remaining, keys)
+    }
+
+    /// Remember the hooks in `result` that ran and passed; a hook that ran
+    /// and failed loses its entry, so only passing results are ever reused
+    pub fn record(&mut self, keys: &HashMap<String, String>, result: &ExecutionResult) {
+        for hook_result in result.hooks.iter().filter(|r| !r.is_skipped()) {
+            let Some(key) = keys.get(&hook_result.hook_id) else {
+                continue;
+            };
+            if hook_result.success && !hook_result.cached {
+                let entry = Entry {
+                    key: key.clone(),
+                    result: hook_result.clone(),
+                };
+                self.hooks.insert(hook_result.hook_id.clone(), entry);
+            } else if !hook_result.success {
+                self.hooks.remove(&hook_result.hook_id);
+            }
+        }
+    }
+
+    /// Persist what was recorded, creating the cache's directory if needed
+    pub fn save(&self) -> Result<()> {
+        match &self.store {
+            Store::File(path) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let stored = StoredFile {
+                    hooks: self.hooks.clone(),
+                };
+                fs::write(
+                    path,
+                    serde_json::to_string(&stored).map_err(io::Error::from)?,
+                )?;
+            }
+            Store::Dir(dir) => {
+                fs::create_dir_all(dir)?;
+                for entry in self.hooks.values() {
+                    let json = serde_json::to_string(&entry.result).map_err(io::Error::from)?;
+                    fs::write(key_path(dir, &entry.key), json)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The stored result of `hook_id` for `key`, if any
+    fn lookup(&self, hook_id: &str, key: &str) -> Option<HookResult> {
+        match &self.store {
+            Store::File(_) => {
+                let entry = self.hooks.get(hook_id)?;
+                (entry.key == key).then(|| entry.result.clone())
+            }
+            Store::Dir(dir) => {
+                let json = fs::read_to_string(key_path(dir, key)).ok()?;
+                serde_json::from_str(&json).ok()
+            }
+        }
+    }
+}
+
+/// On-disk shape of [`Store::File`]
+#[derive(Serialize, Deserialize)]
+struct StoredFile {
+    hooks: HashMap<String, Entry>,
+}
+
+fn key_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{}.json", key))
+}
+
+/// Hash of the hook's definition (`language_version` included), the argv of
+/// each invocation it would make and the path and content of every file it
+/// matches. `None` for hooks that must always run or have no input files,
+/// which are never cached.
+fn key(hook: &Hook, files: &[PathBuf], options: &ExecutionOptions) -> Option<String> {
+    if hook.always_run {
+        return None;
+    }
+    let filtered = hook.filter_files(files, options);
+    if filtered.is_empty() {
+        return None;
+    }
+
+    // `env` iterates in a different order in every process, so it is hashed
+    // sorted and apart from the rest of the definition
+    let definition = Hook {
+        env: HashMap::new(),
+        ..hook.clone()
+    };
+    let env: BTreeMap<&String, &String> = hook.env.iter().collect();
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(&definition).ok()?);
+    hasher.update(serde_json::to_vec(&env).ok()?);
+    for argv in command::batch_argvs(hook, &filtered, options) {
+        hasher.update(serde_json::to_vec(&argv.ok()?).ok()?);
+    }
+
+    let root = options.repo_root.as_deref().unwrap_or(Path::new("."));
+    for file in &filtered {
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        // A deleted file hashes differently from any content
+        match fs::read(root.join(file)) {
+            Ok(content) => hasher.update(Sha256::digest(content)),
+            Err(_) => hasher.update(b"missing"),
+        }
+    }
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_hook(id: &str, files: Option<&str>) -> Hook {
+        Hook {
+            id: id.to_string(),
+            entry: format!("check-{}", id),
+            files: files.map(str::to_string),
+            pass_filenames: true,
+            ..Default::default()
+        }
+    }
+
+    fn passed(hooks: &[Hook]) -> ExecutionResult {
+        let mut results: Vec<HookResult> = hooks
+            .iter()
+            .map(|hook| HookResult::skipped(&hook.id, "unused"))
+            .collect();
+        results.iter_mut().for_each(|r| {
+            r.skip_reason = None;
+            r.duration_ms = 7;
+        });
+        ExecutionResult {
+            hooks: results,
+            total_duration_ms: 7,
+            all_passed: true,
+            levels: Vec::new(),
+        }
+    }
+
+    fn ids(hooks: &[Hook]) -> Vec<&str> {
+        hooks.iter().map(|hook| hook.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_cache_hits_until_an_input_changes() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("a.rs"), "fn a() {}").unwrap();
+        fs::write(root.path().join("b.txt"), "text").unwrap();
+        let options = ExecutionOptions {
+            repo_root: Some(root.path().to_path_buf()),
+            ..Default::default()
+        };
+        let mut rust = make_hook("rust", Some(r"\.rs$"));
+        rust.depends_on = vec!["text".to_string()];
+        let hooks = vec![make_hook("text", Some(r"\.txt$")), rust];
+        let files = vec![PathBuf::from("a.rs"), PathBuf::from("b.txt")];
+
+        let mut cache = ResultCache::in_file(root.path().join("cache.json"));
+        let (cached, remaining, keys) = cache.partition(&hooks, &files, &options);
+        assert!(cached.is_empty());
+        cache.record(&keys, &passed(&remaining));
+
+        let (cached, remaining, _) = cache.partition(&hooks, &files, &options);
+        assert!(remaining.is_empty());
+        assert!(cached.iter().all(|r| r.cached && r.duration_ms == 0));
+
+        // Only the hook whose matched file changed runs again
+        fs::write(root.path().join("a.rs"), "fn a() { changed() }").unwrap();
+        let (cached, remaining, _) = cache.partition(&hooks, &files, &options);
+        assert_eq!(ids(&remaining), vec!["rust"]);
+        assert_eq!(cached[0].hook_id, "text");
+
+        // As does one whose command changed
+        fs::write(root.path().join("a.rs"), "fn a() {}").unwrap();
+        let mut changed = hooks.clone();
+        changed[1].args = vec!["--strict".to_string()];
+        let (_, remaining, _) = cache.partition(&changed, &files, &options);
+        assert_eq!(ids(&remaining), vec!["rust"]);
+        assert!(remaining[0].depends_on.is_empty());
+
+        // Or its interpreter version
+        let mut changed = hooks.clone();
+        changed[1].language_version = Some("3.12".to_string());
+        let (_, remaining, _) = cache.partition(&changed, &files, &options);
+        assert_eq!(ids(&remaining), vec!["rust"]);
+    }
+
+    #[test]
+    fn test_dependents_of_a_rerun_hook_run_again() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("a.rs"), "fn a() {}").unwrap();
+        fs::write(root.path().join("b.txt"), "text").unwrap();
+        let options = ExecutionOptions {
+            repo_root: Some(root.path().to_path_buf()),
+            ..Default::default()
+        };
+        let mut rust = make_hook("rust", Some(r"\.rs$"));
+        rust.depends_on = vec!["text".to_string()];
+        let mut docs = make_hook("docs", Some(r"\.rs$"));
+        docs.depends_on = vec!["rust".to_string()];
+        let hooks = vec![docs, rust, make_hook("text", Some(r"\.txt$"))];
+        let files = vec![PathBuf::from("a.rs"), PathBuf::from("b.txt")];
+
+        let mut cache = ResultCache::in_file(root.path().join("cache.json"));
+        let (_, remaining, keys) = cache.partition(&hooks, &files, &options);
+        cache.record(&keys, &passed(&remaining));
+
+        // `rust` and `docs` match unchanged files, but `text` runs first
+        fs::write(root.path().join("b.txt"), "changed").unwrap();
+        let (cached, remaining, _) = cache.partition(&hooks, &files, &options);
+        assert!(cached.is_empty());
+        assert_eq!(ids(&remaining), vec!["docs", "rust", "text"]);
+        assert_eq!(remaining[1].depends_on, vec!["text"]);
+    }
+
+    #[test]
+    fn test_uncacheable_and_failed_hooks_run() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("a.rs"), "").unwrap();
+        let options = ExecutionOptions {
+            repo_root: Some(root.path().to_path_buf()),
+            ..Default::default()
+        };
+        let mut always = make_hook("always", None);
+        always.always_run = true;
+        let hooks = vec![
+            always,
+            make_hook("no-files", Some(r"\.py$")),
+            make_hook("lint", None),
+        ];
+        let files = vec![PathBuf::from("a.rs")];
+
+        let mut cache = ResultCache::in_file(root.path().join("cache.json"));
+        let (_, remaining, keys) = cache.partition(&hooks, &files, &options);
+        assert_eq!(keys.keys().collect::<Vec<_>>(), vec!["lint"]);
+        cache.record(&keys, &passed(&remaining));
+        let (_, remaining, _) = cache.partition(&hooks, &files, &options);
+        assert_eq!(ids(&remaining), vec!["always", "no-files"]);
+
+        let mut failed = passed(&hooks[2..]);
+        failed.hooks[0].success = false;
+        cache.record(&keys, &failed);
+        let (_, remaining, _) = cache.partition(&hooks, &files, &options);
+        assert_eq!(ids(&remaining), vec!["always", "no-files", "lint"]);
+    }
+
+    #[test]
+    fn test_key_ignores_env_order() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("a.rs"), "").unwrap();
+        let options = ExecutionOptions {
+            repo_root: Some(root.path().to_path_buf()),
+            ..Default::default()
+        };
+        let files = [PathBuf::from("a.rs")];
+        let vars: Vec<(String, String)> = (0..16)
+            .map(|i| (format!("VAR_{}", i), i.to_string()))
+            .collect();
+
+        let mut hook = make_hook("lint", None);
+        hook.env = vars.iter().cloned().collect();
+        let mut reordered = hook.clone();
+        reordered.env = vars.iter().rev().cloned().collect();
+        assert_eq!(
+            key(&hook, &files, &options),
+            key(&reordered, &files, &options)
+        );
+
+        reordered
+            .env
+            .insert("VAR_0".to_string(), "changed".to_string());
+        assert_ne!(
+            key(&hook, &files, &options),
+            key(&reordered, &files, &options)
+        );
+    }
+
+    #[test]
+    fn test_file_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pre-commit-rs").join("cache.json");
+        assert!(ResultCache::in_file(path.clone()).hooks.is_empty());
+
+        let mut cache = ResultCache::in_file(path.clone());
+        let keys = HashMap::from([("fmt".to_string(), "abc".to_string())]);
+        cache.record(&keys, &passed(&[make_hook("fmt", None)]));
+        cache.save().unwrap();
+
+        let loaded = ResultCache::in_file(path.clone());
+        assert_eq!(loaded.hooks["fmt"].key, "abc");
+
+        fs::write(&path, "{").unwrap();
+        assert!(ResultCache::in_file(path).hooks.is_empty());
+    }
+
+    #[test]
+    fn test_dir_store_keeps_each_key() {
+        let root = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("a.rs"), "old").unwrap();
+        let options = ExecutionOptions {
+            repo_root: Some(root.path().to_path_buf()),
+            ..Default::default()
+        };
+        let hooks = vec![make_hook("rust", Some(r"\.rs$"))];
+        let files = vec![PathBuf::from("a.rs")];
+
+        // Cache results for two versions of the file
+        for content in ["old", "new"] {
+            fs::write(root.path().join("a.rs"), content).unwrap();
+            let mut cache = ResultCache::in_dir(cache_dir.path().to_path_buf());
+            let (_, remaining, keys) = cache.partition(&hooks, &files, &options);
+            assert_eq!(ids(&remaining), vec!["rust"]);
+            cache.record(&keys, &passed(&remaining));
+            cache.save().unwrap();
+        }
+
+        // Both hit, as each has its own file
+        for content in ["old", "new"] {
+            fs::write(root.path().join("a.rs"), content).unwrap();
+            let cache = ResultCache::in_dir(cache_dir.path().to_path_buf());
+            let (cached, remaining, _) = cache.partition(&hooks, &files, &options);
+            assert!(remaining.is_empty());
+            assert!(cached[0].cached);
+        }
+    }
+}
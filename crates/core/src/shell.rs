@@ -0,0 +1,260 @@
+use thiserror::Error;
+
+/// Why a hook `entry` (or `--hook-args` value) could not be split into words
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ShellParseError {
+    #[error("unterminated {0} quote")]
+    UnterminatedQuote(char),
+    #[error("trailing backslash")]
+    TrailingBackslash,
+}
+
+/// Why `${VAR}` references in a hook `entry` or `env` value could not be expanded
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExpandError {
+    #[error("environment variable '{0}' is not set")]
+    Unset(String),
+    #[error("unterminated '${{'")]
+    Unterminated,
+}
+
+/// Expand `${VAR}` references with `lookup`, before the result is [`split`].
+///
+/// Unlike a shell, a variable `lookup` doesn't know is an error rather than
+/// an empty string. `$$` is a literal `$`, and any other `$` is kept as
+/// written. As in a shell, nothing inside single quotes or after a backslash
+/// is expanded, so `sh -c '...'` scripts reach the shell untouched.
+pub fn expand_vars(
+    input: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, ExpandError> {
+    let mut expanded = String::with_capacity(input.len());
+    let mut in_double_quotes = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                expanded.push(c);
+                expanded.extend(chars.next());
+            }
+            '\'' if !in_double_quotes => {
+                expanded.push(c);
+                for c in chars.by_ref() {
+                    expanded.push(c);
+                    if c == '\'' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                in_double_quotes = !in_double_quotes;
+                expanded.push(c);
+            }
+            '$' if chars.next_if_eq(&'$').is_some() => expanded.push('$'),
+            '$' if chars.next_if_eq(&'{').is_some() => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(ExpandError::Unterminated),
+                    }
+                }
+                let value = lookup(&name).ok_or(ExpandError::Unset(name))?;
+                expanded.push_str(&value);
+            }
+            c => expanded.push(c),
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Split a command line into words the way a POSIX shell would, without
+/// expanding anything.
+///
+/// Quoted and unquoted segments next to each other join into one word
+/// (`foo"bar"baz` is `foobarbaz`), and an empty quoted string is an empty word.
+/// A backslash is literal inside single quotes; inside double quotes it only
+/// escapes `"`, `\`, `$` and `` ` ``.
+pub fn split(input: &str) -> Result<Vec<String>, ShellParseError> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    // Set once a word has started, so `''` still yields an (empty) word
+    let mut in_word = false;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(ShellParseError::UnterminatedQuote('\'')),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err(ShellParseError::UnterminatedQuote('"')),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(ShellParseError::UnterminatedQuote('"')),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some(c) => current.push(c),
+                    None => return Err(ShellParseError::TrailingBackslash),
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(current);
+    }
+
+    Ok(words)
+}
+
+/// Join argv into a command line for display, single-quoting arguments a shell
+/// would split or expand; the reverse of [`split`]
+pub fn join(argv: &[String]) -> String {
+    argv.iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "'\"\\$".contains(c)) {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_words_and_quotes() {
+        assert_eq!(
+            split("echo hello world").unwrap(),
+            vec!["echo", "hello", "world"]
+        );
+        assert_eq!(
+            split("echo 'hello world'").unwrap(),
+            vec!["echo", "hello world"]
+        );
+        assert_eq!(
+            split("echo \"hello world\"").unwrap(),
+            vec!["echo", "hello world"]
+        );
+        assert_eq!(split("  a\t b  ").unwrap(), vec!["a", "b"]);
+        assert_eq!(split("a '' b").unwrap(), vec!["a", "", "b"]);
+    }
+
+    #[test]
+    fn test_split_escapes() {
+        // Literal inside single quotes
+        assert_eq!(split(r"echo 'a\nb'").unwrap(), vec!["echo", r"a\nb"]);
+        // Escaped quotes inside and outside double quotes
+        assert_eq!(
+            split(r#"echo "say \"hi\"" it\'s"#).unwrap(),
+            vec!["echo", r#"say "hi""#, "it's"]
+        );
+        // Other backslashes in double quotes are kept
+        assert_eq!(split(r#""C:\path""#).unwrap(), vec![r"C:\path"]);
+        assert_eq!(split(r"a\ b").unwrap(), vec!["a b"]);
+    }
+
+    #[test]
+    fn test_split_joins_adjacent_segments() {
+        assert_eq!(split(r#"foo"bar"baz"#).unwrap(), vec!["foobarbaz"]);
+        assert_eq!(
+            split(r#"--flag='x y'"z" next"#).unwrap(),
+            vec!["--flag=x yz", "next"]
+        );
+    }
+
+    #[test]
+    fn test_split_errors() {
+        assert_eq!(
+            split("echo 'oops"),
+            Err(ShellParseError::UnterminatedQuote('\''))
+        );
+        assert_eq!(
+            split("echo \"oops"),
+            Err(ShellParseError::UnterminatedQuote('"'))
+        );
+        assert_eq!(
+            split("echo \"oops\\\""),
+            Err(ShellParseError::UnterminatedQuote('"'))
+        );
+        assert_eq!(split("echo \\"), Err(ShellParseError::TrailingBackslash));
+    }
+
+    #[test]
+    fn test_expand_vars() {
+        let lookup = |var: &str| (var == "CARGO_HOME").then(|| "/opt/cargo".to_string());
+
+        assert_eq!(
+            expand_vars("${CARGO_HOME}/bin/tool --x", lookup).unwrap(),
+            "/opt/cargo/bin/tool --x"
+        );
+        assert_eq!(
+            expand_vars("echo $$ $${CARGO_HOME} $HOME a$", lookup).unwrap(),
+            "echo $ ${CARGO_HOME} $HOME a$"
+        );
+        assert_eq!(
+            expand_vars("${NOPE}/tool", lookup),
+            Err(ExpandError::Unset("NOPE".to_string()))
+        );
+        assert_eq!(
+            expand_vars("${CARGO_HOME", lookup),
+            Err(ExpandError::Unterminated)
+        );
+        // Left for the shell, as a shell would
+        assert_eq!(
+            expand_vars(
+                r#"sh -c 'echo ${X-unset} $$' \${Y} "${CARGO_HOME}'""#,
+                lookup
+            )
+            .unwrap(),
+            r#"sh -c 'echo ${X-unset} $$' \${Y} "/opt/cargo'""#
+        );
+    }
+
+    #[test]
+    fn test_join_quotes_split_arguments() {
+        let argv = ["sh", "-c", "echo 'hi' $X", "a.rs", ""].map(String::from);
+        let joined = join(&argv);
+        assert_eq!(joined, r"sh -c 'echo '\''hi'\'' $X' a.rs ''");
+        assert_eq!(split(&joined).unwrap(), argv);
+    }
+}
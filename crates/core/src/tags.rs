@@ -0,0 +1,280 @@
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Tags for the kind of filesystem entry
+const TYPE_TAGS: &[&str] = &["file", "directory", "symlink"];
+
+/// Tags for the executable bit
+const MODE_TAGS: &[&str] = &["executable", "non-executable"];
+
+/// Tags for the contents
+const ENCODING_TAGS: &[&str] = &["text", "binary"];
+
+/// Tags implied by a file extension (matched case-insensitively)
+const EXTENSIONS: &[(&str, &[&str])] = &[
+    ("bash", &["text", "shell", "bash"]),
+    ("c", &["text", "c"]),
+    ("cc", &["text", "c++"]),
+    ("cpp", &["text", "c++"]),
+    ("css", &["text", "css"]),
+    ("gif", &["binary", "image", "gif"]),
+    ("go", &["text", "go"]),
+    ("gz", &["binary", "gzip"]),
+    ("h", &["text", "header", "c"]),
+    ("html", &["text", "html"]),
+    ("java", &["text", "java"]),
+    ("jpeg", &["binary", "image", "jpeg"]),
+    ("jpg", &["binary", "image", "jpeg"]),
+    ("js", &["text", "javascript"]),
+    ("json", &["text", "json"]),
+    ("md", &["text", "markdown"]),
+    ("pl", &["text", "perl"]),
+    ("png", &["binary", "image", "png"]),
+    ("py", &["text", "python"]),
+    ("pyi", &["text", "pyi", "python"]),
+    ("rb", &["text", "ruby"]),
+    ("rs", &["text", "rust"]),
+    ("sh", &["text", "shell", "sh"]),
+    ("toml", &["text", "toml"]),
+    ("ts", &["text", "ts"]),
+    ("txt", &["text", "plain-text"]),
+    ("yaml", &["text", "yaml"]),
+    ("yml", &["text", "yaml"]),
+    ("zip", &["binary", "zip"]),
+    ("zsh", &["text", "shell", "zsh"]),
+];
+
+/// Tags implied by a whole file name, for files conventionally without an extension
+const NAMES: &[(&str, &[&str])] = &[
+    ("Cargo.lock", &["text", "toml"]),
+    ("Dockerfile", &["text", "dockerfile"]),
+    ("Makefile", &["text", "makefile"]),
+];
+
+/// Tags implied by the interpreter named in a shebang line
+const INTERPRETERS: &[(&str, &[&str])] = &[
+    ("bash", &["shell", "bash"]),
+    ("node", &["javascript"]),
+    ("perl", &["perl"]),
+    ("python", &["python"]),
+    ("ruby", &["ruby"]),
+    ("sh", &["shell", "sh"]),
+    ("zsh", &["shell", "zsh"]),
+];
+
+/// Every tag [`tags_from_path`] can produce, for validating hook `types`
+pub fn all_tags() -> BTreeSet<&'static str> {
+    let tables = [EXTENSIONS, NAMES, INTERPRETERS];
+    [TYPE_TAGS, MODE_TAGS, ENCODING_TAGS]
+        .into_iter()
+        .flatten()
+        .copied()
+        .chain(
+            tables
+                .into_iter()
+                .flatten()
+                .flat_map(|(_, tags)| tags.iter().copied()),
+        )
+        .collect()
+}
+
+/// Classify a path into the tags matched by hook `types`, like pre-commit's
+/// `identify`: from its metadata (`file`, `executable`, ...), its name or
+/// extension (`rust`, `yaml`, ...), the interpreter of its shebang line when
+/// the name says nothing, and a sniff of its contents (`text` or `binary`).
+///
+/// A path that can't be read has no tags; a symlink is only `symlink`, and a
+/// directory only `directory`.
+pub fn tags_from_path(path: &Path) -> BTreeSet<&'static str> {
+    let mut tags = BTreeSet::new();
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return tags;
+    };
+    if metadata.file_type().is_symlink() {
+        tags.insert("symlink");
+        return tags;
+    }
+    if metadata.is_dir() {
+        tags.insert("directory");
+        return tags;
+    }
+
+    tags.insert("file");
+    let executable = is_executable(&metadata);
+    tags.insert(if executable {
+        "executable"
+    } else {
+        "non-executable"
+    });
+
+    match tags_from_filename(path) {
+        Some(by_name) => tags.extend(by_name),
+        None if executable => tags.extend(shebang_tags(path)),
+        None => {}
+    }
+
+    if !tags.iter().any(|tag| ENCODING_TAGS.contains(tag)) {
+        tags.insert(if is_text(path) { "text" } else { "binary" });
+    }
+    tags
+}
+
+/// Tags implied by the file name or extension alone
+pub fn tags_from_filename(path: &Path) -> Option<&'static [&'static str]> {
+    let name = path.file_name()?.to_str()?;
+    if let Some((_, tags)) = NAMES.iter().find(|(known, _)| *known == name) {
+        return Some(tags);
+    }
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map(|(_, tags)| *tags)
+}
+
+/// Tags for the interpreter of a `#!` line, e.g. `#!/usr/bin/env python3`
+fn shebang_tags(path: &Path) -> &'static [&'static str] {
+    let Ok(file) = File::open(path) else {
+        return &[];
+    };
+    let mut line = String::new();
+    if BufReader::new(file.take(1024))
+        .read_line(&mut line)
+        .is_err()
+    {
+        return &[];
+    }
+    let Some(command) = line.strip_prefix("#!") else {
+        return &[];
+    };
+
+    let mut words = command.split_whitespace().map(|word| {
+        Path::new(word)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(word)
+    });
+    let mut interpreter = words.next().unwrap_or_default();
+    if interpreter == "env" {
+        // Skip `env` options such as `-S`
+        interpreter = words
+            .find(|word| !word.starts_with('-'))
+            .unwrap_or_default();
+    }
+
+    // `python3.12` is `python`
+    let interpreter = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    INTERPRETERS
+        .iter()
+        .find(|(known, _)| *known == interpreter)
+        .map_or(&[], |(_, tags)| *tags)
+}
+
+/// Whether the start of the file looks like text: no NUL bytes and valid UTF-8
+/// (allowing a character cut off at the end of the sample)
+fn is_text(path: &Path) -> bool {
+    let mut sample = Vec::new();
+    match File::open(path).and_then(|file| file.take(1024).read_to_end(&mut sample)) {
+        Ok(_) => {}
+        Err(_) => return false,
+    }
+    if sample.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(&sample) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(path: &Path) -> Vec<&'static str> {
+        tags_from_path(path).into_iter().collect()
+    }
+
+    #[test]
+    fn test_rust_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.rs");
+        fs::write(&path, "fn main() {}\n").unwrap();
+
+        assert_eq!(tags(&path), vec!["file", "non-executable", "rust", "text"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shebang_script() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("release");
+        fs::write(&path, "#!/usr/bin/env -S python3.12 -u\nprint('hi')\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(tags(&path), vec!["executable", "file", "python", "text"]);
+
+        fs::write(&path, "#!/bin/bash\necho hi\n").unwrap();
+        assert_eq!(
+            tags(&path),
+            vec!["bash", "executable", "file", "shell", "text"]
+        );
+
+        // A shebang only counts when the file can be executed
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(tags(&path), vec!["file", "non-executable", "text"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("lib.rs");
+        fs::write(&target, "").unwrap();
+        let link = dir.path().join("link.rs");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert_eq!(tags(&link), vec!["symlink"]);
+    }
+
+    #[test]
+    fn test_binary_and_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blob");
+        fs::write(&path, [0x7f, b'E', b'L', b'F', 0, 1]).unwrap();
+
+        assert_eq!(tags(&path), vec!["binary", "file", "non-executable"]);
+        assert!(tags(&dir.path().join("missing")).is_empty());
+        assert_eq!(tags(dir.path()), vec!["directory"]);
+    }
+
+    #[test]
+    fn test_all_tags() {
+        let all = all_tags();
+        for tag in [
+            "file",
+            "symlink",
+            "executable",
+            "text",
+            "rust",
+            "python",
+            "yaml",
+        ] {
+            assert!(all.contains(tag), "{}", tag);
+        }
+    }
+}
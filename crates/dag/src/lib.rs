@@ -1,19 +1,186 @@
-use petgraph::algo::toposort;
+use petgraph::algo::{kosaraju_scc, toposort};
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::visit::EdgeRef;
 use pre_commit_core::{ExecutionPlan, Hook, PlanBuilder, PreCommitError, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// Builds an execution plan from a list of hooks with dependencies
 pub struct DagBuilder;
 
+/// Why a hook was placed in its plan level
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelExplanation {
+    pub hook_id: String,
+    /// Index into `ExecutionPlan::levels`
+    pub level: usize,
+    /// The deepest dependency, which put the hook one level below it; `None`
+    /// for hooks without dependencies. Ties go to the first declared hook.
+    pub determined_by: Option<String>,
+}
+
+/// One hook on the critical path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CriticalStep {
+    pub hook_id: String,
+    pub duration_ms: u64,
+    /// Time from the start of the path to the end of this hook
+    pub cumulative_ms: u64,
+}
+
 impl DagBuilder {
     pub fn new() -> Self {
         Self
     }
 
-    /// Build a directed acyclic graph from hooks
-    fn build_graph(hooks: &[Hook]) -> Result<DiGraph<Hook, ()>> {
+    /// Build a plan with extra ordering constraints on top of `depends_on`.
+    ///
+    /// Each `(before, after)` pair of hook ids is added as an edge, as if
+    /// `after` listed `before` in its `depends_on`. Unknown ids and cycles are
+    /// reported the same way as for `depends_on`.
+    pub fn build_plan_with_edges(
+        &self,
+        hooks: &[Hook],
+        edges: &[(String, String)],
+    ) -> Result<ExecutionPlan> {
+        if hooks.is_empty() {
+            return Ok(ExecutionPlan::new(vec![]));
+        }
+
+        let graph = Self::build_graph(hooks, edges)?;
+        let levels = Self::compute_levels(&graph)?;
+
+        Ok(ExecutionPlan::new(levels))
+    }
+
+    /// Explain the level of each hook in the plan for `hooks`, in config order
+    pub fn explain(&self, hooks: &[Hook]) -> Result<Vec<LevelExplanation>> {
+        let graph = Self::build_graph(hooks, &[])?;
+        let depths = Self::compute_depths(&graph)?;
+
+        let explanations = graph
+            .node_indices()
+            .map(|idx| {
+                let (depth, parent) = depths[&idx];
+                LevelExplanation {
+                    hook_id: graph[idx].id.clone(),
+                    level: depth - 1,
+                    determined_by: parent.map(|parent| graph[parent].id.clone()),
+                }
+            })
+            .collect();
+
+        Ok(explanations)
+    }
+
+    /// Render the dependency graph of `hooks` as GraphViz DOT: one node per
+    /// hook, labelled with its name, and one edge from each dependency to its
+    /// dependent. Cycles are drawn like any other edges rather than rejected.
+    pub fn to_dot(&self, hooks: &[Hook]) -> Result<String> {
+        let graph = Self::build_graph(hooks, &[])?;
+
+        let mut dot = String::from("digraph hooks {\n    rankdir=LR;\n");
+        for idx in graph.node_indices() {
+            let hook = &graph[idx];
+            dot.push_str(&format!(
+                "    \"{}\" [label=\"{}\"];\n",
+                dot_escape(&hook.id),
+                dot_escape(&hook.name)
+            ));
+        }
+        for edge in graph.edge_references() {
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\";\n",
+                dot_escape(&graph[edge.source()].id),
+                dot_escape(&graph[edge.target()].id)
+            ));
+        }
+        dot.push_str("}\n");
+        Ok(dot)
+    }
+
+    /// Each hook id with the ids of the hooks it depends on, dependencies
+    /// first (a topological order, ties in declaration order)
+    pub fn edges(&self, hooks: &[Hook]) -> Result<Vec<(String, Vec<String>)>> {
+        let graph = Self::build_graph(hooks, &[])?;
+        let depths = Self::compute_depths(&graph)?;
+
+        let mut order: Vec<NodeIndex> = graph.node_indices().collect();
+        order.sort_by_key(|idx| (depths[idx].0, *idx));
+
+        let edges = order
+            .into_iter()
+            .map(|idx| {
+                let mut deps: Vec<NodeIndex> = graph
+                    .neighbors_directed(idx, petgraph::Direction::Incoming)
+                    .collect();
+                deps.sort_unstable();
+                deps.dedup();
+                let deps = deps.into_iter().map(|dep| graph[dep].id.clone()).collect();
+                (graph[idx].id.clone(), deps)
+            })
+            .collect();
+        Ok(edges)
+    }
+
+    /// The chain of dependent hooks with the largest total duration, which
+    /// bounds the wall time of a parallel run. `durations_ms` holds each hook's
+    /// recorded duration; hooks without one count as taking no time. Ties go to
+    /// the first declared hook.
+    pub fn critical_path(
+        &self,
+        hooks: &[Hook],
+        durations_ms: &HashMap<String, u64>,
+    ) -> Result<Vec<CriticalStep>> {
+        // Longest finishing time of a path ending at each hook, with the
+        // dependency it came through
+        let mut finish: HashMap<String, (u64, Option<String>)> = HashMap::new();
+        for (id, deps) in self.edges(hooks)? {
+            let slowest_dep = deps
+                .into_iter()
+                .map(|dep| (finish[&dep].0, dep))
+                .reduce(|best, next| if next.0 > best.0 { next } else { best });
+            let duration = durations_ms.get(&id).copied().unwrap_or(0);
+            let entry = match slowest_dep {
+                Some((start, dep)) => (start + duration, Some(dep)),
+                None => (duration, None),
+            };
+            finish.insert(id, entry);
+        }
+
+        let Some(mut current) = hooks
+            .iter()
+            .map(|hook| &hook.id)
+            .reduce(|best, next| {
+                if finish[next].0 > finish[best].0 {
+                    next
+                } else {
+                    best
+                }
+            })
+            .cloned()
+        else {
+            return Ok(Vec::new());
+        };
+
+        let mut path = Vec::new();
+        loop {
+            let (cumulative_ms, previous) = finish[&current].clone();
+            path.push(CriticalStep {
+                duration_ms: durations_ms.get(&current).copied().unwrap_or(0),
+                hook_id: current,
+                cumulative_ms,
+            });
+            match previous {
+                Some(previous) => current = previous,
+                None => break,
+            }
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    /// Build a directed acyclic graph from hooks and any extra edges
+    fn build_graph(hooks: &[Hook], edges: &[(String, String)]) -> Result<DiGraph<Hook, ()>> {
         let mut graph = DiGraph::new();
         let mut hook_indices: HashMap<String, NodeIndex> = HashMap::new();
 
@@ -33,42 +200,158 @@ impl DagBuilder {
                 // Edge from dependency to dependent (dep must run before hook)
                 graph.add_edge(*dep_idx, hook_idx, ());
             }
+
+            // Soft dependencies only order against hooks that are actually in
+            // the plan
+            for other_id in &hook.soft_depends_on {
+                if hook.depends_on.contains(other_id) {
+                    continue;
+                }
+                if let Some(other_idx) = hook_indices.get(other_id) {
+                    graph.add_edge(*other_idx, hook_idx, ());
+                }
+            }
+        }
+
+        let index_of = |id: &String| {
+            hook_indices
+                .get(id)
+                .copied()
+                .ok_or_else(|| PreCommitError::HookNotFound(id.clone()))
+        };
+        for (before, after) in edges {
+            graph.add_edge(index_of(before)?, index_of(after)?, ());
         }
 
         Ok(graph)
     }
 
-    /// Compute execution levels where all hooks in a level can run in parallel
-    fn compute_levels(graph: &DiGraph<Hook, ()>) -> Result<Vec<Vec<Hook>>> {
+    /// Depth of each node (1 for roots), with the parent that determined it
+    fn compute_depths(
+        graph: &DiGraph<Hook, ()>,
+    ) -> Result<HashMap<NodeIndex, (usize, Option<NodeIndex>)>> {
         // Topologically sort the graph
-        let sorted = toposort(graph, None).map_err(|_| PreCommitError::CycleDetected)?;
+        let sorted = toposort(graph, None).map_err(|cycle| {
+            PreCommitError::CycleDetected(Self::cycle_path(graph, cycle.node_id()))
+        })?;
 
-        // Compute the depth of each node
-        let mut depths: HashMap<NodeIndex, usize> = HashMap::new();
+        let mut depths: HashMap<NodeIndex, (usize, Option<NodeIndex>)> = HashMap::new();
 
         for &node_idx in &sorted {
-            let incoming_edges = graph.edges_directed(node_idx, petgraph::Direction::Incoming);
-            let max_parent_depth = incoming_edges
-                .map(|edge| depths.get(&edge.source()).copied().unwrap_or(0))
-                .max()
-                .unwrap_or(0);
+            // Deepest parent first, then the earliest declared one
+            let deepest_parent = graph
+                .edges_directed(node_idx, petgraph::Direction::Incoming)
+                .map(|edge| (depths[&edge.source()].0, edge.source()))
+                .min_by_key(|&(depth, idx)| (std::cmp::Reverse(depth), idx));
+
+            let entry = match deepest_parent {
+                Some((depth, parent)) => (depth + 1, Some(parent)),
+                None => (1, None),
+            };
+            depths.insert(node_idx, entry);
+        }
+
+        Ok(depths)
+    }
 
-            depths.insert(node_idx, max_parent_depth + 1);
+    /// Hook ids of a shortest cycle through the strongly connected component
+    /// containing `node`, starting at its earliest declared hook and following
+    /// `depends_on`
+    fn cycle_path(graph: &DiGraph<Hook, ()>, node: NodeIndex) -> Vec<String> {
+        let component: HashSet<NodeIndex> = kosaraju_scc(graph)
+            .into_iter()
+            .find(|component| component.contains(&node))
+            .map(|component| component.into_iter().collect())
+            .unwrap_or_else(|| HashSet::from([node]));
+        let start = component.iter().copied().min().unwrap_or(node);
+
+        // Breadth-first search back to `start`, walking from each hook to its
+        // dependencies (incoming edges) without leaving the component
+        let mut previous: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        'search: while let Some(current) = queue.pop_front() {
+            for dep in graph.neighbors_directed(current, petgraph::Direction::Incoming) {
+                if dep == start {
+                    previous.insert(start, current);
+                    break 'search;
+                }
+                if component.contains(&dep) && !previous.contains_key(&dep) {
+                    previous.insert(dep, current);
+                    queue.push_back(dep);
+                }
+            }
         }
 
-        // Group hooks by depth
-        let max_depth = depths.values().max().copied().unwrap_or(0);
-        let mut levels: Vec<Vec<Hook>> = vec![Vec::new(); max_depth];
+        // Walk the predecessors back from `start`, then reverse into dependency order
+        let mut path = vec![start];
+        let mut current = start;
+        while let Some(&prev) = previous.get(&current) {
+            path.push(prev);
+            if prev == start {
+                break;
+            }
+            current = prev;
+        }
+        path.reverse();
+
+        path.into_iter().map(|idx| graph[idx].id.clone()).collect()
+    }
+
+    /// Compute execution levels where all hooks in a level can run in parallel
+    fn compute_levels(graph: &DiGraph<Hook, ()>) -> Result<Vec<Vec<Hook>>> {
+        let depths = Self::compute_depths(graph)?;
+
+        // Group nodes by depth
+        let max_depth = depths.values().map(|&(depth, _)| depth).max().unwrap_or(0);
+        let mut levels: Vec<Vec<NodeIndex>> = vec![Vec::new(); max_depth];
 
-        for (node_idx, depth) in depths {
-            let hook = &graph[node_idx];
-            levels[depth - 1].push(hook.clone());
+        for (node_idx, (depth, _)) in depths {
+            levels[depth - 1].push(node_idx);
         }
 
+        // Nodes are added in config order, so the node index is the declaration
+        // index; sorting by it keeps each level in the order hooks were written
+        let levels = levels
+            .into_iter()
+            .map(|mut level| {
+                level.sort_unstable();
+                let hooks = level.into_iter().map(|idx| graph[idx].clone()).collect();
+                Self::apply_after_hints(hooks)
+            })
+            .collect();
+
         Ok(levels)
     }
 }
 
+impl DagBuilder {
+    /// Reorder a level so each hook comes after the same-level hooks in its
+    /// `after` list, otherwise keeping the existing order. Hints that would form
+    /// a cycle are ignored.
+    fn apply_after_hints(mut remaining: Vec<Hook>) -> Vec<Hook> {
+        let mut ordered: Vec<Hook> = Vec::with_capacity(remaining.len());
+
+        while !remaining.is_empty() {
+            let ready = remaining
+                .iter()
+                .position(|hook| {
+                    hook.after
+                        .iter()
+                        .all(|id| !remaining.iter().any(|other| &other.id == id))
+                })
+                .unwrap_or(0);
+            ordered.push(remaining.remove(ready));
+        }
+
+        ordered
+    }
+}
+
+/// Escape a DOT quoted string
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 impl Default for DagBuilder {
     fn default() -> Self {
         Self::new()
@@ -77,14 +360,7 @@ impl Default for DagBuilder {
 
 impl PlanBuilder for DagBuilder {
     fn build_plan(&self, hooks: &[Hook]) -> Result<ExecutionPlan> {
-        if hooks.is_empty() {
-            return Ok(ExecutionPlan::new(vec![]));
-        }
-
-        let graph = Self::build_graph(hooks)?;
-        let levels = Self::compute_levels(&graph)?;
-
-        Ok(ExecutionPlan::new(levels))
+        self.build_plan_with_edges(hooks, &[])
     }
 }
 
@@ -101,6 +377,7 @@ mod tests {
             files: None,
             pass_filenames: false,
             depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
         }
     }
 
@@ -167,6 +444,111 @@ mod tests {
         assert_eq!(plan.levels[2][0].id, "d");
     }
 
+    #[test]
+    fn test_levels_preserve_declaration_order() {
+        let ids = |plan: &ExecutionPlan, level: usize| -> Vec<String> {
+            plan.levels[level].iter().map(|h| h.id.clone()).collect()
+        };
+
+        let builder = DagBuilder::new();
+
+        let hooks = vec![
+            make_hook("z", vec![]),
+            make_hook("a", vec![]),
+            make_hook("m", vec![]),
+            make_hook("last", vec!["a"]),
+            make_hook("first", vec!["z"]),
+        ];
+        let plan = builder.build_plan(&hooks).unwrap();
+        assert_eq!(ids(&plan, 0), vec!["z", "a", "m"]);
+        assert_eq!(ids(&plan, 1), vec!["last", "first"]);
+
+        let reordered = vec![
+            make_hook("m", vec![]),
+            make_hook("first", vec!["z"]),
+            make_hook("z", vec![]),
+            make_hook("a", vec![]),
+            make_hook("last", vec!["a"]),
+        ];
+        let plan = builder.build_plan(&reordered).unwrap();
+        assert_eq!(ids(&plan, 0), vec!["m", "z", "a"]);
+        assert_eq!(ids(&plan, 1), vec!["first", "last"]);
+    }
+
+    #[test]
+    fn test_after_hints_order_within_level() {
+        let mut lint = make_hook("lint", vec![]);
+        lint.after = vec!["fmt".to_string()];
+        let mut report = make_hook("report", vec!["lint"]);
+        // Hints about hooks in another level have no effect
+        report.after = vec!["fmt".to_string()];
+
+        let hooks = vec![
+            lint,
+            make_hook("test", vec![]),
+            make_hook("fmt", vec![]),
+            report,
+        ];
+        let plan = DagBuilder::new().build_plan(&hooks).unwrap();
+
+        // No extra level: `after` is not an edge
+        assert_eq!(plan.levels.len(), 2);
+        let level: Vec<_> = plan.levels[0].iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(level, vec!["test", "fmt", "lint"]);
+        assert_eq!(plan.levels[1][0].id, "report");
+    }
+
+    #[test]
+    fn test_after_hint_cycles_fall_back_to_declaration_order() {
+        let mut a = make_hook("a", vec![]);
+        a.after = vec!["b".to_string()];
+        let mut b = make_hook("b", vec![]);
+        b.after = vec!["a".to_string()];
+
+        let plan = DagBuilder::new().build_plan(&[a, b]).unwrap();
+        let level: Vec<_> = plan.levels[0].iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(level, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_soft_depends_on_orders_present_hooks() {
+        let mut lint = make_hook("lint", vec![]);
+        lint.soft_depends_on = vec!["fmt".to_string()];
+        let hooks = vec![lint, make_hook("test", vec![]), make_hook("fmt", vec![])];
+
+        let plan = DagBuilder::new().build_plan(&hooks).unwrap();
+        let levels: Vec<Vec<_>> = plan
+            .levels
+            .iter()
+            .map(|level| level.iter().map(|h| h.id.as_str()).collect())
+            .collect();
+        assert_eq!(levels, vec![vec!["test", "fmt"], vec!["lint"]]);
+    }
+
+    #[test]
+    fn test_soft_depends_on_absent_hook_is_ignored() {
+        let mut lint = make_hook("lint", vec![]);
+        lint.soft_depends_on = vec!["fmt".to_string()];
+
+        let plan = DagBuilder::new().build_plan(&[lint]).unwrap();
+        assert_eq!(plan.levels.len(), 1);
+        assert_eq!(plan.levels[0][0].id, "lint");
+
+        // `depends_on` stays strict
+        let report = make_hook("report", vec!["fmt"]);
+        assert!(DagBuilder::new().build_plan(&[report]).is_err());
+    }
+
+    #[test]
+    fn test_soft_depends_on_cycles_are_detected() {
+        let mut a = make_hook("a", vec![]);
+        a.soft_depends_on = vec!["b".to_string()];
+        let b = make_hook("b", vec!["a"]);
+
+        let result = DagBuilder::new().build_plan(&[a, b]);
+        assert!(matches!(result, Err(PreCommitError::CycleDetected(_))));
+    }
+
     #[test]
     fn test_cycle_detection() {
         let hooks = vec![make_hook("a", vec!["b"]), make_hook("b", vec!["a"])];
@@ -174,7 +556,34 @@ mod tests {
         let builder = DagBuilder::new();
         let result = builder.build_plan(&hooks);
 
-        assert!(matches!(result, Err(PreCommitError::CycleDetected)));
+        assert!(matches!(result, Err(PreCommitError::CycleDetected(_))));
+    }
+
+    #[test]
+    fn test_cycle_path_in_error() {
+        let hooks = vec![
+            make_hook("fmt", vec![]),
+            make_hook("a", vec!["b", "fmt"]),
+            make_hook("b", vec!["c"]),
+            make_hook("c", vec!["a"]),
+        ];
+
+        let err = DagBuilder::new().build_plan(&hooks).unwrap_err();
+        assert!(
+            matches!(&err, PreCommitError::CycleDetected(path) if path == &["a", "b", "c", "a"])
+        );
+        assert_eq!(
+            err.to_string(),
+            "Cycle detected in hook dependencies: a -> b -> c -> a"
+        );
+
+        let err = DagBuilder::new()
+            .build_plan(&[make_hook("self", vec!["self"])])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cycle detected in hook dependencies: self -> self"
+        );
     }
 
     #[test]
@@ -187,6 +596,45 @@ mod tests {
         assert!(matches!(result, Err(PreCommitError::HookNotFound(_))));
     }
 
+    fn edge(before: &str, after: &str) -> (String, String) {
+        (before.to_string(), after.to_string())
+    }
+
+    #[test]
+    fn test_extra_edges_change_levels() {
+        let hooks = vec![
+            make_hook("security-scan", vec![]),
+            make_hook("fmt", vec![]),
+            make_hook("lint", vec!["fmt"]),
+        ];
+
+        let plan = DagBuilder::new()
+            .build_plan_with_edges(
+                &hooks,
+                &[edge("fmt", "security-scan"), edge("lint", "security-scan")],
+            )
+            .unwrap();
+
+        let ids: Vec<Vec<&str>> = plan
+            .levels
+            .iter()
+            .map(|level| level.iter().map(|h| h.id.as_str()).collect())
+            .collect();
+        assert_eq!(ids, vec![vec!["fmt"], vec!["lint"], vec!["security-scan"]]);
+    }
+
+    #[test]
+    fn test_extra_edge_cycle_and_unknown_id() {
+        let hooks = vec![make_hook("a", vec![]), make_hook("b", vec!["a"])];
+        let builder = DagBuilder::new();
+
+        let result = builder.build_plan_with_edges(&hooks, &[edge("b", "a")]);
+        assert!(matches!(result, Err(PreCommitError::CycleDetected(_))));
+
+        let result = builder.build_plan_with_edges(&hooks, &[edge("a", "missing")]);
+        assert!(matches!(result, Err(PreCommitError::HookNotFound(id)) if id == "missing"));
+    }
+
     #[test]
     fn test_empty_hooks() {
         let hooks = vec![];
@@ -247,4 +695,150 @@ mod tests {
         assert!(g_level > d_level);
         assert!(g_level > e_level);
     }
+
+    #[test]
+    fn test_explain_complex_dag() {
+        let hooks = vec![
+            make_hook("a", vec![]),
+            make_hook("b", vec!["a"]),
+            make_hook("c", vec!["a"]),
+            make_hook("d", vec!["b"]),
+            make_hook("e", vec!["c"]),
+            make_hook("f", vec!["c"]),
+            make_hook("g", vec!["d", "e"]),
+        ];
+
+        let explanations = DagBuilder::new().explain(&hooks).unwrap();
+        let ids: Vec<_> = explanations.iter().map(|e| e.hook_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c", "d", "e", "f", "g"]);
+
+        assert_eq!(
+            explanations[0],
+            LevelExplanation {
+                hook_id: "a".to_string(),
+                level: 0,
+                determined_by: None,
+            }
+        );
+        // d and e are both at level 2; d is declared first
+        assert_eq!(
+            explanations[6],
+            LevelExplanation {
+                hook_id: "g".to_string(),
+                level: 3,
+                determined_by: Some("d".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_to_dot_diamond() {
+        let hooks = vec![
+            make_hook("a", vec![]),
+            make_hook("b", vec!["a"]),
+            make_hook("c", vec!["a"]),
+            make_hook("d", vec!["b", "c"]),
+        ];
+
+        let dot = DagBuilder::new().to_dot(&hooks).unwrap();
+        let lines: Vec<&str> = dot.lines().map(str::trim).collect();
+        assert_eq!(lines.first(), Some(&"digraph hooks {"));
+        assert_eq!(lines.last(), Some(&"}"));
+        for node in ["a", "b", "c", "d"] {
+            let line = format!("\"{}\" [label=\"Hook {}\"];", node, node);
+            assert!(lines.contains(&line.as_str()), "{}", dot);
+        }
+        for edge in [
+            "\"a\" -> \"b\";",
+            "\"a\" -> \"c\";",
+            "\"b\" -> \"d\";",
+            "\"c\" -> \"d\";",
+        ] {
+            assert!(lines.contains(&edge), "{}", dot);
+        }
+        assert_eq!(dot.matches("->").count(), 4);
+    }
+
+    #[test]
+    fn test_edges_in_dependency_order() {
+        let hooks = vec![
+            make_hook("d", vec!["b", "c"]),
+            make_hook("b", vec!["a"]),
+            make_hook("c", vec!["a"]),
+            make_hook("a", vec![]),
+        ];
+
+        let edges = DagBuilder::new().edges(&hooks).unwrap();
+        assert_eq!(
+            edges,
+            vec![
+                ("a".to_string(), vec![]),
+                ("b".to_string(), vec!["a".to_string()]),
+                ("c".to_string(), vec!["a".to_string()]),
+                ("d".to_string(), vec!["b".to_string(), "c".to_string()]),
+            ]
+        );
+
+        let cycle = vec![make_hook("a", vec!["b"]), make_hook("b", vec!["a"])];
+        assert!(matches!(
+            DagBuilder::new().edges(&cycle),
+            Err(PreCommitError::CycleDetected(_))
+        ));
+    }
+
+    #[test]
+    fn test_critical_path_follows_slow_branch() {
+        let hooks = vec![
+            make_hook("a", vec![]),
+            make_hook("fast", vec!["a"]),
+            make_hook("slow", vec!["a"]),
+            make_hook("d", vec!["fast", "slow"]),
+            make_hook("lone", vec![]),
+        ];
+        let durations: HashMap<String, u64> = [
+            ("a", 10),
+            ("fast", 5),
+            ("slow", 500),
+            ("d", 20),
+            ("lone", 400),
+        ]
+        .into_iter()
+        .map(|(id, ms)| (id.to_string(), ms))
+        .collect();
+
+        let path = DagBuilder::new().critical_path(&hooks, &durations).unwrap();
+        let steps: Vec<_> = path
+            .iter()
+            .map(|step| (step.hook_id.as_str(), step.duration_ms, step.cumulative_ms))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![("a", 10, 10), ("slow", 500, 510), ("d", 20, 530)]
+        );
+    }
+
+    #[test]
+    fn test_critical_path_without_durations() {
+        let hooks = vec![make_hook("a", vec![]), make_hook("b", vec!["a"])];
+        let path = DagBuilder::new()
+            .critical_path(&hooks, &HashMap::new())
+            .unwrap();
+        let ids: Vec<_> = path.iter().map(|step| step.hook_id.as_str()).collect();
+        assert_eq!(ids, vec!["a"]);
+        assert!(DagBuilder::new()
+            .critical_path(&[], &HashMap::new())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_to_dot_renders_cycles() {
+        let mut hooks = vec![make_hook("a", vec!["b"]), make_hook("b", vec!["a"])];
+        hooks[0].name = "Say \"hi\"".to_string();
+
+        let dot = DagBuilder::new().to_dot(&hooks).unwrap();
+        assert!(dot.contains("\"a\" [label=\"Say \\\"hi\\\"\"];"), "{}", dot);
+        assert!(dot.contains("\"a\" -> \"b\";"));
+        assert!(dot.contains("\"b\" -> \"a\";"));
+    }
 }
@@ -1,109 +1,353 @@
-use pre_commit_core::{ExecutionPlan, ExecutionResult, Executor, Hook, HookResult, Result};
-use regex::Regex;
-use std::path::PathBuf;
-use std::time::Instant;
+use pre_commit_core::command::{self, build_command};
+use pre_commit_core::{
+    CancelFlag, DependencyFailures, ExecutionOptions, ExecutionPlan, ExecutionResult, Executor,
+    Hook, HookResult, LevelTiming, OutputCallback, OutputStream, Result,
+};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
 use tokio::process::Command;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Longest piece of a single output line handed to `on_output`; longer lines
+/// (e.g. progress bars redrawn with `\r`) arrive in several pieces
+const MAX_STREAMED_LINE: u64 = 8192;
+
+/// How often a running hook checks whether the run was cancelled
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Cancel the run on Ctrl-C: running hooks are killed and no more start.
+/// Once this has been polled, Ctrl-C no longer terminates the process, so
+/// executors only spawn it with [`ExecutionOptions::trap_ctrl_c`].
+pub async fn cancel_on_ctrl_c(cancel: CancelFlag) {
+    if tokio::signal::ctrl_c().await.is_ok() {
+        cancel.cancel();
+    }
+}
+
+/// Resolves once `cancel` is set
+async fn cancelled(cancel: &CancelFlag) {
+    while !cancel.is_cancelled() {
+        tokio::time::sleep(CANCEL_POLL_INTERVAL).await;
+    }
+}
+
+/// Caps how many hooks run at once: a running hook holds as many of the
+/// `jobs` permits as its `weight`
+pub struct JobBudget {
+    budget: Option<(usize, Semaphore)>,
+}
+
+impl JobBudget {
+    /// Unlimited when `jobs` is `None`
+    pub fn new(jobs: Option<usize>) -> Self {
+        Self {
+            budget: jobs.map(|jobs| (jobs.max(1), Semaphore::new(jobs.max(1)))),
+        }
+    }
+
+    /// Wait until `hook` fits in the budget; it may run while the permit is held
+    pub async fn acquire(&self, hook: &Hook) -> Option<SemaphorePermit<'_>> {
+        let (jobs, semaphore) = self.budget.as_ref()?;
+        // Hooks heavier than the whole budget still run, just alone
+        let permits = (hook.weight.max(1) as usize).min(*jobs) as u32;
+        semaphore.acquire_many(permits).await.ok()
+    }
+}
 
 /// Parallel executor that runs hooks respecting dependencies
 pub struct ParallelExecutor {
     plan: ExecutionPlan,
+    options: ExecutionOptions,
 }
 
 impl ParallelExecutor {
     pub fn new(plan: ExecutionPlan) -> Self {
-        Self { plan }
-    }
-
-    /// Filter files based on the hook's file pattern
-    fn filter_files(hook: &Hook, files: &[PathBuf]) -> Vec<PathBuf> {
-        if let Some(pattern) = &hook.files {
-            if let Ok(regex) = Regex::new(pattern) {
-                return files
-                    .iter()
-                    .filter(|f| f.to_str().map(|s| regex.is_match(s)).unwrap_or(false))
-                    .cloned()
-                    .collect();
+        Self {
+            plan,
+            options: ExecutionOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ExecutionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Whether two hooks may not run at the same time.
+    ///
+    /// A `mutates_files` hook conflicts with any hook whose filtered file list
+    /// overlaps its own. When a mutating hook picks its own files
+    /// (`pass_filenames: false`) the overlap cannot be known, so it conflicts
+    /// with everything.
+    fn conflicts(
+        a: &Hook,
+        a_files: &HashSet<PathBuf>,
+        b: &Hook,
+        b_files: &HashSet<PathBuf>,
+    ) -> bool {
+        if !a.mutates_files && !b.mutates_files {
+            return false;
+        }
+        let unknown = |hook: &Hook| hook.mutates_files && !hook.pass_filenames;
+        unknown(a) || unknown(b) || !a_files.is_disjoint(b_files)
+    }
+
+    /// Split a level into waves that can each run fully in parallel, keeping
+    /// file-mutating hooks apart from hooks touching the same files
+    pub fn conflict_free_waves(
+        hooks: &[Hook],
+        files: &[PathBuf],
+        options: &ExecutionOptions,
+    ) -> Vec<Vec<Hook>> {
+        let mut waves: Vec<Vec<(Hook, HashSet<PathBuf>)>> = Vec::new();
+
+        for hook in hooks {
+            let hook_files: HashSet<PathBuf> =
+                hook.filter_files(files, options).into_iter().collect();
+            let wave = waves.iter_mut().find(|wave| {
+                wave.iter().all(|(other, other_files)| {
+                    !Self::conflicts(hook, &hook_files, other, other_files)
+                })
+            });
+
+            match wave {
+                Some(wave) => wave.push((hook.clone(), hook_files)),
+                None => waves.push(vec![(hook.clone(), hook_files)]),
             }
         }
-        files.to_vec()
+
+        waves
+            .into_iter()
+            .map(|wave| wave.into_iter().map(|(hook, _)| hook).collect())
+            .collect()
     }
 
     /// Execute a single hook asynchronously
-    async fn execute_hook_async(hook: &Hook, files: &[PathBuf]) -> HookResult {
-        let start = Instant::now();
-
+    pub async fn execute_hook_async(
+        hook: &Hook,
+        files: &[PathBuf],
+        options: &ExecutionOptions,
+    ) -> HookResult {
         // Filter files if needed
-        let filtered_files = Self::filter_files(hook, files);
+        let filtered_files = hook.filter_files(files, options);
 
-        // Build command
-        let mut parts =
-            shell_words::split(&hook.entry).unwrap_or_else(|_| vec![hook.entry.clone()]);
+        if let Some(reason) = hook.file_count_skip_reason(filtered_files.len()) {
+            return HookResult::skipped(&hook.id, reason);
+        }
+        if options.cancel.is_cancelled() {
+            return HookResult::cancelled(hook);
+        }
 
-        if hook.pass_filenames && !filtered_files.is_empty() {
-            for file in &filtered_files {
-                if let Some(s) = file.to_str() {
-                    parts.push(s.to_string());
-                }
+        // Long file lists run as several invocations, one after another
+        let batches = command::file_batches(hook, &filtered_files, options);
+        let files_path =
+            match command::write_file_list(hook, &filtered_files, options, batches.len()) {
+                Ok(path) => path,
+                Err(e) => return HookResult::from_output(hook, Err(e), Duration::ZERO),
+            };
+        let mut result: Option<HookResult> = None;
+        for batch in batches {
+            let batch_result =
+                Self::execute_batch(hook, batch, files_path.as_deref(), options).await;
+            result = Some(match result {
+                Some(result) => result.merge(batch_result),
+                None => batch_result,
+            });
+            if options.cancel.is_cancelled() {
+                break;
             }
         }
+        result.expect("there is always at least one batch")
+    }
+
+    /// Run a hook once on one batch of its files
+    async fn execute_batch(
+        hook: &Hook,
+        files: &[PathBuf],
+        files_path: Option<&Path>,
+        options: &ExecutionOptions,
+    ) -> HookResult {
+        let start = Instant::now();
+
+        // Build command
+        let parts = match command::hook_argv(hook, files, options) {
+            Ok(parts) => parts,
+            Err(e) => return HookResult::from_output(hook, Err(e), start.elapsed()),
+        };
 
         // Execute command with color support
-        let result = if parts.is_empty() {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Empty command",
-            ))
+        let result = if options.pty {
+            // The pty runner blocks, so keep it off the async workers; it
+            // isn't killed on cancel but runs to completion
+            let (hook, files, options) = (hook.clone(), files.to_vec(), options.clone());
+            let files_path = files_path.map(Path::to_path_buf);
+            tokio::task::spawn_blocking(move || {
+                command::output(&hook, &parts, &files, files_path.as_deref(), &options)
+            })
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::other(e)))
         } else {
-            Command::new(&parts[0])
-                .args(&parts[1..])
-                .env("FORCE_COLOR", "1")
-                .env("CLICOLOR_FORCE", "1")
-                .output()
-                .await
+            match build_command(hook, &parts, files, files_path, options) {
+                Ok(process) => {
+                    let run = Self::output(Command::from(process), &hook.id, options);
+                    match hook.timeout() {
+                        Some(timeout) => tokio::time::timeout(timeout, run)
+                            .await
+                            .unwrap_or_else(|_| Err(command::timed_out_error(timeout))),
+                        None => run.await,
+                    }
+                }
+                Err(e) => Err(e),
+            }
         };
 
         let duration = start.elapsed();
 
-        match result {
-            Ok(output) => HookResult {
-                hook_id: hook.id.clone(),
-                success: output.status.success(),
-                exit_code: output.status.code(),
-                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-                duration_ms: duration.as_millis() as u64,
-            },
-            Err(e) => HookResult {
-                hook_id: hook.id.clone(),
-                success: false,
-                exit_code: None,
-                stdout: String::new(),
-                stderr: format!("Failed to execute command: {}", e),
-                duration_ms: duration.as_millis() as u64,
-            },
+        HookResult::from_output(hook, result, duration)
+    }
+
+    /// Run `command` like `output()`, but read its pipes line by line and hand
+    /// each line to `on_output` (if any) as soon as it is complete. When the
+    /// run is cancelled first, the child is killed and reaped.
+    async fn output(
+        mut command: Command,
+        hook_id: &str,
+        options: &ExecutionOptions,
+    ) -> std::io::Result<Output> {
+        async fn forward(
+            reader: impl AsyncRead + Unpin,
+            stream: OutputStream,
+            hook_id: &str,
+            on_output: Option<&OutputCallback>,
+        ) -> std::io::Result<Vec<u8>> {
+            let mut reader = BufReader::new(reader);
+            let mut collected = Vec::new();
+            loop {
+                let mut line = Vec::new();
+                let read = (&mut reader)
+                    .take(MAX_STREAMED_LINE)
+                    .read_until(b'\n', &mut line)
+                    .await?;
+                if read == 0 {
+                    return Ok(collected);
+                }
+                collected.extend_from_slice(&line);
+                if let Some(on_output) = on_output {
+                    on_output.call(hook_id, stream, line);
+                }
+            }
         }
+
+        // Dropping the future on timeout must take the child down with it
+        command.kill_on_drop(true);
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+
+        let on_output = options.on_output.as_ref();
+
+        let finished = tokio::select! {
+            finished = async {
+                tokio::try_join!(
+                    forward(stdout, OutputStream::Stdout, hook_id, on_output),
+                    forward(stderr, OutputStream::Stderr, hook_id, on_output),
+                    child.wait(),
+                )
+            } => Some(finished),
+            () = cancelled(&options.cancel) => None,
+        };
+        let Some(finished) = finished else {
+            child.kill().await.ok();
+            return Err(command::cancelled_error());
+        };
+        let (stdout, stderr, status) = finished?;
+
+        Ok(Output {
+            status,
+            stdout,
+            stderr,
+        })
     }
 
-    /// Execute all hooks in a level in parallel
-    async fn execute_level(hooks: &[Hook], files: &[PathBuf]) -> Vec<HookResult> {
-        let futures = hooks
-            .iter()
-            .map(|hook| Self::execute_hook_async(hook, files));
+    /// Execute all hooks in a level in parallel, one conflict-free wave at a time
+    async fn execute_level(
+        hooks: &[Hook],
+        files: &[PathBuf],
+        options: &ExecutionOptions,
+    ) -> Vec<HookResult> {
+        let mut results = Vec::new();
+        let budget = JobBudget::new(options.jobs);
+
+        for wave in Self::conflict_free_waves(hooks, files, options) {
+            let futures = wave.iter().map(|hook| async {
+                let _permit = budget.acquire(hook).await;
+                Self::execute_hook_async(hook, files, options).await
+            });
+            results.extend(futures::future::join_all(futures).await);
+        }
 
-        futures::future::join_all(futures).await
+        results
     }
 
     /// Execute the plan with proper dependency ordering
     pub async fn execute_async(&self, files: &[PathBuf]) -> Result<ExecutionResult> {
         let start = Instant::now();
         let mut all_results = Vec::new();
+        let mut levels = Vec::new();
+
+        let mut failures = DependencyFailures::new(self.options.fail_on);
+        let on_ctrl_c = self
+            .options
+            .trap_ctrl_c
+            .then(|| tokio::spawn(cancel_on_ctrl_c(self.options.cancel.clone())));
 
         // Execute each level sequentially, but hooks within a level in parallel
         for level in &self.plan.levels {
-            let level_results = Self::execute_level(level, files).await;
+            if self.options.cancel.is_cancelled() {
+                all_results.extend(level.iter().map(HookResult::cancelled));
+                continue;
+            }
+
+            // Hooks whose dependencies failed are skipped rather than run
+            let mut runnable = Vec::new();
+            for hook in level {
+                match failures.skip(hook) {
+                    Some(skipped) => all_results.push(skipped),
+                    None => runnable.push(hook.clone()),
+                }
+            }
+
+            let level_start = start.elapsed();
+            let level_results = Self::execute_level(&runnable, files, &self.options).await;
+            levels.push(LevelTiming {
+                hooks: runnable.iter().map(|hook| hook.id.clone()).collect(),
+                start_ms: level_start.as_millis() as u64,
+                duration_ms: (start.elapsed() - level_start).as_millis() as u64,
+            });
+            level_results.iter().for_each(|r| failures.record(r));
+            let failed = level_results
+                .iter()
+                .any(|r| r.fails_under(self.options.fail_on));
             all_results.extend(level_results);
+            if failed && self.options.fail_fast {
+                break;
+            }
+        }
+
+        if let Some(on_ctrl_c) = on_ctrl_c {
+            on_ctrl_c.abort();
         }
 
+        // Hooks finish in any order; report them in plan order
+        self.plan.sort_results(&mut all_results);
+
         let total_duration = start.elapsed();
         let all_passed = all_results.iter().all(|r| r.success);
 
@@ -111,6 +355,7 @@ impl ParallelExecutor {
             hooks: all_results,
             total_duration_ms: total_duration.as_millis() as u64,
             all_passed,
+            levels,
         })
     }
 }
@@ -124,64 +369,11 @@ impl Executor for ParallelExecutor {
     }
 }
 
-// Helper module for parsing shell commands
-mod shell_words {
-    pub fn split(input: &str) -> Result<Vec<String>, &'static str> {
-        let mut words = Vec::new();
-        let mut current = String::new();
-        let mut in_single_quote = false;
-        let mut in_double_quote = false;
-        let mut chars = input.chars().peekable();
-
-        while let Some(c) = chars.next() {
-            match c {
-                '\'' if !in_double_quote => {
-                    in_single_quote = !in_single_quote;
-                }
-                '"' if !in_single_quote => {
-                    in_double_quote = !in_double_quote;
-                }
-                ' ' | '\t' if !in_single_quote && !in_double_quote => {
-                    if !current.is_empty() {
-                        words.push(current.clone());
-                        current.clear();
-                    }
-                }
-                '\\' if !in_single_quote => {
-                    if let Some(next) = chars.next() {
-                        current.push(next);
-                    }
-                }
-                _ => {
-                    current.push(c);
-                }
-            }
-        }
-
-        if !current.is_empty() {
-            words.push(current);
-        }
-
-        Ok(words)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use pre_commit_core::ExecutionPlan;
-
-    #[test]
-    fn test_shell_words_split() {
-        assert_eq!(
-            shell_words::split("echo hello world").unwrap(),
-            vec!["echo", "hello", "world"]
-        );
-        assert_eq!(
-            shell_words::split("echo 'hello world'").unwrap(),
-            vec!["echo", "hello world"]
-        );
-    }
+    use std::sync::{Arc, Mutex};
 
     #[tokio::test]
     async fn test_execute_hook_async() {
@@ -193,13 +385,116 @@ mod tests {
             files: None,
             pass_filenames: false,
             depends_on: vec![],
+            always_run: true,
+            ..Default::default()
         };
 
-        let result = ParallelExecutor::execute_hook_async(&hook, &[]).await;
+        let result =
+            ParallelExecutor::execute_hook_async(&hook, &[], &ExecutionOptions::default()).await;
         assert!(result.success);
         assert!(result.stdout.contains("hello"));
     }
 
+    #[tokio::test]
+    async fn test_color_envs_follow_no_color() {
+        let hook = Hook {
+            id: "env".to_string(),
+            entry:
+                r#"sh -c 'echo "${FORCE_COLOR-unset} ${CLICOLOR_FORCE-unset} ${NO_COLOR-unset}"'"#
+                    .to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let forced =
+            ParallelExecutor::execute_hook_async(&hook, &[], &ExecutionOptions::default()).await;
+        assert!(forced.stdout.starts_with("1 1 "), "{}", forced.stdout);
+
+        let options = ExecutionOptions {
+            no_color: true,
+            ..Default::default()
+        };
+        let plain = ParallelExecutor::execute_hook_async(&hook, &[], &options).await;
+        assert_eq!(plain.stdout, "unset unset 1\n");
+    }
+
+    #[tokio::test]
+    async fn test_entry_with_undefined_variable_fails() {
+        let hook = Hook {
+            id: "expand".to_string(),
+            entry: "${PRE_COMMIT_RS_UNDEFINED}/bin/tool".to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let result =
+            ParallelExecutor::execute_hook_async(&hook, &[], &ExecutionOptions::default()).await;
+        assert!(!result.success);
+        assert!(
+            result
+                .stderr
+                .contains("environment variable 'PRE_COMMIT_RS_UNDEFINED' is not set"),
+            "{}",
+            result.stderr
+        );
+    }
+
+    #[tokio::test]
+    async fn test_args_are_passed_verbatim() {
+        let hook = Hook {
+            id: "printf".to_string(),
+            entry: "printf [%s]".to_string(),
+            args: vec!["--config".to_string(), "a b.toml".to_string()],
+            pass_filenames: true,
+            ..Default::default()
+        };
+
+        let files = [PathBuf::from("my file.rs")];
+        let result =
+            ParallelExecutor::execute_hook_async(&hook, &files, &ExecutionOptions::default()).await;
+        assert_eq!(result.stdout, "[--config][a b.toml][my file.rs]");
+    }
+
+    #[tokio::test]
+    async fn test_long_file_lists_run_in_batches() {
+        let hook = Hook {
+            id: "echo".to_string(),
+            entry: "echo batch".to_string(),
+            pass_filenames: true,
+            ..Default::default()
+        };
+        let files: Vec<PathBuf> = (0..2000)
+            .map(|i| PathBuf::from(format!("src/module_{:04}.rs", i)))
+            .collect();
+        let options = ExecutionOptions {
+            max_argv_bytes: Some(4096),
+            ..Default::default()
+        };
+
+        let result = ParallelExecutor::execute_hook_async(&hook, &files, &options).await;
+        assert!(result.success);
+        assert!(result.stdout.lines().count() > 1);
+        assert_eq!(result.stdout.matches(".rs").count(), files.len());
+    }
+
+    #[tokio::test]
+    async fn test_execute_hook_timeout() {
+        let hook = Hook {
+            id: "slow".to_string(),
+            entry: "sleep 5".to_string(),
+            timeout_secs: Some(1),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let result =
+            ParallelExecutor::execute_hook_async(&hook, &[], &ExecutionOptions::default()).await;
+        assert!(!result.success);
+        assert_eq!(result.exit_code, None);
+        assert_eq!(result.stderr, "hook timed out after 1s");
+        assert!((1000..4000).contains(&result.duration_ms));
+    }
+
     #[tokio::test]
     async fn test_execute_level() {
         let hooks = vec![
@@ -211,6 +506,7 @@ mod tests {
                 files: None,
                 pass_filenames: false,
                 depends_on: vec![],
+                ..Default::default()
             },
             Hook {
                 id: "hook2".to_string(),
@@ -220,10 +516,12 @@ mod tests {
                 files: None,
                 pass_filenames: false,
                 depends_on: vec![],
+                ..Default::default()
             },
         ];
 
-        let results = ParallelExecutor::execute_level(&hooks, &[]).await;
+        let results =
+            ParallelExecutor::execute_level(&hooks, &[], &ExecutionOptions::default()).await;
         assert_eq!(results.len(), 2);
         assert!(results.iter().all(|r| r.success));
     }
@@ -239,6 +537,7 @@ mod tests {
                 files: None,
                 pass_filenames: false,
                 depends_on: vec![],
+                ..Default::default()
             },
             Hook {
                 id: "hook2".to_string(),
@@ -248,6 +547,7 @@ mod tests {
                 files: None,
                 pass_filenames: false,
                 depends_on: vec![],
+                ..Default::default()
             },
         ];
 
@@ -259,6 +559,470 @@ mod tests {
         assert!(result.all_passed);
     }
 
+    #[tokio::test]
+    async fn test_results_follow_plan_order() {
+        let hook = |id: &str, entry: &str, files: &str, mutates_files: bool| Hook {
+            id: id.to_string(),
+            entry: entry.to_string(),
+            files: Some(files.to_string()),
+            pass_filenames: mutates_files,
+            mutates_files,
+            ..Default::default()
+        };
+        // `slow` finishes after `fast`, and the two writers of x.txt run in
+        // separate waves, with `reader` in the first one
+        let plan = ExecutionPlan::new(vec![
+            vec![
+                hook("slow", "sleep 0.2", "^y", false),
+                hook("fast", "sleep 0.01", "^y", false),
+            ],
+            vec![
+                hook("writer-1", "true", "^x", true),
+                hook("writer-2", "true", "^x", true),
+                hook("reader", "true", "^y", false),
+            ],
+        ]);
+        let files = [PathBuf::from("x.txt"), PathBuf::from("y.txt")];
+
+        let result = ParallelExecutor::new(plan)
+            .execute_async(&files)
+            .await
+            .unwrap();
+        let ids: Vec<_> = result.hooks.iter().map(|r| r.hook_id.as_str()).collect();
+        assert_eq!(ids, vec!["slow", "fast", "writer-1", "writer-2", "reader"]);
+    }
+
+    #[tokio::test]
+    async fn test_global_exclude_hides_files_from_every_hook() {
+        let hook = |id: &str| Hook {
+            id: id.to_string(),
+            entry: "echo".to_string(),
+            pass_filenames: true,
+            ..Default::default()
+        };
+        let plan = ExecutionPlan::new(vec![vec![hook("a"), hook("b")]]);
+        let files = [PathBuf::from("src/lib.rs"), PathBuf::from("target/gen.rs")];
+
+        let executor = ParallelExecutor::new(plan).with_options(ExecutionOptions {
+            exclude: Some("^target/".to_string()),
+            ..Default::default()
+        });
+        let result = executor.execute_async(&files).await.unwrap();
+
+        for hook_result in &result.hooks {
+            assert_eq!(hook_result.stdout.trim(), "src/lib.rs");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast_skips_later_levels() {
+        let hook = |id: &str, entry: &str| Hook {
+            id: id.to_string(),
+            entry: entry.to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+        let plan = ExecutionPlan::new(vec![
+            vec![hook("bad", "false"), hook("slow", "sleep 0.2")],
+            vec![hook("later", "true")],
+        ]);
+
+        let executor = ParallelExecutor::new(plan).with_options(ExecutionOptions {
+            fail_fast: true,
+            ..Default::default()
+        });
+        let result = executor.execute_async(&[]).await.unwrap();
+
+        // The rest of the failing level still finishes
+        let ids: Vec<_> = result.hooks.iter().map(|r| r.hook_id.as_str()).collect();
+        assert_eq!(ids, vec!["bad", "slow"]);
+        assert!(result.hooks[1].success);
+        assert!(!result.all_passed);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancel_kills_running_hooks() {
+        let dir = tempfile::tempdir().unwrap();
+        let hook = |id: &str, entry: &str| Hook {
+            id: id.to_string(),
+            entry: entry.to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+        let plan = ExecutionPlan::new(vec![
+            vec![hook(
+                "sleepy",
+                "sh -c 'echo $$ > sleepy.pid; exec sleep 30'",
+            )],
+            vec![hook("later", "touch later.ran")],
+        ]);
+        let options = ExecutionOptions {
+            repo_root: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+
+        let cancel = options.cancel.clone();
+        let pid_file = dir.path().join("sleepy.pid");
+        tokio::spawn(async move {
+            while !pid_file.exists() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            cancel.cancel();
+        });
+
+        let start = Instant::now();
+        let result = ParallelExecutor::new(plan)
+            .with_options(options)
+            .execute_async(&[])
+            .await
+            .unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        assert!(!result.all_passed);
+        assert_eq!(result.hooks[0].stderr, "hook killed: run cancelled");
+        assert_eq!(
+            result.hooks[1].skip_reason.as_deref(),
+            Some("run cancelled")
+        );
+        assert!(!dir.path().join("later.ran").exists());
+
+        // Killed and reaped: not even a zombie is left
+        let pid = std::fs::read_to_string(dir.path().join("sleepy.pid")).unwrap();
+        let alive = std::process::Command::new("kill")
+            .args(["-0", pid.trim()])
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(!alive.success(), "hook process {} still exists", pid.trim());
+    }
+
+    #[tokio::test]
+    async fn test_records_level_timings() {
+        let hook = |id: &str, entry: &str| Hook {
+            id: id.to_string(),
+            entry: entry.to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+        let plan = ExecutionPlan::new(vec![
+            vec![hook("slow", "sleep 0.1"), hook("fast", "true")],
+            vec![hook("later", "true")],
+        ]);
+
+        let result = ParallelExecutor::new(plan)
+            .execute_async(&[])
+            .await
+            .unwrap();
+
+        let levels = &result.levels;
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[0].hooks, vec!["slow", "fast"]);
+        assert!(levels[0].duration_ms >= 100, "{:?}", levels);
+        assert!(levels[1].start_ms >= levels[0].start_ms + levels[0].duration_ms);
+        assert!(levels[1].start_ms + levels[1].duration_ms <= result.total_duration_ms);
+    }
+
+    #[tokio::test]
+    async fn test_failed_dependency_skips_descendants() {
+        //     a (fails)
+        //    / \
+        //   b   c
+        //    \ /
+        //     d
+        let hook = |id: &str, entry: &str, depends_on: &[&str]| Hook {
+            id: id.to_string(),
+            entry: entry.to_string(),
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            always_run: true,
+            ..Default::default()
+        };
+        let plan = ExecutionPlan::new(vec![
+            vec![hook("a", "false", &[]), hook("other", "true", &[])],
+            vec![hook("b", "true", &["a"]), hook("c", "true", &["a"])],
+            vec![hook("d", "true", &["b", "c"])],
+        ]);
+
+        let result = ParallelExecutor::new(plan)
+            .execute_async(&[])
+            .await
+            .unwrap();
+        assert!(!result.all_passed);
+
+        let by_id = |id: &str| result.hooks.iter().find(|r| r.hook_id == id).unwrap();
+        assert!(by_id("a").is_failure());
+        assert!(by_id("other").success);
+        for id in ["b", "c", "d"] {
+            let skipped = by_id(id);
+            assert!(!skipped.success);
+            assert_eq!(
+                skipped.skip_reason.as_deref(),
+                Some("dependency 'a' failed")
+            );
+            assert_eq!(skipped.stderr, "dependency 'a' failed");
+        }
+    }
+
+    fn make_mutating_hook(id: &str, files: &str, entry: &str) -> Hook {
+        Hook {
+            id: id.to_string(),
+            name: id.to_string(),
+            entry: entry.to_string(),
+            language: "system".to_string(),
+            files: Some(files.to_string()),
+            pass_filenames: true,
+            mutates_files: true,
+            always_run: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_conflict_free_waves() {
+        let files = vec![
+            PathBuf::from("a.rs"),
+            PathBuf::from("b.rs"),
+            PathBuf::from("c.py"),
+        ];
+        let rust_fmt = make_mutating_hook("rust-fmt", "\\.rs$", "true");
+        let a_only = make_mutating_hook("a-only", "^a\\.rs$", "true");
+        let py_fmt = make_mutating_hook("py-fmt", "\\.py$", "true");
+        let reader = Hook {
+            id: "reader".to_string(),
+            ..Default::default()
+        };
+
+        // Disjoint file sets share a wave
+        let waves = ParallelExecutor::conflict_free_waves(
+            &[rust_fmt.clone(), py_fmt.clone()],
+            &files,
+            &ExecutionOptions::default(),
+        );
+        assert_eq!(waves.len(), 1);
+
+        // Overlapping mutating hooks are separated, and so is a reader of the same files
+        let waves = ParallelExecutor::conflict_free_waves(
+            &[rust_fmt.clone(), a_only, reader.clone()],
+            &files,
+            &ExecutionOptions::default(),
+        );
+        assert_eq!(waves.len(), 3);
+
+        // A mutating hook that chooses its own files is serialized against everything
+        let opaque = Hook {
+            pass_filenames: false,
+            ..py_fmt
+        };
+        let waves = ParallelExecutor::conflict_free_waves(
+            &[opaque, reader],
+            &files,
+            &ExecutionOptions::default(),
+        );
+        assert_eq!(waves.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_mutating_hooks_do_not_overlap() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("log");
+        std::fs::write(&log, "").unwrap();
+
+        let entry = |id: &str| {
+            format!(
+                "sh -c 'echo start-{id} >> {log}; sleep 0.2; echo end-{id} >> {log}' --",
+                id = id,
+                log = log.display()
+            )
+        };
+        let hooks = vec![
+            make_mutating_hook("first", "\\.rs$", &entry("first")),
+            make_mutating_hook("second", "\\.rs$", &entry("second")),
+        ];
+
+        let results = ParallelExecutor::execute_level(
+            &hooks,
+            &[PathBuf::from("a.rs")],
+            &ExecutionOptions::default(),
+        )
+        .await;
+        assert!(results.iter().all(|r| r.success));
+
+        let log_contents = std::fs::read_to_string(&log).unwrap();
+        let lines: Vec<&str> = log_contents.lines().collect();
+        assert_eq!(
+            lines,
+            vec!["start-first", "end-first", "start-second", "end-second"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_weights_limit_concurrency() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = dir.path().join("lock");
+
+        // mkdir fails if another hook holds the lock, so any overlap fails a hook
+        let entry = format!(
+            "sh -c 'mkdir {lock} || exit 1; sleep 0.1; rmdir {lock}'",
+            lock = lock.display()
+        );
+        let hooks: Vec<Hook> = ["a", "b", "c"]
+            .iter()
+            .map(|id| Hook {
+                id: id.to_string(),
+                entry: entry.clone(),
+                weight: 4,
+                always_run: true,
+                ..Default::default()
+            })
+            .collect();
+        let options = ExecutionOptions {
+            jobs: Some(4),
+            ..Default::default()
+        };
+
+        let start = Instant::now();
+        let results = ParallelExecutor::execute_level(&hooks, &[], &options).await;
+
+        assert!(results.iter().all(|r| r.success), "{:?}", results);
+        assert!(start.elapsed() >= std::time::Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_single_job_serializes_hooks() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("log");
+
+        let hooks: Vec<Hook> = ["a", "b", "c"]
+            .iter()
+            .map(|id| Hook {
+                id: id.to_string(),
+                entry: format!(
+                    "sh -c 'echo start $(date +%s%N) >> {log}; sleep 0.05; echo end $(date +%s%N) >> {log}'",
+                    log = log.display()
+                ),
+                always_run: true,
+                ..Default::default()
+            })
+            .collect();
+        let options = ExecutionOptions {
+            jobs: Some(1),
+            ..Default::default()
+        };
+        let executor = ParallelExecutor::new(ExecutionPlan::new(vec![hooks])).with_options(options);
+
+        let result = executor.execute_async(&[]).await.unwrap();
+        assert!(result.all_passed, "{:?}", result);
+
+        // Every hook ends before the next one starts
+        let log = std::fs::read_to_string(&log).unwrap();
+        let events: Vec<(&str, u128)> = log
+            .lines()
+            .map(|line| {
+                let (event, time) = line.split_once(' ').unwrap();
+                (event, time.parse().unwrap())
+            })
+            .collect();
+        assert_eq!(events.len(), 6, "{}", log);
+        for pair in events.chunks(2) {
+            assert_eq!((pair[0].0, pair[1].0), ("start", "end"), "{}", log);
+        }
+        assert!(events.windows(2).all(|w| w[0].1 <= w[1].1), "{}", log);
+    }
+
+    #[tokio::test]
+    async fn test_light_hooks_share_the_budget() {
+        let hooks: Vec<Hook> = ["a", "b"]
+            .iter()
+            .map(|id| Hook {
+                id: id.to_string(),
+                entry: "sleep 0.2".to_string(),
+                weight: 2,
+                ..Default::default()
+            })
+            .collect();
+        let options = ExecutionOptions {
+            jobs: Some(4),
+            ..Default::default()
+        };
+
+        let start = Instant::now();
+        let results = ParallelExecutor::execute_level(&hooks, &[], &options).await;
+
+        assert!(results.iter().all(|r| r.success));
+        assert!(start.elapsed() < std::time::Duration::from_millis(390));
+    }
+
+    #[tokio::test]
+    async fn test_output_callback_receives_chunks() {
+        let chunks = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&chunks);
+        let options = ExecutionOptions {
+            on_output: Some(OutputCallback::new(move |hook_id, stream, chunk| {
+                sink.lock()
+                    .unwrap()
+                    .push((hook_id.to_string(), stream, chunk));
+            })),
+            ..Default::default()
+        };
+        let hook = Hook {
+            id: "chatty".to_string(),
+            entry: "sh -c 'echo one; echo oops >&2; sleep 0.05; echo two'".to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let result = ParallelExecutor::execute_hook_async(&hook, &[], &options).await;
+        assert!(result.success);
+
+        let chunks = chunks.lock().unwrap();
+        assert!(chunks.iter().all(|(id, _, _)| id == "chatty"));
+        let reassemble = |wanted: OutputStream| {
+            let bytes: Vec<u8> = chunks
+                .iter()
+                .filter(|(_, stream, _)| *stream == wanted)
+                .flat_map(|(_, _, chunk)| chunk.clone())
+                .collect();
+            String::from_utf8(bytes).unwrap()
+        };
+        assert_eq!(reassemble(OutputStream::Stdout), result.stdout);
+        assert_eq!(reassemble(OutputStream::Stderr), result.stderr);
+        assert_eq!(result.stdout, "one\ntwo\n");
+    }
+
+    #[tokio::test]
+    async fn test_output_lines_arrive_before_exit() {
+        // The hook prints a line, then waits for the callback to have seen it:
+        // with buffered output it would never get past the loop
+        let dir = tempfile::tempdir().unwrap();
+        let go = dir.path().join("go");
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let (sink, signal) = (Arc::clone(&lines), go.clone());
+        let options = ExecutionOptions {
+            on_output: Some(OutputCallback::new(move |_, _, chunk| {
+                if chunk == b"first\n" {
+                    std::fs::write(&signal, "").unwrap();
+                }
+                sink.lock().unwrap().push(String::from_utf8(chunk).unwrap());
+            })),
+            ..Default::default()
+        };
+        let hook = Hook {
+            id: "waits".to_string(),
+            entry: format!(
+                "sh -c 'echo first; while [ ! -e {} ]; do sleep 0.01; done; printf second'",
+                go.display()
+            ),
+            timeout_secs: Some(10),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let result = ParallelExecutor::execute_hook_async(&hook, &[], &options).await;
+        assert!(result.success, "{:?}", result);
+        assert_eq!(*lines.lock().unwrap(), vec!["first\n", "second"]);
+        assert_eq!(result.stdout, "first\nsecond");
+    }
+
     #[test]
     fn test_filter_files() {
         let hook = Hook {
@@ -269,6 +1033,7 @@ mod tests {
             files: Some("\\.rs$".to_string()),
             pass_filenames: false,
             depends_on: vec![],
+            ..Default::default()
         };
 
         let files = vec![
@@ -276,7 +1041,7 @@ mod tests {
             PathBuf::from("test.txt"),
             PathBuf::from("main.rs"),
         ];
-        let filtered = ParallelExecutor::filter_files(&hook, &files);
+        let filtered = hook.filter_files(&files, &ExecutionOptions::default());
         assert_eq!(filtered.len(), 2);
     }
 }
@@ -1,83 +1,95 @@
-use pre_commit_core::{ExecutionResult, Executor, Hook, HookResult, Result};
-use regex::Regex;
-use std::path::PathBuf;
-use std::process::Command;
-use std::time::Instant;
+use pre_commit_core::command;
+use pre_commit_core::{
+    CancelFlag, ExecutionOptions, ExecutionResult, Executor, Hook, HookResult, Result,
+};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 /// Sequential executor that runs hooks one at a time
-pub struct SyncExecutor;
+pub struct SyncExecutor {
+    options: ExecutionOptions,
+}
 
 impl SyncExecutor {
     pub fn new() -> Self {
-        Self
-    }
-
-    /// Filter files based on the hook's file pattern
-    fn filter_files(hook: &Hook, files: &[PathBuf]) -> Vec<PathBuf> {
-        if let Some(pattern) = &hook.files {
-            if let Ok(regex) = Regex::new(pattern) {
-                return files
-                    .iter()
-                    .filter(|f| f.to_str().map(|s| regex.is_match(s)).unwrap_or(false))
-                    .cloned()
-                    .collect();
-            }
-        }
-        files.to_vec()
+        Self::with_options(ExecutionOptions::default())
     }
 
-    /// Execute a single hook
-    fn execute_hook(hook: &Hook, files: &[PathBuf]) -> HookResult {
-        let start = Instant::now();
+    pub fn with_options(options: ExecutionOptions) -> Self {
+        Self { options }
+    }
 
+    /// Execute a single hook
+    fn execute_hook(hook: &Hook, files: &[PathBuf], options: &ExecutionOptions) -> HookResult {
         // Filter files if needed
-        let filtered_files = Self::filter_files(hook, files);
+        let filtered_files = hook.filter_files(files, options);
 
-        // Build command
-        let mut parts =
-            shell_words::split(&hook.entry).unwrap_or_else(|_| vec![hook.entry.clone()]);
-
-        if hook.pass_filenames && !filtered_files.is_empty() {
-            for file in &filtered_files {
-                if let Some(s) = file.to_str() {
-                    parts.push(s.to_string());
-                }
-            }
+        if let Some(reason) = hook.file_count_skip_reason(filtered_files.len()) {
+            return HookResult::skipped(&hook.id, reason);
         }
 
+        // Long file lists run as several invocations, one per batch
+        let batches = command::file_batches(hook, &filtered_files, options);
+        let files_path =
+            match command::write_file_list(hook, &filtered_files, options, batches.len()) {
+                Ok(path) => path,
+                Err(e) => return HookResult::from_output(hook, Err(e), Duration::ZERO),
+            };
+        batches
+            .into_iter()
+            .map(|batch| Self::execute_batch(hook, batch, files_path.as_deref(), options))
+            .reduce(HookResult::merge)
+            .expect("there is always at least one batch")
+    }
+
+    /// Run a hook once on one batch of its files
+    fn execute_batch(
+        hook: &Hook,
+        files: &[PathBuf],
+        files_path: Option<&Path>,
+        options: &ExecutionOptions,
+    ) -> HookResult {
+        let start = Instant::now();
+
         // Execute command with color support
-        let result = if parts.is_empty() {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Empty command",
-            ))
-        } else {
-            Command::new(&parts[0])
-                .args(&parts[1..])
-                .env("FORCE_COLOR", "1")
-                .env("CLICOLOR_FORCE", "1")
-                .output()
-        };
-
-        let duration = start.elapsed();
-
-        match result {
-            Ok(output) => HookResult {
-                hook_id: hook.id.clone(),
-                success: output.status.success(),
-                exit_code: output.status.code(),
-                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-                duration_ms: duration.as_millis() as u64,
-            },
-            Err(e) => HookResult {
-                hook_id: hook.id.clone(),
-                success: false,
-                exit_code: None,
-                stdout: String::new(),
-                stderr: format!("Failed to execute command: {}", e),
-                duration_ms: duration.as_millis() as u64,
-            },
+        let result = command::hook_argv(hook, files, options)
+            .and_then(|parts| command::output(hook, &parts, files, files_path, options));
+
+        HookResult::from_output(hook, result, start.elapsed())
+    }
+}
+
+/// Cancel the run on Ctrl-C: the running hook finishes but no more start. A
+/// second Ctrl-C exits at once, as the flag is then already set. The handlers
+/// are removed when the returned guard is dropped.
+#[cfg(unix)]
+fn trap_interrupt(cancel: &CancelFlag) -> InterruptTrap {
+    use signal_hook::{consts::SIGINT, flag};
+
+    let ids = [
+        flag::register_conditional_shutdown(SIGINT, 130, cancel.as_atomic()),
+        flag::register(SIGINT, cancel.as_atomic()),
+    ];
+    InterruptTrap(ids.into_iter().filter_map(|id| id.ok()).collect())
+}
+
+#[cfg(not(unix))]
+fn trap_interrupt(_cancel: &CancelFlag) -> InterruptTrap {
+    InterruptTrap
+}
+
+/// Signal handlers installed by [`trap_interrupt`]
+#[cfg(unix)]
+struct InterruptTrap(Vec<signal_hook::SigId>);
+
+#[cfg(not(unix))]
+struct InterruptTrap;
+
+#[cfg(unix)]
+impl Drop for InterruptTrap {
+    fn drop(&mut self) {
+        for id in self.0.drain(..) {
+            signal_hook::low_level::unregister(id);
         }
     }
 }
@@ -92,10 +104,22 @@ impl Executor for SyncExecutor {
     fn execute(&self, hooks: &[Hook], files: &[PathBuf]) -> Result<ExecutionResult> {
         let start = Instant::now();
         let mut results = Vec::new();
+        let _trap = self
+            .options
+            .trap_ctrl_c
+            .then(|| trap_interrupt(&self.options.cancel));
 
         for hook in hooks {
-            let result = Self::execute_hook(hook, files);
+            if self.options.cancel.is_cancelled() {
+                results.push(HookResult::cancelled(hook));
+                continue;
+            }
+            let result = Self::execute_hook(hook, files, &self.options);
+            let failed = result.fails_under(self.options.fail_on);
             results.push(result);
+            if failed && self.options.fail_fast {
+                break;
+            }
         }
 
         let total_duration = start.elapsed();
@@ -105,71 +129,16 @@ impl Executor for SyncExecutor {
             hooks: results,
             total_duration_ms: total_duration.as_millis() as u64,
             all_passed,
+            levels: Vec::new(),
         })
     }
 }
 
-// Helper module for parsing shell commands
-mod shell_words {
-    pub fn split(input: &str) -> Result<Vec<String>, &'static str> {
-        let mut words = Vec::new();
-        let mut current = String::new();
-        let mut in_single_quote = false;
-        let mut in_double_quote = false;
-        let mut chars = input.chars().peekable();
-
-        while let Some(c) = chars.next() {
-            match c {
-                '\'' if !in_double_quote => {
-                    in_single_quote = !in_single_quote;
-                }
-                '"' if !in_single_quote => {
-                    in_double_quote = !in_double_quote;
-                }
-                ' ' | '\t' if !in_single_quote && !in_double_quote => {
-                    if !current.is_empty() {
-                        words.push(current.clone());
-                        current.clear();
-                    }
-                }
-                '\\' if !in_single_quote => {
-                    if let Some(next) = chars.next() {
-                        current.push(next);
-                    }
-                }
-                _ => {
-                    current.push(c);
-                }
-            }
-        }
-
-        if !current.is_empty() {
-            words.push(current);
-        }
-
-        Ok(words)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[test]
-    fn test_shell_words_split() {
-        assert_eq!(
-            shell_words::split("echo hello world").unwrap(),
-            vec!["echo", "hello", "world"]
-        );
-        assert_eq!(
-            shell_words::split("echo 'hello world'").unwrap(),
-            vec!["echo", "hello world"]
-        );
-        assert_eq!(
-            shell_words::split("echo \"hello world\"").unwrap(),
-            vec!["echo", "hello world"]
-        );
-    }
+    use pre_commit_core::Severity;
+    use std::fs;
 
     #[test]
     fn test_filter_files_no_pattern() {
@@ -181,10 +150,11 @@ mod tests {
             files: None,
             pass_filenames: false,
             depends_on: vec![],
+            ..Default::default()
         };
 
         let files = vec![PathBuf::from("test.rs"), PathBuf::from("test.txt")];
-        let filtered = SyncExecutor::filter_files(&hook, &files);
+        let filtered = hook.filter_files(&files, &ExecutionOptions::default());
         assert_eq!(filtered.len(), 2);
     }
 
@@ -198,6 +168,7 @@ mod tests {
             files: Some("\\.rs$".to_string()),
             pass_filenames: false,
             depends_on: vec![],
+            ..Default::default()
         };
 
         let files = vec![
@@ -205,12 +176,45 @@ mod tests {
             PathBuf::from("test.txt"),
             PathBuf::from("main.rs"),
         ];
-        let filtered = SyncExecutor::filter_files(&hook, &files);
+        let filtered = hook.filter_files(&files, &ExecutionOptions::default());
         assert_eq!(filtered.len(), 2);
         assert!(filtered.contains(&PathBuf::from("test.rs")));
         assert!(filtered.contains(&PathBuf::from("main.rs")));
     }
 
+    #[test]
+    fn test_fail_fast_stops_after_first_failure() {
+        let hook = |id: &str, entry: &str| Hook {
+            id: id.to_string(),
+            entry: entry.to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+        let hooks = vec![
+            hook("ok", "true"),
+            hook("bad", "false"),
+            hook("later", "true"),
+        ];
+
+        let executor = SyncExecutor::with_options(ExecutionOptions {
+            fail_fast: true,
+            ..Default::default()
+        });
+        let result = executor.execute(&hooks, &[]).unwrap();
+        let ids: Vec<_> = result.hooks.iter().map(|r| r.hook_id.as_str()).collect();
+        assert_eq!(ids, vec!["ok", "bad"]);
+        assert!(!result.all_passed);
+
+        let result = SyncExecutor::new().execute(&hooks, &[]).unwrap();
+        assert_eq!(result.hooks.len(), 3);
+
+        // A failure below `fail_on` doesn't stop the run
+        let mut hooks = hooks;
+        hooks[1].severity = Severity::Warning;
+        let result = executor.execute(&hooks, &[]).unwrap();
+        assert_eq!(result.hooks.len(), 3);
+    }
+
     #[test]
     fn test_execute_simple_hook() {
         let hook = Hook {
@@ -221,13 +225,458 @@ mod tests {
             files: None,
             pass_filenames: false,
             depends_on: vec![],
+            always_run: true,
+            ..Default::default()
         };
 
-        let result = SyncExecutor::execute_hook(&hook, &[]);
+        let result = SyncExecutor::execute_hook(&hook, &[], &ExecutionOptions::default());
         assert!(result.success);
         assert!(result.stdout.contains("hello"));
     }
 
+    #[test]
+    fn test_execute_hook_with_unparsable_entry_fails() {
+        let hook = Hook {
+            id: "broken".to_string(),
+            entry: "echo 'unterminated".to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let result = SyncExecutor::execute_hook(&hook, &[], &ExecutionOptions::default());
+        assert!(!result.success);
+        assert!(result
+            .stderr
+            .contains("invalid entry: unterminated ' quote"));
+    }
+
+    #[test]
+    fn test_entry_expands_environment_variables() {
+        // Cargo sets this for the test binary
+        let hook = Hook {
+            id: "expand".to_string(),
+            entry: "echo ${CARGO_MANIFEST_DIR} $$5".to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let result = SyncExecutor::execute_hook(&hook, &[], &ExecutionOptions::default());
+        assert!(result.success, "{}", result.stderr);
+        assert_eq!(
+            result.stdout,
+            format!("{} $5\n", env!("CARGO_MANIFEST_DIR"))
+        );
+    }
+
+    #[test]
+    fn test_entry_with_undefined_variable_fails() {
+        let hook = Hook {
+            id: "expand".to_string(),
+            entry: "${PRE_COMMIT_RS_UNDEFINED}/bin/tool".to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let result = SyncExecutor::execute_hook(&hook, &[], &ExecutionOptions::default());
+        assert!(!result.success);
+        assert!(
+            result.stderr.contains(
+                "invalid entry: environment variable 'PRE_COMMIT_RS_UNDEFINED' is not set"
+            ),
+            "{}",
+            result.stderr
+        );
+    }
+
+    #[test]
+    fn test_docker_hook_without_image_fails() {
+        let hook = Hook {
+            id: "containerized".to_string(),
+            entry: "cargo fmt".to_string(),
+            language: "docker".to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let result = SyncExecutor::execute_hook(&hook, &[], &ExecutionOptions::default());
+        assert!(!result.success);
+        assert!(result.stderr.contains("docker hooks need an `image`"));
+    }
+
+    #[test]
+    fn test_execute_hook_timeout() {
+        let hook = Hook {
+            id: "slow".to_string(),
+            entry: "sleep 5".to_string(),
+            timeout_secs: Some(1),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let result = SyncExecutor::execute_hook(&hook, &[], &ExecutionOptions::default());
+        assert!(!result.success);
+        assert_eq!(result.exit_code, None);
+        assert_eq!(result.stderr, "hook timed out after 1s");
+        assert!((1000..4000).contains(&result.duration_ms));
+    }
+
+    #[test]
+    fn test_execute_hook_in_repo_root() {
+        let root = std::env::temp_dir();
+        let hook = Hook {
+            id: "pwd".to_string(),
+            name: "Pwd".to_string(),
+            entry: "pwd".to_string(),
+            language: "system".to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+        let options = ExecutionOptions {
+            repo_root: Some(root.clone()),
+            ..Default::default()
+        };
+
+        let result = SyncExecutor::execute_hook(&hook, &[], &options);
+        assert!(result.success);
+        assert_eq!(
+            PathBuf::from(result.stdout.trim()).canonicalize().unwrap(),
+            root.canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_execute_hook_file_count_window() {
+        let hook = Hook {
+            id: "bounded".to_string(),
+            name: "Bounded".to_string(),
+            entry: "echo ran".to_string(),
+            language: "system".to_string(),
+            min_files: Some(2),
+            max_files: Some(3),
+            ..Default::default()
+        };
+        let files = |n: usize| -> Vec<PathBuf> {
+            (0..n).map(|i| PathBuf::from(format!("{}.rs", i))).collect()
+        };
+        let options = ExecutionOptions::default();
+
+        for (count, runs) in [(1, false), (2, true), (3, true), (4, false)] {
+            let result = SyncExecutor::execute_hook(&hook, &files(count), &options);
+            assert!(result.success);
+            assert_eq!(result.is_skipped(), !runs, "{} files", count);
+            assert_eq!(result.stdout.contains("ran"), runs, "{} files", count);
+        }
+    }
+
+    #[test]
+    fn test_hook_without_matching_files_is_skipped() {
+        let hook = Hook {
+            id: "rust".to_string(),
+            entry: "echo ran".to_string(),
+            files: Some(r"\.rs$".to_string()),
+            pass_filenames: true,
+            ..Default::default()
+        };
+        let files = [PathBuf::from("README.md")];
+        let options = ExecutionOptions::default();
+
+        let result = SyncExecutor::execute_hook(&hook, &files, &options);
+        assert!(result.success);
+        assert_eq!(result.skip_reason.as_deref(), Some("no files to check"));
+
+        // `always_run` runs it anyway, without filenames
+        let always_run = Hook {
+            always_run: true,
+            ..hook
+        };
+        let result = SyncExecutor::execute_hook(&always_run, &files, &options);
+        assert!(!result.is_skipped());
+        assert_eq!(result.stdout.trim(), "ran");
+    }
+
+    #[test]
+    fn test_global_exclude_hides_files_from_every_hook() {
+        let hook = |id: &str, files: Option<&str>, always_run: bool| Hook {
+            id: id.to_string(),
+            entry: "echo".to_string(),
+            files: files.map(str::to_string),
+            pass_filenames: true,
+            always_run,
+            ..Default::default()
+        };
+        let hooks = vec![
+            hook("all", None, false),
+            hook("vendored", Some("^vendor/"), false),
+            hook("always", Some("^vendor/"), true),
+        ];
+        let files = [PathBuf::from("src/lib.rs"), PathBuf::from("vendor/dep.rs")];
+
+        let executor = SyncExecutor::with_options(ExecutionOptions {
+            exclude: Some("^vendor/".to_string()),
+            ..Default::default()
+        });
+        let result = executor.execute(&hooks, &files).unwrap();
+
+        assert_eq!(result.hooks[0].stdout.trim(), "src/lib.rs");
+        // A hook's own `files` can't bring a globally excluded path back
+        assert_eq!(
+            result.hooks[1].skip_reason.as_deref(),
+            Some("no files to check")
+        );
+        // `always_run` still runs, just without the excluded file
+        assert!(!result.hooks[2].is_skipped());
+        assert_eq!(result.hooks[2].stdout.trim(), "");
+    }
+
+    #[test]
+    fn test_execute_hook_extra_args_before_filenames() {
+        let hook = Hook {
+            id: "echo".to_string(),
+            name: "Echo".to_string(),
+            entry: "echo base".to_string(),
+            language: "system".to_string(),
+            pass_filenames: true,
+            ..Default::default()
+        };
+        let mut options = ExecutionOptions::default();
+        options
+            .extra_args
+            .insert("echo".to_string(), vec!["--fix".to_string()]);
+
+        let result = SyncExecutor::execute_hook(&hook, &[PathBuf::from("a.rs")], &options);
+        assert_eq!(result.stdout.trim(), "base --fix a.rs");
+    }
+
+    #[test]
+    fn test_args_are_passed_verbatim() {
+        let hook = Hook {
+            id: "printf".to_string(),
+            entry: "printf [%s]".to_string(),
+            args: vec!["--config".to_string(), "a b.toml".to_string()],
+            pass_filenames: true,
+            ..Default::default()
+        };
+        let mut options = ExecutionOptions::default();
+        options
+            .extra_args
+            .insert("printf".to_string(), vec!["--fix".to_string()]);
+
+        let files = [PathBuf::from("my file.rs")];
+        let result = SyncExecutor::execute_hook(&hook, &files, &options);
+        assert_eq!(result.stdout, "[--config][a b.toml][--fix][my file.rs]");
+    }
+
+    #[test]
+    fn test_crlf_config_runs_without_carriage_returns_in_argv() {
+        let yaml = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: printf
+        name: Printf
+        entry: printf [%s]
+        language: system
+        args: [--check]
+        pass_filenames: true
+"#
+        .replace('\n', "\r\n");
+        let config = pre_commit_parser::parse_config(&yaml).unwrap();
+        let hooks = pre_commit_parser::resolve_hooks(&config).unwrap();
+
+        let files = [PathBuf::from("a.rs")];
+        let result = SyncExecutor::execute_hook(&hooks[0], &files, &ExecutionOptions::default());
+        assert!(result.success, "{:?}", result);
+        assert_eq!(result.stdout, "[--check][a.rs]");
+        assert!(!result.stdout.contains('\r'));
+    }
+
+    #[test]
+    fn test_hook_env_reaches_the_child() {
+        std::env::set_var("PRE_COMMIT_RS_TEST_PARENT", "parent");
+        let hook = Hook {
+            id: "env".to_string(),
+            entry: "sh -c 'env | grep PRE_COMMIT_RS_TEST_HOOK_ENV'".to_string(),
+            env: [(
+                "PRE_COMMIT_RS_TEST_HOOK_ENV".to_string(),
+                "${PRE_COMMIT_RS_TEST_PARENT}/child".to_string(),
+            )]
+            .into(),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let result = SyncExecutor::execute_hook(&hook, &[], &ExecutionOptions::default());
+        assert!(result.success, "{:?}", result);
+        assert_eq!(
+            result.stdout.trim(),
+            "PRE_COMMIT_RS_TEST_HOOK_ENV=parent/child"
+        );
+    }
+
+    #[test]
+    fn test_working_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let package = root.path().join("crates").join("foo");
+        fs::create_dir_all(&package).unwrap();
+        let options = ExecutionOptions {
+            repo_root: Some(root.path().to_path_buf()),
+            ..Default::default()
+        };
+
+        let pwd = Hook {
+            id: "pwd".to_string(),
+            entry: "pwd -P".to_string(),
+            working_dir: Some(PathBuf::from("crates/foo")),
+            always_run: true,
+            ..Default::default()
+        };
+        let result = SyncExecutor::execute_hook(&pwd, &[], &options);
+        assert!(result.success, "{:?}", result);
+        assert_eq!(
+            PathBuf::from(result.stdout.trim()),
+            package.canonicalize().unwrap()
+        );
+
+        // Files inside the working dir are passed relative to it, others absolute
+        let echo = Hook {
+            id: "echo".to_string(),
+            entry: "echo".to_string(),
+            pass_filenames: true,
+            ..pwd.clone()
+        };
+        let files = [
+            PathBuf::from("crates/foo/src/lib.rs"),
+            PathBuf::from("README.md"),
+        ];
+        let result = SyncExecutor::execute_hook(&echo, &files, &options);
+        let root = std::path::absolute(root.path()).unwrap();
+        assert_eq!(
+            result.stdout.trim(),
+            format!("src/lib.rs {}", root.join("README.md").display())
+        );
+    }
+
+    #[test]
+    fn test_missing_working_dir_fails_the_hook() {
+        let hook = Hook {
+            id: "pwd".to_string(),
+            entry: "pwd".to_string(),
+            working_dir: Some(PathBuf::from("does/not/exist")),
+            always_run: true,
+            ..Default::default()
+        };
+
+        let result = SyncExecutor::execute_hook(&hook, &[], &ExecutionOptions::default());
+        assert!(!result.success);
+        assert!(
+            result
+                .stderr
+                .contains("working_dir does/not/exist is not a directory"),
+            "{}",
+            result.stderr
+        );
+    }
+
+    #[test]
+    fn test_long_file_lists_run_in_batches() {
+        let hook = Hook {
+            id: "echo".to_string(),
+            entry: "echo batch".to_string(),
+            pass_filenames: true,
+            ..Default::default()
+        };
+        let files: Vec<PathBuf> = (0..2000)
+            .map(|i| PathBuf::from(format!("src/module_{:04}.rs", i)))
+            .collect();
+        let options = ExecutionOptions {
+            max_argv_bytes: Some(4096),
+            ..Default::default()
+        };
+
+        let result = SyncExecutor::execute_hook(&hook, &files, &options);
+        assert!(result.success);
+        let invocations: Vec<&str> = result.stdout.lines().collect();
+        assert!(invocations.len() > 1, "{} invocations", invocations.len());
+        assert!(invocations.iter().all(|line| line.len() < 4096));
+        let passed: Vec<&str> = invocations
+            .iter()
+            .flat_map(|line| line.split(' ').skip(1))
+            .collect();
+        assert_eq!(passed.len(), files.len());
+
+        let serial = Hook {
+            require_serial: true,
+            ..hook
+        };
+        let result = SyncExecutor::execute_hook(&serial, &files, &options);
+        assert_eq!(result.stdout.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_batches_see_the_whole_file_list() {
+        let hook = Hook {
+            id: "list".to_string(),
+            entry: "sh -c 'wc -l < \"$PRE_COMMIT_FILES_PATH\"' sh".to_string(),
+            pass_filenames: true,
+            ..Default::default()
+        };
+        let files: Vec<PathBuf> = ["a", "b", "c"].iter().map(PathBuf::from).collect();
+        let options = ExecutionOptions {
+            max_argv_bytes: Some(1),
+            ..Default::default()
+        };
+
+        let result = SyncExecutor::execute_hook(&hook, &files, &options);
+        assert!(result.success, "{}", result.stderr);
+        let counts: Vec<&str> = result.stdout.lines().map(str::trim).collect();
+        assert_eq!(counts, vec!["3", "3", "3"]);
+    }
+
+    #[test]
+    fn test_failing_batch_fails_the_hook() {
+        // Only the batch containing `bad` fails
+        let hook = Hook {
+            id: "grep".to_string(),
+            entry: "sh -c 'for f; do [ \"$f\" != bad ] || exit 3; done' sh".to_string(),
+            pass_filenames: true,
+            ..Default::default()
+        };
+        let files: Vec<PathBuf> = ["a", "b", "bad", "c"].iter().map(PathBuf::from).collect();
+        let options = ExecutionOptions {
+            max_argv_bytes: Some(1),
+            ..Default::default()
+        };
+
+        let result = SyncExecutor::execute_hook(&hook, &files, &options);
+        assert!(!result.success);
+        assert_eq!(result.exit_code, Some(3));
+    }
+
+    #[test]
+    fn test_fail_on_stderr() {
+        let hook = Hook {
+            id: "warns".to_string(),
+            name: "Warns".to_string(),
+            entry: "sh -c 'echo problem >&2'".to_string(),
+            language: "system".to_string(),
+            always_run: true,
+            ..Default::default()
+        };
+        let options = ExecutionOptions::default();
+
+        let lenient = SyncExecutor::execute_hook(&hook, &[], &options);
+        assert!(lenient.success);
+
+        let strict = Hook {
+            fail_on_stderr: true,
+            ..hook
+        };
+        let result = SyncExecutor::execute_hook(&strict, &[], &options);
+        assert!(!result.success);
+        assert_eq!(result.exit_code, Some(0));
+        assert!(result.stderr.contains("problem"));
+    }
+
     #[test]
     fn test_executor_multiple_hooks() {
         let hooks = vec![
@@ -239,6 +688,8 @@ mod tests {
                 files: None,
                 pass_filenames: false,
                 depends_on: vec![],
+                always_run: true,
+                ..Default::default()
             },
             Hook {
                 id: "hook2".to_string(),
@@ -248,6 +699,8 @@ mod tests {
                 files: None,
                 pass_filenames: false,
                 depends_on: vec![],
+                always_run: true,
+                ..Default::default()
             },
         ];
 
@@ -260,6 +713,31 @@ mod tests {
         assert!(result.hooks[1].stdout.contains("second"));
     }
 
+    #[test]
+    fn test_cancelled_run_starts_no_hooks() {
+        let dir = tempfile::tempdir().unwrap();
+        let hook = |id: &str| Hook {
+            id: id.to_string(),
+            entry: format!("touch {}", id),
+            always_run: true,
+            ..Default::default()
+        };
+        let options = ExecutionOptions {
+            repo_root: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        options.cancel.cancel();
+
+        let executor = SyncExecutor::with_options(options);
+        let result = executor.execute(&[hook("a"), hook("b")], &[]).unwrap();
+
+        assert!(!result.all_passed);
+        for hook_result in &result.hooks {
+            assert_eq!(hook_result.skip_reason.as_deref(), Some("run cancelled"));
+            assert!(!dir.path().join(&hook_result.hook_id).exists());
+        }
+    }
+
     #[test]
     fn test_executor_failing_hook() {
         let hooks = vec![Hook {
@@ -270,6 +748,8 @@ mod tests {
             files: None,
             pass_filenames: false,
             depends_on: vec![],
+            always_run: true,
+            ..Default::default()
         }];
 
         let executor = SyncExecutor::new();
@@ -0,0 +1,455 @@
+use pre_commit_core::{PreCommitError, Result};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// A `git` command run from `repo_root` (the current directory when `None`)
+pub fn git_command(git: &Path, repo_root: Option<&Path>) -> Command {
+    let mut command = Command::new(git);
+    if let Some(root) = repo_root {
+        command.current_dir(root);
+    }
+    command
+}
+
+/// Check that the configured git executable can actually be run; `git` from
+/// `PATH` when none is configured
+pub fn resolve_git_binary(git_binary: Option<PathBuf>) -> Result<PathBuf> {
+    let Some(git) = git_binary else {
+        return Ok(PathBuf::from("git"));
+    };
+
+    let status = Command::new(&git)
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+
+    match status {
+        Ok(status) if status.success() => Ok(git),
+        Ok(_) => Err(PreCommitError::Execution(format!(
+            "Git binary failed to run: {}",
+            git.display()
+        ))),
+        Err(e) => Err(PreCommitError::Execution(format!(
+            "Git binary is not executable: {} ({})",
+            git.display(),
+            e
+        ))),
+    }
+}
+
+/// Resolve the repository root, preferring an explicit override. `None`
+/// outside a git checkout (or without git installed).
+pub fn resolve_repo_root(repo_root: Option<PathBuf>, git: &Path) -> Result<Option<PathBuf>> {
+    if let Some(root) = repo_root {
+        if !root.is_dir() {
+            return Err(PreCommitError::Execution(format!(
+                "Repository root is not a directory: {}",
+                root.display()
+            )));
+        }
+        return Ok(Some(root));
+    }
+
+    let output = Command::new(git)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+
+    let Some(output) = output else {
+        return Ok(None);
+    };
+
+    let root = String::from_utf8(output.stdout).map_err(|e| {
+        PreCommitError::Execution(format!("git printed a non-UTF-8 repository root: {}", e))
+    })?;
+    Ok(Some(PathBuf::from(root.trim())))
+}
+
+/// Every file git tracks (`git ls-files`), relative to the repository root
+pub fn tracked_files(git: &Path, repo_root: Option<&Path>) -> Result<Vec<PathBuf>> {
+    list_files(git, repo_root, &["ls-files"], "tracked")
+}
+
+/// Files git tracks under `dir` (`git ls-files -- <dir>`), relative to the
+/// repository root
+pub fn tracked_files_under(
+    git: &Path,
+    repo_root: Option<&Path>,
+    dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let dir = dir.to_string_lossy();
+    list_files(git, repo_root, &["ls-files", "--", &dir], "tracked")
+}
+
+/// Files added, copied or modified in the index, relative to the repository
+/// root; deleted files are left out since there is nothing to check
+pub fn staged_files(git: &Path, repo_root: Option<&Path>) -> Result<Vec<PathBuf>> {
+    list_files(
+        git,
+        repo_root,
+        &["diff", "--cached", "--name-only", "--diff-filter=ACM"],
+        "staged",
+    )
+}
+
+/// Files added, copied or modified between two commits (`from..to`), relative
+/// to the repository root
+pub fn changed_files(
+    git: &Path,
+    repo_root: Option<&Path>,
+    from_ref: &str,
+    to_ref: &str,
+) -> Result<Vec<PathBuf>> {
+    let range = format!("{}..{}", from_ref, to_ref);
+    list_files(
+        git,
+        repo_root,
+        &["diff", "--name-only", "--diff-filter=ACM", &range, "--"],
+        "changed",
+    )
+}
+
+/// One ref update git is about to push, as written to a `pre-push` hook's
+/// stdin: `<local ref> <local sha> <remote ref> <remote sha>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushRef {
+    pub local_ref: String,
+    pub local_sha: String,
+    pub remote_ref: String,
+    pub remote_sha: String,
+}
+
+impl PushRef {
+    /// The push deletes the remote ref
+    fn is_deletion(&self) -> bool {
+        is_zero_sha(&self.local_sha)
+    }
+
+    /// The push creates the remote ref, so there is nothing to diff against
+    fn is_new_ref(&self) -> bool {
+        is_zero_sha(&self.remote_sha)
+    }
+}
+
+/// git's all-zeros object id, which stands for a ref that doesn't exist
+fn is_zero_sha(sha: &str) -> bool {
+    sha.bytes().all(|b| b == b'0')
+}
+
+/// Parse the lines git writes to a `pre-push` hook's stdin, ignoring blank ones
+pub fn parse_push_refs(input: &str) -> Result<Vec<PushRef>> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [local_ref, local_sha, remote_ref, remote_sha] => Ok(PushRef {
+                local_ref: local_ref.to_string(),
+                local_sha: local_sha.to_string(),
+                remote_ref: remote_ref.to_string(),
+                remote_sha: remote_sha.to_string(),
+            }),
+            _ => Err(PreCommitError::Parse(format!(
+                "Expected `<local ref> <local sha> <remote ref> <remote sha>` on pre-push stdin, got: {}",
+                line
+            ))),
+        })
+        .collect()
+}
+
+/// Files the pushed commits add, copy or modify, sorted and relative to the
+/// repository root: for each ref, those changed from the remote sha to the
+/// local one. A new ref has no remote sha, so every file in its commit counts;
+/// a deleted ref counts none. When the remote sha isn't in the local repository
+/// (the remote has commits that weren't fetched), the files changed by the
+/// commits no remote-tracking ref contains count instead.
+pub fn pushed_files(
+    git: &Path,
+    repo_root: Option<&Path>,
+    refs: &[PushRef],
+) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for push in refs {
+        if push.is_deletion() {
+            continue;
+        }
+        files.extend(if push.is_new_ref() {
+            tree_files(git, repo_root, &push.local_sha)?
+        } else if !has_commit(git, repo_root, &push.remote_sha)? {
+            unpushed_files(git, repo_root, &push.local_sha)?
+        } else {
+            changed_files(git, repo_root, &push.remote_sha, &push.local_sha)?
+        });
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Every file in `commit`'s tree
+fn tree_files(git: &Path, repo_root: Option<&Path>, commit: &str) -> Result<Vec<PathBuf>> {
+    list_files(
+        git,
+        repo_root,
+        &["ls-tree", "-r", "--name-only", commit],
+        "pushed",
+    )
+}
+
+/// Whether `sha` names a commit in the local repository
+fn has_commit(git: &Path, repo_root: Option<&Path>, sha: &str) -> Result<bool> {
+    let status = git_command(git, repo_root)
+        .args(["cat-file", "-e", &format!("{}^{{commit}}", sha)])
+        .output()?
+        .status;
+    Ok(status.success())
+}
+
+/// Files that the commits reachable from `local_sha` but from no
+/// remote-tracking ref add, copy or modify, and that still exist in it
+fn unpushed_files(git: &Path, repo_root: Option<&Path>, local_sha: &str) -> Result<Vec<PathBuf>> {
+    let tree: HashSet<PathBuf> = tree_files(git, repo_root, local_sha)?.into_iter().collect();
+    let changed = list_files(
+        git,
+        repo_root,
+        &[
+            "log",
+            "--name-only",
+            "--no-renames",
+            "--diff-filter=ACM",
+            "--format=",
+            local_sha,
+            "--not",
+            "--remotes",
+        ],
+        "pushed",
+    )?;
+    Ok(changed
+        .into_iter()
+        .filter(|file| tree.contains(file))
+        .collect())
+}
+
+fn list_files(
+    git: &Path,
+    repo_root: Option<&Path>,
+    args: &[&str],
+    kind: &str,
+) -> Result<Vec<PathBuf>> {
+    let output = git_command(git, repo_root).args(args).output()?;
+    if !output.status.success() {
+        return Err(PreCommitError::Execution(format!(
+            "Failed to get {} files from git: {}",
+            kind,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let stdout = String::from_utf8(output.stdout).map_err(|e| {
+        PreCommitError::Execution(format!("git listed a non-UTF-8 file name: {}", e))
+    })?;
+    Ok(stdout.lines().map(PathBuf::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn git(root: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(root)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_tracked_and_staged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "-q"]);
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-qm", "init"]);
+
+        fs::write(root.join("README.md"), "changed").unwrap();
+        fs::write(root.join("untracked.txt"), "").unwrap();
+        git(root, &["add", "README.md"]);
+
+        let git_binary = Path::new("git");
+        assert_eq!(
+            tracked_files(git_binary, Some(root)).unwrap(),
+            vec![PathBuf::from("README.md"), PathBuf::from("src/lib.rs")]
+        );
+        assert_eq!(
+            staged_files(git_binary, Some(root)).unwrap(),
+            vec![PathBuf::from("README.md")]
+        );
+    }
+
+    #[test]
+    fn test_changed_files_between_refs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "-q"]);
+        fs::write(root.join("a.txt"), "").unwrap();
+        fs::write(root.join("b.txt"), "").unwrap();
+        fs::write(root.join("gone.txt"), "").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-qm", "first"]);
+        fs::write(root.join("b.txt"), "changed").unwrap();
+        fs::remove_file(root.join("gone.txt")).unwrap();
+        git(root, &["add", "-A"]);
+        git(root, &["commit", "-qm", "second"]);
+
+        let git_binary = Path::new("git");
+        assert_eq!(
+            changed_files(git_binary, Some(root), "HEAD~1", "HEAD").unwrap(),
+            vec![PathBuf::from("b.txt")]
+        );
+
+        let err = changed_files(git_binary, Some(root), "nope", "HEAD").unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains("Failed to get changed files from git"),
+            "{}",
+            message
+        );
+        assert!(message.contains("nope"), "{}", message);
+    }
+
+    #[test]
+    fn test_parse_push_refs() {
+        let zero = "0".repeat(40);
+        let input = format!(
+            "refs/heads/main abc123 refs/heads/main def456\n\n(delete) {} refs/heads/old 789abc\n",
+            zero
+        );
+        let refs = parse_push_refs(&input).unwrap();
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].local_sha, "abc123");
+        assert_eq!(refs[0].remote_ref, "refs/heads/main");
+        assert!(refs[1].is_deletion());
+        assert!(!refs[1].is_new_ref());
+
+        let err = parse_push_refs("refs/heads/main abc123").unwrap_err();
+        assert!(
+            err.to_string().contains("refs/heads/main abc123"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_pushed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "-q"]);
+        fs::write(root.join("a.txt"), "").unwrap();
+        fs::write(root.join("b.txt"), "").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-qm", "first"]);
+        fs::write(root.join("b.txt"), "changed").unwrap();
+        fs::write(root.join("c.txt"), "").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-qm", "second"]);
+
+        let sha = |rev: &str| {
+            let output = git_command(Path::new("git"), Some(root))
+                .args(["rev-parse", rev])
+                .output()
+                .unwrap();
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+        let push = |local_sha: String, remote_sha: String| PushRef {
+            local_ref: "refs/heads/main".to_string(),
+            local_sha,
+            remote_ref: "refs/heads/main".to_string(),
+            remote_sha,
+        };
+        let zero = "0".repeat(40);
+        let files = |refs: &[PushRef]| pushed_files(Path::new("git"), Some(root), refs).unwrap();
+
+        // An update checks what changed since the remote's commit
+        let update = push(sha("HEAD"), sha("HEAD~1"));
+        assert_eq!(
+            files(std::slice::from_ref(&update)),
+            vec![PathBuf::from("b.txt"), PathBuf::from("c.txt")]
+        );
+
+        // A new branch checks everything in the pushed commit
+        let new_branch = push(sha("HEAD~1"), zero.clone());
+        assert_eq!(
+            files(std::slice::from_ref(&new_branch)),
+            vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")]
+        );
+
+        // A deletion checks nothing; several refs are merged
+        let deletion = push(zero, sha("HEAD"));
+        assert!(files(std::slice::from_ref(&deletion)).is_empty());
+        assert_eq!(
+            files(&[update, new_branch, deletion]),
+            vec![
+                PathBuf::from("a.txt"),
+                PathBuf::from("b.txt"),
+                PathBuf::from("c.txt")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pushed_files_with_unknown_remote_sha() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "-q"]);
+        fs::write(root.join("a.txt"), "").unwrap();
+        fs::write(root.join("gone.txt"), "").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-qm", "first"]);
+        fs::write(root.join("b.txt"), "").unwrap();
+        fs::remove_file(root.join("gone.txt")).unwrap();
+        git(root, &["add", "-A"]);
+        git(root, &["commit", "-qm", "second"]);
+
+        let head = git_command(Path::new("git"), Some(root))
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .unwrap();
+        // The remote moved on to a commit this clone never fetched
+        let push = PushRef {
+            local_ref: "refs/heads/main".to_string(),
+            local_sha: String::from_utf8(head.stdout).unwrap().trim().to_string(),
+            remote_ref: "refs/heads/main".to_string(),
+            remote_sha: "1234567890abcdef1234567890abcdef12345678".to_string(),
+        };
+        let files = || pushed_files(Path::new("git"), Some(root), std::slice::from_ref(&push));
+
+        // Without remote-tracking refs every commit is unpushed; files
+        // deleted since are left out
+        assert_eq!(
+            files().unwrap(),
+            vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")]
+        );
+
+        // Otherwise only the commits no remote-tracking ref has
+        git(root, &["update-ref", "refs/remotes/origin/main", "HEAD~1"]);
+        assert_eq!(files().unwrap(), vec![PathBuf::from("b.txt")]);
+    }
+
+    #[test]
+    fn test_outside_a_repository_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = tracked_files(Path::new("git"), Some(dir.path())).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Failed to get tracked files from git"));
+    }
+}
@@ -0,0 +1,198 @@
+use globset::{Glob, GlobSetBuilder};
+use ignore::WalkBuilder;
+use pre_commit_core::{PreCommitError, Result};
+use std::path::{Path, PathBuf};
+
+mod git;
+
+pub use git::{
+    changed_files, git_command, parse_push_refs, pushed_files, resolve_git_binary,
+    resolve_repo_root, staged_files, tracked_files, tracked_files_under, PushRef,
+};
+
+/// Discover files under `root` matching any of `patterns`.
+///
+/// This does not need git: the tree is walked directly, honoring `.gitignore`
+/// files when they exist. Returned paths are relative to `root` and sorted.
+pub fn glob_files(root: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| PreCommitError::Parse(format!("Invalid glob '{}': {}", pattern, e)))?;
+        builder.add(glob);
+    }
+    let globs = builder
+        .build()
+        .map_err(|e| PreCommitError::Parse(format!("Invalid globs: {}", e)))?;
+
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(|e| PreCommitError::Execution(e.to_string()))?;
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        if globs.is_match(relative) {
+            files.push(relative.to_path_buf());
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Replace each directory in `paths` (relative to `repo_root`, like the files
+/// hooks receive) with the files git tracks under it, recursively.
+///
+/// Expanded files are relative to the repository root, so untracked and
+/// ignored files never reach a hook; other paths are kept as given. Hooks
+/// receive files, never directories.
+pub fn expand_dirs(
+    git: &Path,
+    repo_root: Option<&Path>,
+    paths: &[PathBuf],
+) -> Result<Vec<PathBuf>> {
+    let mut expanded = Vec::new();
+
+    for path in paths {
+        let in_root = repo_root.map_or_else(|| path.clone(), |root| root.join(path));
+        if in_root.is_dir() {
+            expanded.extend(tracked_files_under(git, repo_root, path)?);
+        } else {
+            expanded.push(path.clone());
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Find every file named `file_name` under `root`, e.g. each subproject's
+/// `.pre-commit-config.yaml` in a monorepo.
+///
+/// Ignored directories are skipped like in [`glob_files`]. Returned paths
+/// include `root` and are sorted, so parent projects come before nested ones.
+pub fn find_configs(root: &Path, file_name: &str) -> Result<Vec<PathBuf>> {
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    let mut configs = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(|e| PreCommitError::Execution(e.to_string()))?;
+        if entry.file_type().is_some_and(|t| t.is_file()) && entry.file_name() == file_name {
+            configs.push(entry.into_path());
+        }
+    }
+
+    configs.sort();
+    Ok(configs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_glob_files_without_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("nested")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("a.rs"), "").unwrap();
+        fs::write(root.join("b.txt"), "").unwrap();
+        fs::write(root.join("nested/d.rs"), "").unwrap();
+        fs::write(root.join("target/c.rs"), "").unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+
+        let files = glob_files(root, &["*.rs".to_string()]).unwrap();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("a.rs"), PathBuf::from("nested/d.rs")]
+        );
+
+        let files = glob_files(root, &["*.txt".to_string(), ".gitignore".to_string()]).unwrap();
+        assert_eq!(
+            files,
+            vec![PathBuf::from(".gitignore"), PathBuf::from("b.txt")]
+        );
+    }
+
+    #[test]
+    fn test_expand_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        fs::write(root.join("src/nested/mod.rs"), "").unwrap();
+        fs::write(root.join("src/.gitignore"), "target/\n").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+
+        // Neither untracked nor ignored files are listed
+        fs::create_dir_all(root.join("src/target")).unwrap();
+        fs::write(root.join("src/target/out.rs"), "").unwrap();
+        fs::write(root.join("src/untracked.rs"), "").unwrap();
+
+        let paths = [PathBuf::from("README.md"), PathBuf::from("src")];
+        let files = expand_dirs(Path::new("git"), Some(root), &paths).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("README.md"),
+                PathBuf::from("src/.gitignore"),
+                PathBuf::from("src/lib.rs"),
+                PathBuf::from("src/nested/mod.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_configs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let name = ".pre-commit-config.yaml";
+        fs::create_dir_all(root.join("api")).unwrap();
+        fs::create_dir_all(root.join("web/app")).unwrap();
+        fs::create_dir_all(root.join("vendor")).unwrap();
+        fs::write(root.join(name), "").unwrap();
+        fs::write(root.join("api").join(name), "").unwrap();
+        fs::write(root.join("web/app").join(name), "").unwrap();
+        fs::write(root.join("vendor").join(name), "").unwrap();
+        fs::write(root.join(".gitignore"), "vendor/\n").unwrap();
+
+        let configs = find_configs(root, name).unwrap();
+        assert_eq!(
+            configs,
+            vec![
+                root.join(name),
+                root.join("api").join(name),
+                root.join("web/app").join(name),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_glob_files_invalid_pattern() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(glob_files(dir.path(), &["a[".to_string()]).is_err());
+    }
+}
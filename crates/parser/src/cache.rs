@@ -0,0 +1,77 @@
+use crate::parse_config_file;
+use pre_commit_core::{Config, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Parsed configs keyed by path, reparsed only when the file's mtime changes
+#[derive(Debug, Default)]
+pub struct ConfigCache {
+    entries: HashMap<PathBuf, (Option<SystemTime>, Config)>,
+}
+
+impl ConfigCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the config at `path`, parsing it only if it is new or has been modified.
+    /// Files whose mtime can't be read are reparsed every time.
+    pub fn get<P: AsRef<Path>>(&mut self, path: P) -> Result<&Config> {
+        let path = path.as_ref();
+        let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok();
+
+        let fresh = modified.is_some()
+            && self
+                .entries
+                .get(path)
+                .is_some_and(|(cached, _)| *cached == modified);
+
+        if !fresh {
+            let config = parse_config_file(path)?;
+            self.entries.insert(path.to_path_buf(), (modified, config));
+        }
+
+        Ok(&self.entries[path].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::time::Duration;
+
+    const CONFIG: &str = "repos:\n  - repo: local\n    hooks:\n      - id: first\n        name: First\n        entry: echo\n        language: system\n";
+
+    #[test]
+    fn test_unchanged_file_is_not_reparsed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".pre-commit-config.yaml");
+        fs::write(&path, CONFIG).unwrap();
+        let original_mtime = fs::metadata(&path).unwrap().modified().unwrap();
+
+        let mut cache = ConfigCache::new();
+        assert_eq!(cache.get(&path).unwrap().repos[0].hooks[0].id, "first");
+
+        // Rewrite the contents but keep the mtime: the cached config is served
+        fs::write(&path, CONFIG.replace("first", "second")).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(original_mtime)
+            .unwrap();
+        assert_eq!(cache.get(&path).unwrap().repos[0].hooks[0].id, "first");
+
+        // Bumping the mtime invalidates the entry
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(original_mtime + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(cache.get(&path).unwrap().repos[0].hooks[0].id, "second");
+    }
+}
@@ -0,0 +1,193 @@
+use pre_commit_core::{Config, Hook, HookTemplate, Repo};
+use schemars::JsonSchema;
+use serde_yaml::{Mapping, Value};
+use std::fmt;
+
+/// A config key that the struct it appears in has no field for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+    /// The key as written
+    pub field: String,
+    /// Where the key appeared, e.g. `hook 'lint'`
+    pub location: String,
+    /// The known field the key most likely misspells
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for UnknownField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown field `{}` in {}", self.field, self.location)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "; did you mean `{}`?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collect the keys of a config value tree that `Config`, `HookTemplate`,
+/// `Repo` or `Hook` don't accept, in document order. With `remove`, they are also dropped from
+/// the tree so it deserializes despite `deny_unknown_fields`.
+pub(crate) fn unknown_fields(value: &mut Value, remove: bool) -> Vec<UnknownField> {
+    let mut found = Vec::new();
+    let Some(config) = value.as_mapping_mut() else {
+        return found;
+    };
+    check(
+        config,
+        &field_names::<Config>(),
+        "the config",
+        remove,
+        &mut found,
+    );
+
+    let template_fields = field_names::<HookTemplate>();
+    let templates = config.get_mut("templates").and_then(Value::as_mapping_mut);
+    for (name, template) in templates.into_iter().flatten() {
+        let Some(template) = template.as_mapping_mut() else {
+            continue;
+        };
+        let location = match name.as_str() {
+            Some(name) => format!("template '{}'", name),
+            None => "a template".to_string(),
+        };
+        check(template, &template_fields, &location, remove, &mut found);
+    }
+
+    let repo_fields = field_names::<Repo>();
+    let hook_fields = field_names::<Hook>();
+    let repos = config.get_mut("repos").and_then(Value::as_sequence_mut);
+    for (index, repo) in repos.into_iter().flatten().enumerate() {
+        let Some(repo) = repo.as_mapping_mut() else {
+            continue;
+        };
+        let location = match repo.get("repo").and_then(Value::as_str) {
+            Some(name) => format!("repo '{}'", name),
+            None => format!("repo #{}", index + 1),
+        };
+        check(repo, &repo_fields, &location, remove, &mut found);
+
+        let hooks = repo.get_mut("hooks").and_then(Value::as_sequence_mut);
+        for (index, hook) in hooks.into_iter().flatten().enumerate() {
+            let Some(hook) = hook.as_mapping_mut() else {
+                continue;
+            };
+            let location = match hook.get("id").and_then(Value::as_str) {
+                Some(id) => format!("hook '{}'", id),
+                None => format!("hook #{} of {}", index + 1, location),
+            };
+            check(hook, &hook_fields, &location, remove, &mut found);
+        }
+    }
+    found
+}
+
+fn check(
+    mapping: &mut Mapping,
+    known: &[String],
+    location: &str,
+    remove: bool,
+    found: &mut Vec<UnknownField>,
+) {
+    let unknown: Vec<Value> = mapping
+        .keys()
+        .filter(|key| {
+            !key.as_str()
+                .is_some_and(|key| known.iter().any(|k| k == key))
+        })
+        .cloned()
+        .collect();
+
+    // A field that is already set is unlikely to be the intended spelling
+    let unset: Vec<String> = known
+        .iter()
+        .filter(|field| !mapping.contains_key(field.as_str()))
+        .cloned()
+        .collect();
+
+    for key in unknown {
+        let field = match &key {
+            Value::String(name) => name.clone(),
+            other => serde_yaml::to_string(other)
+                .map(|s| s.trim_end().to_string())
+                .unwrap_or_default(),
+        };
+        found.push(UnknownField {
+            suggestion: closest(&field, &unset),
+            field,
+            location: location.to_string(),
+        });
+        if remove {
+            mapping.remove(&key);
+        }
+    }
+}
+
+/// Field names of a config struct, taken from its JSON schema so they track the
+/// serde attributes
+fn field_names<T: JsonSchema>() -> Vec<String> {
+    schemars::schema_for!(T)
+        .get("properties")
+        .and_then(|properties| properties.as_object())
+        .map(|properties| properties.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// The known field within two edits of `field`, if any
+fn closest(field: &str, known: &[String]) -> Option<String> {
+    known
+        .iter()
+        .map(|candidate| (edit_distance(field, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.clone())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("file", "files"), 1);
+        assert_eq!(edit_distance("depend_on", "depends_on"), 1);
+        assert_eq!(edit_distance("entyr", "entry"), 2);
+        assert_eq!(edit_distance("", "id"), 2);
+    }
+
+    #[test]
+    fn test_unknown_fields_are_located_and_removed() {
+        let mut value: Value = serde_yaml::from_str(
+            "templates:\n  rust:\n    fils: x\nrepos:\n  - repo: local\n    rev: v1\n    hooks:\n      \
+             - id: lint\n        file: x\n        bogus: 1\nstop_early: true\n",
+        )
+        .unwrap();
+
+        let found = unknown_fields(&mut value, true);
+        let messages: Vec<String> = found.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "unknown field `stop_early` in the config",
+                "unknown field `fils` in template 'rust'; did you mean `files`?",
+                "unknown field `rev` in repo 'local'",
+                "unknown field `file` in hook 'lint'; did you mean `files`?",
+                "unknown field `bogus` in hook 'lint'",
+            ]
+        );
+        assert!(unknown_fields(&mut value, false).is_empty());
+    }
+}
@@ -1,29 +1,602 @@
-use pre_commit_core::{Config, Hook, PreCommitError, Result};
+mod cache;
+mod fields;
+
+pub use cache::ConfigCache;
+pub use fields::UnknownField;
+use pre_commit_core::{
+    tags, Config, Hook, HookResult, HookTemplate, PreCommitError, Result, DEFAULT_STAGE, STAGES,
+};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Config file names looked for by [`find_config`], in order of preference
+pub const CONFIG_FILE_NAMES: [&str; 2] = [".pre-commit-config.yaml", ".pre-commit-config.yml"];
+
+/// Locate the config for a run started in `start`: the first of
+/// [`CONFIG_FILE_NAMES`] found in `start` or its nearest parent. The search
+/// stops at the git root (a directory containing `.git`) or the filesystem root.
+pub fn find_config(start: &Path) -> Result<PathBuf> {
+    for dir in start.ancestors() {
+        let found = CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file());
+        if let Some(path) = found {
+            return Ok(path);
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    Err(PreCommitError::NoConfigFound(start.to_path_buf()))
+}
 
-/// Parse a pre-commit configuration from a file
+/// Parse a pre-commit configuration from a file, as YAML (`.yaml`/`.yml`) or
+/// TOML (`.toml`) depending on its extension
 pub fn parse_config_file<P: AsRef<Path>>(path: P) -> Result<Config> {
-    let content = fs::read_to_string(path)?;
-    parse_config(&content)
+    read_config_file(path.as_ref(), false).map(|(config, _)| config)
 }
 
-/// Parse a pre-commit configuration from a string
+/// Like [`parse_config_file`], but keys no config struct accepts are dropped
+/// instead of rejected. They are returned so callers can warn about them.
+pub fn parse_config_file_lenient<P: AsRef<Path>>(path: P) -> Result<(Config, Vec<UnknownField>)> {
+    read_config_file(path.as_ref(), true)
+}
+
+fn read_config_file(
+    path: &Path,
+    allow_unknown_fields: bool,
+) -> Result<(Config, Vec<UnknownField>)> {
+    let format = ConfigFormat::from_path(path)?;
+    let content = fs::read_to_string(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => PreCommitError::ConfigNotFound(path.to_path_buf()),
+        _ => PreCommitError::Io(e),
+    })?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    parse_config_in(&content, format, base_dir, allow_unknown_fields)
+}
+
+/// Parse a pre-commit configuration from a YAML string.
+/// Hook manifests are resolved relative to the current directory.
 pub fn parse_config(content: &str) -> Result<Config> {
-    serde_yaml::from_str(content)
-        .map_err(|e| PreCommitError::Parse(format!("Failed to parse YAML: {}", e)))
+    parse_config_in(content, ConfigFormat::Yaml, Path::new("."), false).map(|(config, _)| config)
+}
+
+/// Parse a pre-commit configuration from a TOML string, with the same layout
+/// as the YAML format (`[[repos]]` tables holding `[[repos.hooks]]`)
+pub fn parse_config_toml(content: &str) -> Result<Config> {
+    parse_config_in(content, ConfigFormat::Toml, Path::new("."), false).map(|(config, _)| config)
+}
+
+/// Syntax of a config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigFormat {
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            Some("toml") => Ok(Self::Toml),
+            _ => Err(PreCommitError::Parse(format!(
+                "Unsupported config file {}: expected a .yaml, .yml or .toml extension",
+                path.display()
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Yaml => "YAML",
+            Self::Toml => "TOML",
+        }
+    }
+}
+
+fn parse_config_in(
+    content: &str,
+    format: ConfigFormat,
+    base_dir: &Path,
+    allow_unknown_fields: bool,
+) -> Result<(Config, Vec<UnknownField>)> {
+    // Files edited on Windows may carry CRLF line endings
+    let content = content.replace("\r\n", "\n");
+    // TOML is converted to the YAML value tree so both formats share manifest
+    // inlining and deserialization
+    let parse_error = |e: &dyn std::fmt::Display| {
+        PreCommitError::Parse(format!("Failed to parse {}: {}", format.name(), e))
+    };
+    let mut value: Value = match format {
+        ConfigFormat::Yaml => serde_yaml::from_str(&content).map_err(|e| parse_error(&e))?,
+        ConfigFormat::Toml => toml::from_str::<toml::Value>(&content)
+            .map_err(|e| parse_error(&e))
+            .and_then(|toml| serde_yaml::to_value(toml).map_err(|e| parse_error(&e)))?,
+    };
+    inline_manifests(&mut value, base_dir)?;
+
+    let dropped = if allow_unknown_fields {
+        fields::unknown_fields(&mut value, true)
+    } else {
+        Vec::new()
+    };
+    let mut config = match Config::deserialize(&value) {
+        Ok(config) => config,
+        // serde's message names neither the struct nor the hook, so find the
+        // offending key ourselves
+        Err(e) if e.to_string().contains("unknown field") => {
+            let unknown = fields::unknown_fields(&mut value, false);
+            return Err(match unknown.first() {
+                Some(field) => parse_error(field),
+                None => parse_error(&e),
+            });
+        }
+        Err(e) => return Err(parse_error(&e)),
+    };
+    normalize_config(&mut config);
+    Ok((config, dropped))
+}
+
+/// Replace the hooks of each repo that has a `manifest` with the manifest's hook
+/// definitions. When the repo lists hooks, only those ids are taken, with the
+/// listed keys overriding the manifest's.
+fn inline_manifests(value: &mut Value, base_dir: &Path) -> Result<()> {
+    let Some(repos) = value.get_mut("repos").and_then(Value::as_sequence_mut) else {
+        return Ok(());
+    };
+
+    for repo in repos.iter_mut().filter_map(Value::as_mapping_mut) {
+        let Some(manifest) = repo.get("manifest").and_then(Value::as_str) else {
+            continue;
+        };
+        let path = base_dir.join(manifest);
+
+        let content = fs::read_to_string(&path).map_err(|e| {
+            PreCommitError::Parse(format!(
+                "Failed to read hook manifest {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let manifest_hooks: Vec<Mapping> = serde_yaml::from_str(&content.replace("\r\n", "\n"))
+            .map_err(|e| {
+                PreCommitError::Parse(format!(
+                    "Failed to parse hook manifest {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+
+        let overrides: Vec<Mapping> = match repo.get("hooks") {
+            Some(hooks) => serde_yaml::from_value(hooks.clone())
+                .map_err(|e| PreCommitError::Parse(format!("Failed to parse YAML: {}", e)))?,
+            None => Vec::new(),
+        };
+
+        let hooks = if overrides.is_empty() {
+            manifest_hooks
+        } else {
+            overrides
+                .into_iter()
+                .map(|overrides| {
+                    let id = overrides.get("id").and_then(Value::as_str).ok_or_else(|| {
+                        PreCommitError::Parse("Manifest hook override is missing an id".to_string())
+                    })?;
+                    let mut hook = manifest_hooks
+                        .iter()
+                        .find(|hook| hook.get("id").and_then(Value::as_str) == Some(id))
+                        .cloned()
+                        .ok_or_else(|| {
+                            PreCommitError::HookNotFound(format!(
+                                "Hook '{}' is not defined in manifest {}",
+                                id,
+                                path.display()
+                            ))
+                        })?;
+                    hook.extend(overrides);
+                    Ok(hook)
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        repo.insert(
+            Value::from("hooks"),
+            Value::Sequence(hooks.into_iter().map(Value::Mapping).collect()),
+        );
+    }
+
+    Ok(())
 }
 
-/// Extract all hooks from a configuration
+/// Strip stray whitespace from scalar values so it never leaks into commands
+fn normalize_config(config: &mut Config) {
+    fn trim(value: &mut String) {
+        let trimmed = value.trim();
+        if trimmed.len() != value.len() {
+            *value = trimmed.to_string();
+        }
+    }
+
+    for repo in &mut config.repos {
+        trim(&mut repo.repo);
+        for hook in &mut repo.hooks {
+            trim(&mut hook.id);
+            trim(&mut hook.name);
+            trim(&mut hook.entry);
+            trim(&mut hook.language);
+            if let Some(files) = &mut hook.files {
+                trim(files);
+            }
+            if let Some(version) = &mut hook.language_version {
+                trim(version);
+            }
+            hook.depends_on.iter_mut().for_each(trim);
+        }
+    }
+}
+
+/// Generate the JSON Schema describing a pre-commit configuration file
+pub fn config_schema() -> Result<String> {
+    let schema = schemars::schema_for!(Config);
+    serde_json::to_string_pretty(&schema)
+        .map_err(|e| PreCommitError::Parse(format!("Failed to serialize schema: {}", e)))
+}
+
+/// Extract all hooks from a configuration, exactly as written apart from
+/// each being tagged with its `repo`
 pub fn extract_hooks(config: &Config) -> Vec<Hook> {
     config
         .repos
         .iter()
-        .flat_map(|repo| repo.hooks.iter().cloned())
+        .flat_map(|repo| {
+            repo.hooks.iter().map(|hook| Hook {
+                repo: repo.repo.clone(),
+                ..hook.clone()
+            })
+        })
         .collect()
 }
 
-/// Validate that all hook IDs are unique
+/// Extract all hooks with templates and the repo and config defaults applied.
+///
+/// Precedence, highest first:
+/// 1. the value set on the hook itself
+/// 2. the hook's `template`, then the templates it extends, nearest first
+///    (`entry`, `files` and `args`)
+/// 3. the hook's repo: `default_stages`, `default_timeout`, and
+///    `default_language_version` keyed by language
+/// 4. the same keys at the top level of the config
+/// 5. the built-in default: the `pre-commit` stage, no timeout, and the
+///    interpreter on `PATH`
+///
+/// The config's `fail_fast` applies to the whole run rather than to hooks, so
+/// runners read it from the [`Config`] itself.
+///
+/// Ids used in more than one repo become `repo:id`, and `depends_on`,
+/// `soft_depends_on` and `after` entries are rewritten to the ids they name
+/// (see [`Hook::is_named`]).
+///
+/// This is the single place defaults are resolved; runners should call it
+/// instead of [`extract_hooks`].
+pub fn resolve_hooks(config: &Config) -> Result<Vec<Hook>> {
+    let hooks = config
+        .repos
+        .iter()
+        .flat_map(|repo| repo.hooks.iter().map(move |hook| (repo, hook)))
+        .map(|(repo, hook)| {
+            let mut hook = Hook {
+                repo: repo.repo.clone(),
+                ..hook.clone()
+            };
+            if let Some(name) = &hook.template {
+                let template = flatten_template(config, &hook.id, name)?;
+                if hook.entry.is_empty() {
+                    hook.entry = template.entry.unwrap_or_default();
+                }
+                if hook.files.is_none() {
+                    hook.files = template.files;
+                }
+                if hook.args.is_empty() {
+                    hook.args = template.args.unwrap_or_default();
+                }
+            }
+
+            if hook.entry.is_empty() {
+                return Err(PreCommitError::Parse(format!(
+                    "Hook '{}' has no entry",
+                    hook.id
+                )));
+            }
+
+            if hook.stages.is_empty() {
+                hook.stages = repo
+                    .default_stages
+                    .clone()
+                    .or_else(|| config.default_stages.clone())
+                    .unwrap_or_else(|| vec![DEFAULT_STAGE.to_string()]);
+            }
+            if hook.timeout_secs.is_none() {
+                hook.timeout_secs = repo.default_timeout.or(config.default_timeout);
+            }
+            if hook.language_version.is_none() {
+                hook.language_version = repo
+                    .default_language_version
+                    .get(&hook.language)
+                    .or_else(|| config.default_language_version.get(&hook.language))
+                    .cloned();
+            }
+            Ok(hook)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    qualify_shared_ids(hooks)
+}
+
+/// Qualify ids that more than one repo uses as `repo:id`, then point each
+/// `depends_on`, `soft_depends_on` and `after` entry at the id it names,
+/// preferring the hook's own repo for a bare id. Entries naming no hook are
+/// left for validation to report.
+fn qualify_shared_ids(mut hooks: Vec<Hook>) -> Result<Vec<Hook>> {
+    let mut repos_by_id: HashMap<String, HashSet<String>> = HashMap::new();
+    for hook in &hooks {
+        repos_by_id
+            .entry(hook.id.clone())
+            .or_default()
+            .insert(hook.repo.clone());
+    }
+    for hook in &mut hooks {
+        if repos_by_id[&hook.id].len() > 1 {
+            hook.id = format!("{}:{}", hook.repo, hook.id);
+        }
+    }
+
+    let named = hooks.clone();
+    for hook in &mut hooks {
+        let references = hook
+            .depends_on
+            .iter_mut()
+            .chain(hook.soft_depends_on.iter_mut())
+            .chain(hook.after.iter_mut());
+        for reference in references {
+            if let Some(id) = resolve_reference(&named, reference, Some(&hook.repo))? {
+                *reference = id.to_string();
+            }
+        }
+    }
+    Ok(hooks)
+}
+
+/// The id of the hook `reference` names (see [`Hook::is_named`]), or `None`
+/// when there is none. A bare id that several repos use is an error, unless
+/// one of them is `from_repo`.
+fn resolve_reference<'a>(
+    hooks: &'a [Hook],
+    reference: &str,
+    from_repo: Option<&str>,
+) -> Result<Option<&'a str>> {
+    let named: Vec<&Hook> = hooks
+        .iter()
+        .filter(|hook| hook.is_named(reference))
+        .collect();
+    let local: Vec<&Hook> = named
+        .iter()
+        .copied()
+        .filter(|hook| Some(hook.repo.as_str()) == from_repo)
+        .collect();
+    let candidates = if local.is_empty() { named } else { local };
+
+    let mut seen = HashSet::new();
+    let ids: Vec<&str> = candidates
+        .into_iter()
+        .map(|hook| hook.id.as_str())
+        .filter(|id| seen.insert(*id))
+        .collect();
+    match ids.as_slice() {
+        [] => Ok(None),
+        [id] => Ok(Some(id)),
+        _ => Err(PreCommitError::Parse(format!(
+            "Hook id '{}' is used by several repos; write one of: {}",
+            reference,
+            ids.join(", ")
+        ))),
+    }
+}
+
+/// `references` as the ids they name, keeping those that name no hook as
+/// written so callers can report them
+fn resolve_references(hooks: &[Hook], references: &[String]) -> Result<Vec<String>> {
+    references
+        .iter()
+        .map(|reference| {
+            Ok(resolve_reference(hooks, reference, None)?
+                .map_or_else(|| reference.clone(), str::to_string))
+        })
+        .collect()
+}
+
+/// Merge a template with the templates it extends, nearer values winning
+fn flatten_template(config: &Config, hook_id: &str, name: &str) -> Result<HookTemplate> {
+    let mut merged = HookTemplate::default();
+    let mut chain: Vec<&str> = Vec::new();
+    let mut current = Some(name);
+
+    while let Some(name) = current {
+        if chain.contains(&name) {
+            chain.push(name);
+            return Err(PreCommitError::Parse(format!(
+                "Template cycle: {}",
+                chain.join(" -> ")
+            )));
+        }
+        chain.push(name);
+
+        let template = config.templates.get(name).ok_or_else(|| {
+            PreCommitError::Parse(format!(
+                "Hook '{}' uses unknown template '{}'",
+                hook_id, name
+            ))
+        })?;
+        merged.entry = merged.entry.or_else(|| template.entry.clone());
+        merged.files = merged.files.or_else(|| template.files.clone());
+        merged.args = merged.args.or_else(|| template.args.clone());
+        current = template.template.as_deref();
+    }
+
+    Ok(merged)
+}
+
+/// Select the hooks in a named profile plus everything they transitively depend on,
+/// keeping config order
+pub fn select_profile(config: &Config, hooks: &[Hook], profile: &str) -> Result<Vec<Hook>> {
+    let members = config
+        .profiles
+        .get(profile)
+        .ok_or_else(|| PreCommitError::Parse(format!("Unknown profile: {}", profile)))?;
+    let members = resolve_references(hooks, members)?;
+
+    let selected = dependency_closure(hooks, &members).map_err(|id| {
+        PreCommitError::HookNotFound(format!(
+            "Profile '{}' references non-existent hook '{}'",
+            profile, id
+        ))
+    })?;
+
+    Ok(hooks
+        .iter()
+        .filter(|hook| selected.contains(hook.id.as_str()))
+        .cloned()
+        .collect())
+}
+
+/// Select hooks by id for `run --hook-id`, keeping config order.
+///
+/// With `with_deps` everything the selected hooks transitively depend on is
+/// included too. Otherwise only the named hooks run, and their `depends_on`
+/// entries on hooks that were not selected are dropped.
+pub fn select_hooks(hooks: &[Hook], ids: &[String], with_deps: bool) -> Result<Vec<Hook>> {
+    let ids = resolve_references(hooks, ids)?;
+    let selected = dependency_closure(hooks, &ids).map_err(|id| {
+        let available: Vec<&str> = hooks.iter().map(|h| h.id.as_str()).collect();
+        PreCommitError::HookNotFound(format!(
+            "No hook with id '{}' (available: {})",
+            id,
+            available.join(", ")
+        ))
+    })?;
+    let selected: HashSet<&str> = if with_deps {
+        selected
+    } else {
+        ids.iter().map(String::as_str).collect()
+    };
+
+    Ok(hooks
+        .iter()
+        .filter(|hook| selected.contains(hook.id.as_str()))
+        .map(|hook| {
+            let mut hook = hook.clone();
+            hook.depends_on
+                .retain(|dep| selected.contains(dep.as_str()));
+            hook
+        })
+        .collect())
+}
+
+/// The given hook ids plus everything they transitively depend on, or the
+/// first id that names no hook
+fn dependency_closure<'a>(
+    hooks: &'a [Hook],
+    ids: &'a [String],
+) -> std::result::Result<HashSet<&'a str>, &'a str> {
+    let by_id: HashMap<&str, &Hook> = hooks.iter().map(|h| (h.id.as_str(), h)).collect();
+    let mut selected: HashSet<&str> = HashSet::new();
+    let mut pending: Vec<&str> = ids.iter().map(String::as_str).collect();
+
+    while let Some(id) = pending.pop() {
+        let hook = by_id.get(id).ok_or(id)?;
+        if selected.insert(id) {
+            pending.extend(hook.depends_on.iter().map(String::as_str));
+        }
+    }
+
+    Ok(selected)
+}
+
+/// Remove hooks by id or tag before planning, returning the remaining hooks and
+/// a skipped result for each removed one. A remaining hook may not depend on a
+/// removed one.
+pub fn skip_hooks(
+    hooks: &[Hook],
+    ids: &[String],
+    tags: &[String],
+) -> Result<(Vec<Hook>, Vec<HookResult>)> {
+    let ids = resolve_references(hooks, ids)?;
+    for id in &ids {
+        if !hooks.iter().any(|hook| &hook.id == id) {
+            return Err(PreCommitError::HookNotFound(format!(
+                "Cannot skip non-existent hook '{}'",
+                id
+            )));
+        }
+    }
+
+    let mut kept = Vec::new();
+    let mut skipped = Vec::new();
+    for hook in hooks {
+        if ids.contains(&hook.id) {
+            skipped.push(HookResult::skipped(&hook.id, "--skip-hook"));
+        } else if let Some(tag) = hook.tags.iter().find(|tag| tags.contains(tag)) {
+            skipped.push(HookResult::skipped(&hook.id, format!("--skip-tag {}", tag)));
+        } else {
+            kept.push(hook.clone());
+        }
+    }
+
+    for hook in &kept {
+        if let Some(dep) = skipped
+            .iter()
+            .find(|r| hook.depends_on.contains(&r.hook_id))
+        {
+            return Err(PreCommitError::Parse(format!(
+                "Hook '{}' depends on skipped hook '{}'; skip it as well",
+                hook.id, dep.hook_id
+            )));
+        }
+    }
+
+    Ok((kept, skipped))
+}
+
+/// Keep only the hooks that run in the given git stage, in config order.
+/// A kept hook may not depend on one that does not run in that stage.
+pub fn select_stage(hooks: &[Hook], stage: &str) -> Result<Vec<Hook>> {
+    let kept: Vec<Hook> = hooks
+        .iter()
+        .filter(|hook| hook.runs_in_stage(stage))
+        .cloned()
+        .collect();
+
+    for hook in &kept {
+        if let Some(dep) = hook
+            .depends_on
+            .iter()
+            .find(|dep| !kept.iter().any(|h| &h.id == *dep))
+        {
+            return Err(PreCommitError::Parse(format!(
+                "Hook '{}' depends on '{}', which does not run in the {} stage",
+                hook.id, dep, stage
+            )));
+        }
+    }
+
+    Ok(kept)
+}
+
+/// Validate that all hook IDs are unique. Run on [`resolve_hooks`] output,
+/// this only rejects an id repeated within one repo, since ids shared across
+/// repos are qualified by then.
 pub fn validate_unique_ids(hooks: &[Hook]) -> Result<()> {
     let mut seen = std::collections::HashSet::new();
     for hook in hooks {
@@ -37,12 +610,18 @@ pub fn validate_unique_ids(hooks: &[Hook]) -> Result<()> {
     Ok(())
 }
 
-/// Validate that all dependencies exist
+/// Validate that all dependencies exist and no hook depends on itself
 pub fn validate_dependencies(hooks: &[Hook]) -> Result<()> {
     let ids: std::collections::HashSet<_> = hooks.iter().map(|h| &h.id).collect();
 
     for hook in hooks {
         for dep in &hook.depends_on {
+            if dep == &hook.id {
+                return Err(PreCommitError::Parse(format!(
+                    "Hook '{}' cannot depend on itself",
+                    hook.id
+                )));
+            }
             if !ids.contains(dep) {
                 return Err(PreCommitError::HookNotFound(format!(
                     "Hook '{}' depends on non-existent hook '{}'",
@@ -50,15 +629,190 @@ pub fn validate_dependencies(hooks: &[Hook]) -> Result<()> {
                 )));
             }
         }
+        for other in &hook.after {
+            if !ids.contains(other) {
+                return Err(PreCommitError::HookNotFound(format!(
+                    "Hook '{}' is ordered after non-existent hook '{}'",
+                    hook.id, other
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Drop repeated ids from each hook's `depends_on`, keeping the first
+/// occurrence. Returns a message per duplicate so callers can warn about them.
+pub fn dedup_dependencies(hooks: &mut [Hook]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for hook in hooks {
+        let mut seen = std::collections::HashSet::new();
+        let mut duplicates = Vec::new();
+        hook.depends_on.retain(|dep| {
+            let first = seen.insert(dep.clone());
+            if !first && !duplicates.contains(dep) {
+                duplicates.push(dep.clone());
+            }
+            first
+        });
+        warnings.extend(duplicates.into_iter().map(|dep| {
+            format!(
+                "hook '{}' lists dependency '{}' more than once",
+                hook.id, dep
+            )
+        }));
+    }
+    warnings
+}
+
+/// Validate that `min_files` does not exceed `max_files`
+pub fn validate_file_counts(hooks: &[Hook]) -> Result<()> {
+    for hook in hooks {
+        if let (Some(min), Some(max)) = (hook.min_files, hook.max_files) {
+            if min > max {
+                return Err(PreCommitError::Parse(format!(
+                    "Hook '{}' has min_files ({}) greater than max_files ({})",
+                    hook.id, min, max
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate that every `files` pattern is a valid regex, since an invalid one
+/// would otherwise silently match nothing and the hook would never run
+pub fn validate_files_patterns(hooks: &[Hook]) -> Result<()> {
+    for hook in hooks {
+        if let Some(pattern) = &hook.files {
+            if let Err(e) = regex::Regex::new(pattern) {
+                return Err(PreCommitError::Parse(format!(
+                    "Hook '{}' has an invalid files pattern: {}",
+                    hook.id, e
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate that every `exclude` pattern is a valid regex, since an invalid
+/// one would otherwise silently exclude nothing
+pub fn validate_exclude_patterns(hooks: &[Hook]) -> Result<()> {
+    for hook in hooks {
+        if let Some(pattern) = &hook.exclude {
+            if let Err(e) = regex::Regex::new(pattern) {
+                return Err(PreCommitError::Parse(format!(
+                    "Hook '{}' has an invalid exclude pattern: {}",
+                    hook.id, e
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate the config-level `exclude` regex
+pub fn validate_global_exclude(config: &Config) -> Result<()> {
+    if let Some(pattern) = &config.exclude {
+        if let Err(e) = regex::Regex::new(pattern) {
+            return Err(PreCommitError::Parse(format!(
+                "Config has an invalid exclude pattern: {}",
+                e
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Validate that every `stages` entry is a known git hook stage
+pub fn validate_stages(hooks: &[Hook]) -> Result<()> {
+    for hook in hooks {
+        if let Some(stage) = hook.stages.iter().find(|s| !STAGES.contains(&s.as_str())) {
+            return Err(PreCommitError::Parse(format!(
+                "Hook '{}' has unknown stage '{}' (expected one of: {})",
+                hook.id,
+                stage,
+                STAGES.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Validate that every `types` and `types_or` entry is a known file type tag
+pub fn validate_types(hooks: &[Hook]) -> Result<()> {
+    let known = tags::all_tags();
+    for hook in hooks {
+        let mut types = hook.types.iter().chain(&hook.types_or);
+        if let Some(tag) = types.find(|tag| !known.contains(tag.as_str())) {
+            return Err(PreCommitError::Parse(format!(
+                "Hook '{}' has unknown file type '{}'",
+                hook.id, tag
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Validate that every profile member names an existing hook
+pub fn validate_profiles(config: &Config, hooks: &[Hook]) -> Result<()> {
+    let ids: HashSet<_> = hooks.iter().map(|h| &h.id).collect();
+
+    for (profile, members) in &config.profiles {
+        for member in &resolve_references(hooks, members)? {
+            if !ids.contains(member) {
+                return Err(PreCommitError::HookNotFound(format!(
+                    "Profile '{}' references non-existent hook '{}'",
+                    profile, member
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate that this build satisfies the config's `minimum_version`
+pub fn validate_minimum_version(config: &Config) -> Result<()> {
+    match &config.minimum_version {
+        Some(required) => check_minimum_version(required, env!("CARGO_PKG_VERSION")),
+        None => Ok(()),
+    }
+}
+
+fn check_minimum_version(required: &str, running: &str) -> Result<()> {
+    let required = semver::Version::parse(required.trim()).map_err(|e| {
+        PreCommitError::Parse(format!(
+            "Invalid minimum_version '{}': {} (expected a version like 1.2.3)",
+            required, e
+        ))
+    })?;
+    let running = semver::Version::parse(running).map_err(|e| {
+        PreCommitError::Parse(format!("Invalid running version '{}': {}", running, e))
+    })?;
+
+    if running < required {
+        return Err(PreCommitError::Parse(format!(
+            "This config requires pre-commit-rs {} or newer, but this is {}; please upgrade",
+            required, running
+        )));
     }
     Ok(())
 }
 
 /// Validate an entire configuration
 pub fn validate_config(config: &Config) -> Result<()> {
-    let hooks = extract_hooks(config);
+    validate_minimum_version(config)?;
+    validate_global_exclude(config)?;
+    let hooks = resolve_hooks(config)?;
     validate_unique_ids(&hooks)?;
     validate_dependencies(&hooks)?;
+    validate_file_counts(&hooks)?;
+    validate_files_patterns(&hooks)?;
+    validate_exclude_patterns(&hooks)?;
+    validate_stages(&hooks)?;
+    validate_types(&hooks)?;
+    validate_profiles(config, &hooks)?;
     Ok(())
 }
 
@@ -86,6 +840,151 @@ repos:
         assert_eq!(config.repos[0].hooks[0].id, "test-hook");
     }
 
+    #[test]
+    fn test_parse_crlf_config() {
+        let yaml = "repos:\r\n  - repo: local\r\n    hooks:\r\n      - id: hook1\r\n        name: Hook 1\r\n        entry: \"echo hello   \"\r\n        language: system\r\n        files: \"\\\\.rs$\\r\"\r\n        depends_on: [\" hook0 \"]\r\n";
+        let config = parse_config(yaml).unwrap();
+        let hook = &config.repos[0].hooks[0];
+
+        assert_eq!(hook.id, "hook1");
+        assert_eq!(hook.entry, "echo hello");
+        assert_eq!(hook.language, "system");
+        assert_eq!(hook.files.as_deref(), Some("\\.rs$"));
+        assert_eq!(hook.depends_on, vec!["hook0"]);
+    }
+
+    #[test]
+    fn test_parse_missing_config_file() {
+        let result = parse_config_file("does-not-exist/.pre-commit-config.yaml");
+        let err = result.unwrap_err();
+
+        assert!(matches!(err, PreCommitError::ConfigNotFound(_)));
+        assert!(err.to_string().contains("sample-config"));
+    }
+
+    #[test]
+    fn test_find_config_searches_upward() {
+        let root = tempfile::tempdir().unwrap();
+        let start = root.path().join("a").join("b");
+        fs::create_dir_all(&start).unwrap();
+        fs::write(root.path().join(".pre-commit-config.yml"), "repos: []\n").unwrap();
+
+        assert_eq!(
+            find_config(&start).unwrap(),
+            root.path().join(".pre-commit-config.yml")
+        );
+
+        // `.yaml` wins over `.yml` in the same directory, and nearer beats further
+        fs::write(root.path().join(".pre-commit-config.yaml"), "repos: []\n").unwrap();
+        assert_eq!(
+            find_config(&start).unwrap(),
+            root.path().join(".pre-commit-config.yaml")
+        );
+        fs::write(start.join(".pre-commit-config.yml"), "repos: []\n").unwrap();
+        assert_eq!(
+            find_config(&start).unwrap(),
+            start.join(".pre-commit-config.yml")
+        );
+    }
+
+    #[test]
+    fn test_find_config_stops_at_git_root() {
+        let root = tempfile::tempdir().unwrap();
+        let repo = root.path().join("repo");
+        let start = repo.join("src");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(&start).unwrap();
+        fs::write(root.path().join(".pre-commit-config.yaml"), "repos: []\n").unwrap();
+
+        let err = find_config(&start).unwrap_err();
+        assert!(matches!(err, PreCommitError::NoConfigFound(ref dir) if *dir == start));
+        assert!(err.to_string().contains("No .pre-commit-config.yaml"));
+    }
+
+    const EQUIVALENT_YAML: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: fmt
+        name: Format
+        entry: cargo fmt --check
+        language: system
+        files: \.rs$
+        stages: [pre-commit, pre-push]
+      - id: test
+        name: Test
+        entry: cargo test
+        language: system
+        args: [--workspace]
+        depends_on: [fmt]
+        pass_filenames: false
+        timeout_secs: 300
+"#;
+
+    const EQUIVALENT_TOML: &str = r#"
+[[repos]]
+repo = "local"
+
+[[repos.hooks]]
+id = "fmt"
+name = "Format"
+entry = "cargo fmt --check"
+language = "system"
+files = '\.rs$'
+stages = ["pre-commit", "pre-push"]
+
+[[repos.hooks]]
+id = "test"
+name = "Test"
+entry = "cargo test"
+language = "system"
+args = ["--workspace"]
+depends_on = ["fmt"]
+pass_filenames = false
+timeout_secs = 300
+"#;
+
+    #[test]
+    fn test_toml_and_yaml_configs_are_equivalent() {
+        let from_yaml = extract_hooks(&parse_config(EQUIVALENT_YAML).unwrap());
+        let from_toml = extract_hooks(&parse_config_toml(EQUIVALENT_TOML).unwrap());
+
+        assert_eq!(from_yaml.len(), 2);
+        assert_eq!(from_toml[1].timeout_secs, Some(300));
+        assert_eq!(from_toml, from_yaml);
+        validate_config(&parse_config_toml(EQUIVALENT_TOML).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_parse_config_file_dispatches_on_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("pre-commit.yml");
+        let toml = dir.path().join("pre-commit.toml");
+        fs::write(&yaml, EQUIVALENT_YAML).unwrap();
+        fs::write(&toml, EQUIVALENT_TOML).unwrap();
+
+        assert_eq!(
+            extract_hooks(&parse_config_file(&toml).unwrap()),
+            extract_hooks(&parse_config_file(&yaml).unwrap())
+        );
+
+        // TOML syntax errors name the format
+        fs::write(&toml, EQUIVALENT_YAML).unwrap();
+        let err = parse_config_file(&toml).unwrap_err();
+        assert!(err.to_string().contains("Failed to parse TOML"), "{}", err);
+
+        let json = dir.path().join("pre-commit.json");
+        fs::write(&json, "{}").unwrap();
+        let err = parse_config_file(&json).unwrap_err();
+        assert!(matches!(err, PreCommitError::Parse(_)));
+        assert!(
+            err.to_string()
+                .contains("expected a .yaml, .yml or .toml extension"),
+            "{}",
+            err
+        );
+    }
+
     #[test]
     fn test_parse_with_dependencies() {
         let yaml = r#"
@@ -119,6 +1018,7 @@ repos:
             files: None,
             pass_filenames: false,
             depends_on: vec![],
+            ..Default::default()
         };
         let hook2 = hook1.clone();
 
@@ -136,6 +1036,7 @@ repos:
             files: None,
             pass_filenames: false,
             depends_on: vec![],
+            ..Default::default()
         };
         let hook2 = Hook {
             id: "hook2".to_string(),
@@ -145,6 +1046,7 @@ repos:
             files: None,
             pass_filenames: false,
             depends_on: vec!["hook1".to_string()],
+            ..Default::default()
         };
 
         let result = validate_dependencies(&[hook1, hook2]);
@@ -161,12 +1063,124 @@ repos:
             files: None,
             pass_filenames: false,
             depends_on: vec!["nonexistent".to_string()],
+            ..Default::default()
         };
 
         let result = validate_dependencies(&[hook]);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_validate_dependencies_self() {
+        let hook = Hook {
+            id: "fmt".to_string(),
+            entry: "echo".to_string(),
+            language: "system".to_string(),
+            depends_on: vec!["fmt".to_string()],
+            ..Default::default()
+        };
+
+        let err = validate_dependencies(&[hook]).unwrap_err();
+        assert!(matches!(err, PreCommitError::Parse(_)));
+        assert!(
+            err.to_string()
+                .contains("Hook 'fmt' cannot depend on itself"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_dedup_dependencies() {
+        let hook = |id: &str, deps: &[&str]| Hook {
+            id: id.to_string(),
+            entry: "echo".to_string(),
+            language: "system".to_string(),
+            depends_on: deps.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        };
+        let mut hooks = vec![
+            hook("fmt", &[]),
+            hook("build", &["fmt"]),
+            hook("lint", &["fmt", "build", "fmt", "fmt", "build"]),
+        ];
+
+        let warnings = dedup_dependencies(&mut hooks);
+        assert_eq!(hooks[1].depends_on, vec!["fmt"]);
+        assert_eq!(hooks[2].depends_on, vec!["fmt", "build"]);
+        assert_eq!(
+            warnings,
+            vec![
+                "hook 'lint' lists dependency 'fmt' more than once",
+                "hook 'lint' lists dependency 'build' more than once",
+            ]
+        );
+        assert!(dedup_dependencies(&mut hooks).is_empty());
+    }
+
+    #[test]
+    fn test_config_schema() {
+        let schema: serde_json::Value = serde_json::from_str(&config_schema().unwrap()).unwrap();
+        assert!(schema["properties"]["repos"].is_object());
+
+        let text = schema.to_string();
+        assert!(text.contains("depends_on"));
+        assert!(text.contains("pass_filenames"));
+    }
+
+    #[test]
+    fn test_validate_file_counts() {
+        let hook = Hook {
+            id: "hook1".to_string(),
+            min_files: Some(3),
+            max_files: Some(3),
+            ..Default::default()
+        };
+        assert!(validate_file_counts(std::slice::from_ref(&hook)).is_ok());
+
+        let inverted = Hook {
+            max_files: Some(2),
+            ..hook
+        };
+        assert!(validate_file_counts(&[inverted]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_exclude_is_a_config_error() {
+        let yaml = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: lint
+        name: Lint
+        entry: lint
+        language: system
+        exclude: "^vendor/("
+"#;
+        let config = parse_config(yaml).unwrap();
+        let err = validate_config(&config).unwrap_err();
+        assert!(err.to_string().contains("invalid exclude pattern"));
+    }
+
+    #[test]
+    fn test_invalid_files_pattern_is_a_config_error() {
+        let yaml = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: lint
+        name: Lint
+        entry: lint
+        language: system
+        files: "\\.(rs$"
+"#;
+        let config = parse_config(yaml).unwrap();
+        let err = validate_config(&config).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Hook 'lint' has an invalid files pattern"));
+    }
+
     #[test]
     fn test_extract_hooks() {
         let config = Config {
@@ -181,7 +1195,9 @@ repos:
                         files: None,
                         pass_filenames: false,
                         depends_on: vec![],
+                        ..Default::default()
                     }],
+                    ..Default::default()
                 },
                 Repo {
                     repo: "local".to_string(),
@@ -193,9 +1209,12 @@ repos:
                         files: None,
                         pass_filenames: false,
                         depends_on: vec![],
+                        ..Default::default()
                     }],
+                    ..Default::default()
                 },
             ],
+            ..Default::default()
         };
 
         let hooks = extract_hooks(&config);
@@ -203,4 +1222,604 @@ repos:
         assert_eq!(hooks[0].id, "hook1");
         assert_eq!(hooks[1].id, "hook2");
     }
+
+    #[test]
+    fn test_resolve_hooks_language_version_precedence() {
+        let yaml = r#"
+default_language_version:
+  python: python3.11
+repos:
+  - repo: local
+    hooks:
+      - id: inherits
+        name: Inherits
+        entry: black
+        language: python
+      - id: pinned
+        name: Pinned
+        entry: black
+        language: python
+        language_version: python3.12
+      - id: other
+        name: Other
+        entry: echo
+        language: system
+"#;
+
+        let config = parse_config(yaml).unwrap();
+        assert_eq!(extract_hooks(&config)[0].language_version, None);
+
+        let hooks = resolve_hooks(&config).unwrap();
+        assert_eq!(hooks[0].language_version.as_deref(), Some("python3.11"));
+        assert_eq!(hooks[1].language_version.as_deref(), Some("python3.12"));
+        assert_eq!(hooks[2].language_version, None);
+    }
+
+    #[test]
+    fn test_resolve_hooks_default_precedence() {
+        let yaml = r#"
+default_stages: [pre-push]
+default_timeout: 60
+default_language_version:
+  python: python3.11
+repos:
+  - repo: pinned
+    default_stages: [commit-msg]
+    default_timeout: 30
+    default_language_version:
+      python: python3.10
+    hooks:
+      - id: own
+        name: Own
+        entry: black
+        language: python
+        stages: [post-commit]
+        timeout_secs: 5
+        language_version: python3.12
+      - id: from-repo
+        name: From repo
+        entry: black
+        language: python
+  - repo: plain
+    hooks:
+      - id: from-config
+        name: From config
+        entry: black
+        language: python
+"#;
+        let config = parse_config(yaml).unwrap();
+        let hooks = resolve_hooks(&config).unwrap();
+        let resolved = |i: usize| {
+            (
+                hooks[i].stages.clone(),
+                hooks[i].timeout_secs,
+                hooks[i].language_version.clone().unwrap_or_default(),
+            )
+        };
+
+        // The hook's own values, then its repo's defaults, then the config's
+        assert_eq!(
+            resolved(0),
+            (
+                vec!["post-commit".to_string()],
+                Some(5),
+                "python3.12".to_string()
+            )
+        );
+        assert_eq!(
+            resolved(1),
+            (
+                vec!["commit-msg".to_string()],
+                Some(30),
+                "python3.10".to_string()
+            )
+        );
+        assert_eq!(
+            resolved(2),
+            (
+                vec!["pre-push".to_string()],
+                Some(60),
+                "python3.11".to_string()
+            )
+        );
+        assert!(!config.fail_fast);
+
+        // With no defaults anywhere, the built-in ones
+        let config = parse_config(
+            "fail_fast: true\nrepos:\n  - repo: local\n    hooks:\n      \
+             - {id: a, name: A, entry: black, language: python}\n",
+        )
+        .unwrap();
+        let hooks = resolve_hooks(&config).unwrap();
+        assert_eq!(hooks[0].stages, vec!["pre-commit"]);
+        assert_eq!(hooks[0].timeout_secs, None);
+        assert_eq!(hooks[0].language_version, None);
+        assert!(config.fail_fast);
+    }
+
+    #[test]
+    fn test_unknown_default_stage_is_rejected() {
+        let config = parse_config(
+            "default_stages: [pre-comit]\nrepos:\n  - repo: local\n    hooks:\n      \
+             - {id: a, name: A, entry: \"true\", language: system}\n",
+        )
+        .unwrap();
+        let error = validate_config(&config).unwrap_err().to_string();
+        assert!(error.contains("unknown stage 'pre-comit'"), "{}", error);
+    }
+
+    const PROFILE_CONFIG: &str = r#"
+profiles:
+  quick: [lint]
+  full: [test]
+  broken: [missing]
+repos:
+  - repo: local
+    hooks:
+      - id: fmt
+        name: Format
+        entry: cargo fmt
+        language: system
+      - id: lint
+        name: Lint
+        entry: cargo clippy
+        language: system
+      - id: build
+        name: Build
+        entry: cargo build
+        language: system
+        depends_on: [fmt]
+      - id: test
+        name: Test
+        entry: cargo test
+        language: system
+        depends_on: [build]
+"#;
+
+    #[test]
+    fn test_select_profile_without_dependencies() {
+        let config = parse_config(PROFILE_CONFIG).unwrap();
+        let hooks = resolve_hooks(&config).unwrap();
+
+        let selected = select_profile(&config, &hooks, "quick").unwrap();
+        let ids: Vec<_> = selected.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, vec!["lint"]);
+    }
+
+    #[test]
+    fn test_select_profile_includes_dependency_closure() {
+        let config = parse_config(PROFILE_CONFIG).unwrap();
+        let hooks = resolve_hooks(&config).unwrap();
+
+        let selected = select_profile(&config, &hooks, "full").unwrap();
+        let ids: Vec<_> = selected.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, vec!["fmt", "build", "test"]);
+
+        assert!(select_profile(&config, &hooks, "nope").is_err());
+    }
+
+    #[test]
+    fn test_select_hooks() {
+        let config = parse_config(PROFILE_CONFIG).unwrap();
+        let hooks = resolve_hooks(&config).unwrap();
+        let ids =
+            |selected: Vec<Hook>| -> Vec<String> { selected.into_iter().map(|h| h.id).collect() };
+
+        let only = select_hooks(&hooks, &["test".to_string()], false).unwrap();
+        assert!(only[0].depends_on.is_empty());
+        assert_eq!(ids(only), vec!["test"]);
+
+        let with_deps = select_hooks(&hooks, &["test".to_string()], true).unwrap();
+        assert_eq!(with_deps[2].depends_on, vec!["build"]);
+        assert_eq!(ids(with_deps), vec!["fmt", "build", "test"]);
+
+        let err = select_hooks(&hooks, &["tset".to_string()], false).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No hook with id 'tset' (available: fmt, lint, build, test)"));
+    }
+
+    const SHARED_ID_CONFIG: &str = r#"
+profiles:
+  fast: [repo-a:fmt]
+repos:
+  - repo: repo-a
+    hooks:
+      - {id: fmt, name: Format A, entry: "true", language: system}
+      - {id: build, name: Build, entry: "true", language: system, depends_on: [fmt]}
+  - repo: repo-b
+    hooks:
+      - {id: fmt, name: Format B, entry: "true", language: system}
+      - id: lint
+        name: Lint
+        entry: "true"
+        language: system
+        depends_on: [repo-b:fmt, repo-a:build]
+        soft_depends_on: [repo-b:fmt]
+        after: [repo-a:fmt]
+"#;
+
+    #[test]
+    fn test_same_id_in_several_repos() {
+        let config = parse_config(SHARED_ID_CONFIG).unwrap();
+        validate_config(&config).unwrap();
+        let hooks = resolve_hooks(&config).unwrap();
+
+        let ids: Vec<_> = hooks.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, vec!["repo-a:fmt", "build", "repo-b:fmt", "lint"]);
+        assert_eq!(hooks[0].repo, "repo-a");
+        assert_eq!(hooks[0].bare_id(), "fmt");
+        assert_eq!(hooks[1].bare_id(), "build");
+
+        // A bare id means the hook in the same repo; qualified references
+        // name the qualified id, or the plain one when the id is unique
+        assert_eq!(hooks[1].depends_on, vec!["repo-a:fmt"]);
+        assert_eq!(hooks[3].depends_on, vec!["repo-b:fmt", "build"]);
+        assert_eq!(hooks[3].soft_depends_on, vec!["repo-b:fmt"]);
+        assert_eq!(hooks[3].after, vec!["repo-a:fmt"]);
+
+        let selected = select_hooks(&hooks, &["repo-b:lint".to_string()], true).unwrap();
+        let ids: Vec<_> = selected.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, vec!["repo-a:fmt", "build", "repo-b:fmt", "lint"]);
+
+        let fast = select_profile(&config, &hooks, "fast").unwrap();
+        assert_eq!(fast.len(), 1);
+        assert_eq!(fast[0].name, "Format A");
+    }
+
+    #[test]
+    fn test_ambiguous_bare_reference() {
+        let yaml = format!(
+            "{}  - repo: repo-c\n    hooks:\n      - {{id: docs, name: Docs, entry: \"true\", language: system, depends_on: [fmt]}}\n",
+            SHARED_ID_CONFIG
+        );
+        let config = parse_config(&yaml).unwrap();
+        let err = resolve_hooks(&config).unwrap_err();
+        assert!(
+            err.to_string().contains(
+                "Hook id 'fmt' is used by several repos; write one of: repo-a:fmt, repo-b:fmt"
+            ),
+            "{}",
+            err
+        );
+
+        let config = parse_config(SHARED_ID_CONFIG).unwrap();
+        let hooks = resolve_hooks(&config).unwrap();
+        assert!(skip_hooks(&hooks, &["fmt".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn test_same_id_twice_in_one_repo() {
+        let config = parse_config(
+            r#"
+repos:
+  - repo: local
+    hooks:
+      - {id: fmt, name: Format, entry: "true", language: system}
+      - {id: fmt, name: Format again, entry: "true", language: system}
+"#,
+        )
+        .unwrap();
+        let err = validate_config(&config).unwrap_err();
+        assert!(
+            err.to_string().contains("Duplicate hook ID: fmt"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_validate_profiles() {
+        let config = parse_config(PROFILE_CONFIG).unwrap();
+        assert!(matches!(
+            validate_config(&config),
+            Err(PreCommitError::HookNotFound(_))
+        ));
+    }
+
+    const TEMPLATE_CONFIG: &str = r#"
+templates:
+  cargo:
+    entry: cargo
+    files: \.rs$
+    args: [--workspace]
+  clippy:
+    template: cargo
+    entry: cargo clippy
+repos:
+  - repo: local
+    hooks:
+      - id: check
+        name: Check
+        language: system
+        template: cargo
+      - id: lint
+        name: Lint
+        language: system
+        template: clippy
+      - id: docs
+        name: Docs
+        entry: cargo doc
+        files: \.md$
+        args: [--no-deps]
+        language: system
+        template: clippy
+"#;
+
+    #[test]
+    fn test_templates_inherit_and_override() {
+        let config = parse_config(TEMPLATE_CONFIG).unwrap();
+        let hooks = resolve_hooks(&config).unwrap();
+
+        // Inherited wholesale from the template
+        assert_eq!(hooks[0].entry, "cargo");
+        assert_eq!(hooks[0].files.as_deref(), Some("\\.rs$"));
+        assert_eq!(hooks[0].args, vec!["--workspace"]);
+
+        // Nearest template wins, the rest comes from the template it extends
+        assert_eq!(hooks[1].entry, "cargo clippy");
+        assert_eq!(hooks[1].files.as_deref(), Some("\\.rs$"));
+        assert_eq!(hooks[1].args, vec!["--workspace"]);
+
+        // Fields set on the hook win over every template
+        assert_eq!(hooks[2].entry, "cargo doc");
+        assert_eq!(hooks[2].files.as_deref(), Some("\\.md$"));
+        assert_eq!(hooks[2].args, vec!["--no-deps"]);
+    }
+
+    #[test]
+    fn test_template_errors() {
+        let unknown = TEMPLATE_CONFIG.replace(
+            "template: cargo\n      - id: lint",
+            "template: nope\n      - id: lint",
+        );
+        let err = resolve_hooks(&parse_config(&unknown).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("unknown template 'nope'"));
+
+        let cyclic = TEMPLATE_CONFIG.replace(
+            "    entry: cargo\n    files",
+            "    template: clippy\n    files",
+        );
+        let err = resolve_hooks(&parse_config(&cyclic).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("Template cycle"));
+    }
+
+    #[test]
+    fn test_hooks_from_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(".pre-commit-hooks.yaml"),
+            "- id: fmt\n  name: Format\n  entry: cargo fmt\n  language: system\n\
+             - id: lint\n  name: Lint\n  entry: cargo clippy\n  language: system\n",
+        )
+        .unwrap();
+        let config_path = dir.path().join(".pre-commit-config.yaml");
+
+        // Without hook entries every manifest hook is used
+        fs::write(
+            &config_path,
+            "repos:\n  - repo: local\n    manifest: .pre-commit-hooks.yaml\n",
+        )
+        .unwrap();
+        let hooks = extract_hooks(&parse_config_file(&config_path).unwrap());
+        let ids: Vec<_> = hooks.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, vec!["fmt", "lint"]);
+
+        // Listed entries select hooks and override their fields
+        fs::write(
+            &config_path,
+            "repos:\n  - repo: local\n    manifest: .pre-commit-hooks.yaml\n    hooks:\n\
+             \x20     - id: lint\n        entry: cargo clippy -- -D warnings\n",
+        )
+        .unwrap();
+        let hooks = extract_hooks(&parse_config_file(&config_path).unwrap());
+        assert_eq!(hooks.len(), 1);
+        assert_eq!(hooks[0].name, "Lint");
+        assert_eq!(hooks[0].entry, "cargo clippy -- -D warnings");
+
+        fs::write(
+            &config_path,
+            "repos:\n  - repo: local\n    manifest: .pre-commit-hooks.yaml\n    hooks:\n\
+             \x20     - id: missing\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            parse_config_file(&config_path),
+            Err(PreCommitError::HookNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_skip_leaf_hook() {
+        let config = parse_config(PROFILE_CONFIG).unwrap();
+        let hooks = resolve_hooks(&config).unwrap();
+
+        let (kept, skipped) = skip_hooks(&hooks, &["test".to_string()], &[]).unwrap();
+        let ids: Vec<_> = kept.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, vec!["fmt", "lint", "build"]);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].hook_id, "test");
+        assert_eq!(skipped[0].skip_reason.as_deref(), Some("--skip-hook"));
+    }
+
+    #[test]
+    fn test_skip_depended_upon_hook() {
+        let config = parse_config(PROFILE_CONFIG).unwrap();
+        let mut hooks = resolve_hooks(&config).unwrap();
+        hooks[2].tags = vec!["slow".to_string()];
+
+        // `test` depends on `build`, so skipping only `build` is an error
+        let err = skip_hooks(&hooks, &[], &["slow".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("depends on skipped hook 'build'"));
+
+        // ...unless its dependents are skipped too
+        let (kept, skipped) =
+            skip_hooks(&hooks, &["test".to_string()], &["slow".to_string()]).unwrap();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(skipped[0].skip_reason.as_deref(), Some("--skip-tag slow"));
+
+        assert!(skip_hooks(&hooks, &["nope".to_string()], &[]).is_err());
+    }
+
+    const STAGES_CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: fmt
+        name: Format
+        entry: cargo fmt
+        language: system
+      - id: test
+        name: Test
+        entry: cargo test
+        language: system
+        stages: [pre-push]
+      - id: build
+        name: Build
+        entry: cargo build
+        language: system
+        stages: [pre-commit, pre-push]
+"#;
+
+    #[test]
+    fn test_select_stage() {
+        let config = parse_config(STAGES_CONFIG).unwrap();
+        validate_config(&config).unwrap();
+        let hooks = resolve_hooks(&config).unwrap();
+        assert_eq!(hooks[0].stages, vec!["pre-commit"]);
+
+        let ids = |stage: &str| -> Vec<String> {
+            select_stage(&hooks, stage)
+                .unwrap()
+                .into_iter()
+                .map(|h| h.id)
+                .collect()
+        };
+        assert_eq!(ids("pre-commit"), vec!["fmt", "build"]);
+        assert_eq!(ids("pre-push"), vec!["test", "build"]);
+        assert!(ids("commit-msg").is_empty());
+
+        // `build` runs on pre-push, but `fmt` does not
+        let mut hooks = hooks;
+        hooks[2].depends_on = vec!["fmt".to_string()];
+        let err = select_stage(&hooks, "pre-push").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("does not run in the pre-push stage"));
+    }
+
+    #[test]
+    fn test_unknown_stage_is_a_config_error() {
+        let config = parse_config(&STAGES_CONFIG.replace("[pre-push]", "[pre-pushh]")).unwrap();
+        let err = validate_config(&config).unwrap_err();
+        assert!(err.to_string().contains("unknown stage 'pre-pushh'"));
+    }
+
+    #[test]
+    fn test_unknown_file_type_is_a_config_error() {
+        let yaml = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: lint
+        name: Lint
+        entry: lint
+        language: system
+        types: [file]
+        types_or: [python, pyhton]
+"#;
+        let err = validate_config(&parse_config(yaml).unwrap()).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Hook 'lint' has unknown file type 'pyhton'"));
+    }
+
+    #[test]
+    fn test_global_exclude() {
+        let config = parse_config("exclude: ^vendor/\nrepos: []\n").unwrap();
+        assert_eq!(config.exclude.as_deref(), Some("^vendor/"));
+        validate_config(&config).unwrap();
+
+        let config = parse_config("exclude: ^vendor/(\nrepos: []\n").unwrap();
+        let err = validate_config(&config).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Config has an invalid exclude pattern"));
+    }
+
+    fn config_requiring(version: &str) -> Config {
+        parse_config(&format!(
+            "minimum_version: \"{}\"\nrepos:\n  - repo: local\n    hooks: []\n",
+            version
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_minimum_version_satisfied() {
+        assert!(validate_config(&config_requiring(env!("CARGO_PKG_VERSION"))).is_ok());
+        assert!(check_minimum_version("1.2.3", "1.10.0").is_ok());
+    }
+
+    #[test]
+    fn test_minimum_version_unsatisfied() {
+        let err = validate_config(&config_requiring("999.0.0")).unwrap_err();
+        assert!(matches!(err, PreCommitError::Parse(_)));
+        assert!(err
+            .to_string()
+            .contains("requires pre-commit-rs 999.0.0 or newer"));
+
+        let err = check_minimum_version("0.3.0", "0.2.9").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("but this is 0.2.9; please upgrade"));
+    }
+
+    #[test]
+    fn test_malformed_minimum_version() {
+        let err = validate_config(&config_requiring("1.x")).unwrap_err();
+        assert!(matches!(err, PreCommitError::Parse(_)));
+        assert!(err.to_string().contains("Invalid minimum_version '1.x'"));
+    }
+
+    const MISSPELLED_CONFIG: &str = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: lint
+        name: Lint
+        entry: lint
+        language: system
+        file: \.rs$
+"#;
+
+    #[test]
+    fn test_unknown_field_is_a_parse_error() {
+        let err = parse_config(MISSPELLED_CONFIG).unwrap_err();
+        assert!(matches!(err, PreCommitError::Parse(_)));
+        assert_eq!(
+            err.to_string(),
+            "Parse error: Failed to parse YAML: unknown field `file` in hook 'lint'; \
+             did you mean `files`?"
+        );
+
+        let err = parse_config_toml("minimum_versoin = \"0.1.0\"\nrepos = []\n").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown field `minimum_versoin` in the config"));
+    }
+
+    #[test]
+    fn test_lenient_parse_drops_unknown_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, MISSPELLED_CONFIG).unwrap();
+
+        assert!(parse_config_file(&path).is_err());
+        let (config, dropped) = parse_config_file_lenient(&path).unwrap();
+        assert_eq!(extract_hooks(&config)[0].files, None);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].field, "file");
+        assert_eq!(dropped[0].location, "hook 'lint'");
+    }
 }